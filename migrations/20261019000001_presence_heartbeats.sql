@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS presence_heartbeats (
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    node_id TEXT NOT NULL,
+    last_heartbeat TEXT NOT NULL,
+    PRIMARY KEY (user_id, node_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_presence_heartbeats_last_heartbeat ON presence_heartbeats(last_heartbeat);
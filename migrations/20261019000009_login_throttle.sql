@@ -0,0 +1,21 @@
+-- Penghitung login gagal per username (`user:<nama>`) dan per IP (`ip:<alamat>`).
+CREATE TABLE IF NOT EXISTS login_throttles (
+    key TEXT PRIMARY KEY,
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_failure_at TEXT NOT NULL,
+    locked_until TEXT
+);
+
+-- Catatan audit setiap kali username atau IP dikunci sementara.
+CREATE TABLE IF NOT EXISTS login_lockouts (
+    id TEXT PRIMARY KEY,
+    scope TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    failures INTEGER NOT NULL,
+    ip_address TEXT,
+    user_agent TEXT,
+    locked_until TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_lockouts_subject ON login_lockouts(scope, subject);
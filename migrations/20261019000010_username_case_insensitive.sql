@@ -0,0 +1,3 @@
+-- Username unik tanpa membedakan huruf besar/kecil. Pastikan tidak ada username yang
+-- hanya berbeda kapitalisasi sebelum menjalankan migrasi ini.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_username_lower ON users(LOWER(username));
@@ -0,0 +1,6 @@
+-- Peran pengguna (user, moderator, admin) dan status blokir akun.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'user';
+ALTER TABLE users ADD COLUMN IF NOT EXISTS banned_at TEXT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS ban_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_users_role ON users(role);
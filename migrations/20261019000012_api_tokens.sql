@@ -0,0 +1,22 @@
+-- Akun bot dimiliki seorang pengguna dan hanya bisa dipakai lewat personal access token.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_bot INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS bot_owner_id TEXT REFERENCES users(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_users_bot_owner_id ON users(bot_owner_id);
+
+-- Personal access token. `user_id` adalah akun yang diwakili token (pengguna itu sendiri
+-- atau bot miliknya), `owner_id` adalah pengguna yang mengelolanya.
+CREATE TABLE IF NOT EXISTS personal_access_tokens (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    owner_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT NOT NULL,
+    expires_at TEXT,
+    last_used_at TEXT,
+    created_at TEXT NOT NULL,
+    revoked_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_personal_access_tokens_owner_id ON personal_access_tokens(owner_id);
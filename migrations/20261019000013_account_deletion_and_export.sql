@@ -0,0 +1,41 @@
+-- Pengguna pengganti untuk pesan milik akun yang sudah dihapus. Username-nya tidak lolos
+-- validasi registrasi dan akunnya diblokir, jadi tidak bisa dipakai login.
+INSERT INTO users (id, username, password_hash, banned_at, ban_reason, last_seen, created_at, updated_at)
+VALUES (
+    '00000000-0000-0000-0000-000000000000',
+    '[deleted]',
+    '$2b$12$QhqBBaZrB3uGVSVUXp5pOeaNcYELw05CeBAz85Lx1Tpyv639M.wFm',
+    '2026-10-19T00:00:00Z',
+    'Pengguna pengganti untuk akun yang dihapus',
+    '2026-10-19T00:00:00Z',
+    '2026-10-19T00:00:00Z',
+    '2026-10-19T00:00:00Z'
+)
+ON CONFLICT (id) DO NOTHING;
+
+-- Permintaan hapus akun yang menunggu masa tenggang. `message_mode` menentukan pesan yang
+-- dikirim pengguna dianonimkan (`anonymize`) atau ikut dihapus (`delete`).
+CREATE TABLE IF NOT EXISTS account_deletions (
+    user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    message_mode TEXT NOT NULL,
+    requested_at TEXT NOT NULL,
+    scheduled_for TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_deletions_scheduled_for ON account_deletions(scheduled_for);
+
+-- Arsip ekspor data pribadi yang dibuat oleh background job.
+CREATE TABLE IF NOT EXISTS data_exports (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    file_path TEXT,
+    error TEXT,
+    requested_at TEXT NOT NULL,
+    started_at TEXT,
+    completed_at TEXT,
+    expires_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_exports_user_id ON data_exports(user_id);
+CREATE INDEX IF NOT EXISTS idx_data_exports_status ON data_exports(status);
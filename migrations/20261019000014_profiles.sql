@@ -0,0 +1,18 @@
+-- Profil yang bisa diubah pengguna. Semua opsional; klien memakai `username` jika
+-- `display_name` kosong.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS display_name TEXT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS bio TEXT;
+-- Nama zona waktu IANA, mis. `Asia/Jakarta`.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS timezone TEXT;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS pronouns TEXT;
+-- Waktu avatar terakhir diganti, dipakai sebagai versi di URL avatar. `NULL` jika
+-- pengguna tidak punya avatar.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS avatar_updated_at TEXT;
+
+-- Avatar yang sudah diperkecil server, satu per pengguna.
+CREATE TABLE IF NOT EXISTS user_avatars (
+    user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    content_type TEXT NOT NULL,
+    data BYTEA NOT NULL,
+    updated_at TEXT NOT NULL
+);
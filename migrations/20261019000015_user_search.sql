@@ -0,0 +1,8 @@
+-- Pencarian direktori pengguna. Indeks trigram dipakai untuk pencocokan fuzzy (`%`) dan
+-- `LIKE` pada username dan nama tampilan; indeks `text_pattern_ops` mempercepat
+-- pencarian awalan username berapa pun panjangnya.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_users_username_pattern ON users(LOWER(username) text_pattern_ops);
+CREATE INDEX IF NOT EXISTS idx_users_username_trgm ON users USING GIN (LOWER(username) gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_users_display_name_trgm ON users USING GIN (LOWER(display_name) gin_trgm_ops);
@@ -0,0 +1,11 @@
+-- Blokir dan bisukan. `kind` berisi `block` (pesan pribadi dari `target_id` ditolak dan
+-- pesan publiknya disembunyikan) atau `mute` (pesan tetap tampil tanpa notifikasi).
+CREATE TABLE IF NOT EXISTS user_blocks (
+    user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    target_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, target_id, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_blocks_target_id ON user_blocks(target_id);
@@ -0,0 +1,17 @@
+-- Siapa yang boleh mengirim pesan pribadi dan melihat status online/`last_seen` pengguna:
+-- `everyone`, `contacts`, atau `nobody`.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS privacy TEXT NOT NULL DEFAULT 'everyone';
+
+-- Permintaan kontak dan kontak yang sudah diterima. Satu pasangan pengguna hanya punya
+-- satu baris, apa pun arahnya. Permintaan yang ditolak dihapus.
+CREATE TABLE IF NOT EXISTS contacts (
+    requester_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    addressee_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    accepted_at TEXT,
+    PRIMARY KEY (requester_id, addressee_id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_contacts_pair ON contacts(LEAST(requester_id, addressee_id), GREATEST(requester_id, addressee_id));
+CREATE INDEX IF NOT EXISTS idx_contacts_addressee_id ON contacts(addressee_id);
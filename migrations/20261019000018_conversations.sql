@@ -0,0 +1,12 @@
+-- Penanda baca channel publik: pesan publik setelah `last_read_at` dihitung belum dibaca.
+-- Pesan pribadi memakai `messages.is_read`.
+CREATE TABLE IF NOT EXISTS public_reads (
+    user_id TEXT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    last_read_at TEXT NOT NULL
+);
+
+-- Daftar percakapan: pesan terakhir per lawan bicara dan jumlah pesan belum dibaca.
+CREATE INDEX IF NOT EXISTS idx_messages_sender_receiver ON messages(sender_id, receiver_id, created_at DESC) WHERE receiver_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_messages_receiver_sender ON messages(receiver_id, sender_id, created_at DESC) WHERE receiver_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_messages_receiver_unread ON messages(receiver_id, is_read, sender_id);
+CREATE INDEX IF NOT EXISTS idx_messages_public_created_at ON messages(created_at DESC) WHERE receiver_id IS NULL;
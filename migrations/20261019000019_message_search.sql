@@ -0,0 +1,6 @@
+-- Pencarian full-text pesan. Konfigurasi `simple` tidak melakukan stemming sehingga cocok
+-- untuk pesan campuran bahasa Indonesia dan Inggris.
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_messages_search_vector ON messages USING GIN (search_vector);
@@ -0,0 +1,46 @@
+-- Kunci percakapan untuk kebijakan retensi dan legal hold: `public` untuk channel publik,
+-- atau `direct:<id terkecil>:<id terbesar>` untuk pesan pribadi.
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS conversation_key TEXT GENERATED ALWAYS AS (
+    CASE
+        WHEN receiver_id IS NULL THEN 'public'
+        ELSE 'direct:' || LEAST(sender_id, receiver_id)::text || ':' || GREATEST(sender_id, receiver_id)::text
+    END
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_messages_conversation_key ON messages(conversation_key, created_at);
+
+-- Masa retensi per percakapan, menggantikan `MESSAGE_RETENTION_DAYS`. `retention_days`
+-- kosong berarti pesan di percakapan ini disimpan selamanya.
+CREATE TABLE IF NOT EXISTS retention_policies (
+    conversation_key TEXT PRIMARY KEY,
+    retention_days INTEGER,
+    updated_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Legal hold: pesan milik pengguna (dikirim atau diterima) atau di percakapan tertentu tidak
+-- dihapus job retensi sampai hold dilepas. `user_id` sengaja tanpa foreign key supaya hold tidak
+-- ikut terhapus bersama akunnya.
+CREATE TABLE IF NOT EXISTS legal_holds (
+    id TEXT PRIMARY KEY,
+    user_id TEXT,
+    conversation_key TEXT,
+    reason TEXT NOT NULL,
+    created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL,
+    released_at TEXT,
+    CHECK ((user_id IS NULL) <> (conversation_key IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_legal_holds_active_user_id ON legal_holds(user_id) WHERE released_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_legal_holds_active_conversation_key ON legal_holds(conversation_key) WHERE released_at IS NULL;
+
+-- Pesan yang diarsipkan job retensi dengan `MESSAGE_RETENTION_ACTION=archive`.
+CREATE TABLE IF NOT EXISTS archived_messages (
+    id TEXT PRIMARY KEY,
+    sender_id TEXT NOT NULL,
+    receiver_id TEXT,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    archived_at TEXT NOT NULL
+);
@@ -14,7 +14,8 @@ pub fn create_app(app_state: AppState) -> Router {
     // Auth routes
     let auth_routes = Router::new()
         .route("/register", post(auth::register))
-        .route("/login", post(auth::login));
+        .route("/login", post(auth::login))
+        .route("/refresh", post(auth::refresh));
 
     // User routes
     let user_routes = Router::new()
@@ -0,0 +1,43 @@
+use crate::config::get_env_var;
+
+pub const DEFAULT_ACCOUNT_DELETION_GRACE_PERIOD: i64 = 14 * 86400;
+pub const DEFAULT_DATA_EXPORT_DIR: &str = "exports";
+pub const DEFAULT_DATA_EXPORT_TTL: i64 = 7 * 86400;
+pub const DEFAULT_REAUTH_MAX_AGE: i64 = 10 * 60;
+
+/// Jeda sebelum akun yang diminta dihapus benar-benar dihapus (detik). Selama jeda ini
+/// penghapusan masih bisa dibatalkan.
+pub fn get_account_deletion_grace_period() -> i64 {
+    get_env_var(
+        "ACCOUNT_DELETION_GRACE_PERIOD",
+        &DEFAULT_ACCOUNT_DELETION_GRACE_PERIOD.to_string(),
+    )
+    .parse()
+    .ok()
+    .filter(|secs| *secs >= 0)
+    .unwrap_or(DEFAULT_ACCOUNT_DELETION_GRACE_PERIOD)
+}
+
+/// Umur maksimal sesi login (detik) yang masih dianggap login ulang, sehingga aksi
+/// sensitif seperti hapus akun tidak perlu password atau kode 2FA.
+pub fn get_reauth_max_age() -> i64 {
+    get_env_var("REAUTH_MAX_AGE", &DEFAULT_REAUTH_MAX_AGE.to_string())
+        .parse()
+        .ok()
+        .filter(|secs| *secs >= 0)
+        .unwrap_or(DEFAULT_REAUTH_MAX_AGE)
+}
+
+/// Direktori tempat arsip ekspor data pengguna disimpan.
+pub fn get_data_export_dir() -> String {
+    get_env_var("DATA_EXPORT_DIR", DEFAULT_DATA_EXPORT_DIR)
+}
+
+/// Lama arsip ekspor bisa diunduh sebelum dihapus (detik).
+pub fn get_data_export_ttl() -> i64 {
+    get_env_var("DATA_EXPORT_TTL", &DEFAULT_DATA_EXPORT_TTL.to_string())
+        .parse()
+        .ok()
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_DATA_EXPORT_TTL)
+}
@@ -1,21 +1,78 @@
-use anyhow::Result;
-use chrono::{Duration, Utc};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use std::fs;
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+};
+use once_cell::sync::Lazy;
+use rsa::RsaPublicKey;
+use rsa::pkcs1::DecodeRsaPublicKey;
+use rsa::pkcs8::{DecodePublicKey, Document, ObjectIdentifier, SubjectPublicKeyInfoRef};
+use rsa::traits::PublicKeyParts;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::config::get_jwt_expiration;
-use crate::config::get_jwt_secret;
+use crate::config::{
+    DEFAULT_JWT_SECRET, get_jwt_algorithm, get_jwt_expiration, get_jwt_key_id,
+    get_jwt_private_key_file, get_jwt_public_keys, get_jwt_secret, is_production,
+};
+use crate::models::role::Role;
+
+const RSA_ENCRYPTION_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const ED25519_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Kunci JWT dimuat sekali dari environment. Error disimpan agar bisa dilaporkan saat startup.
+static KEY_RING: Lazy<Result<KeyRing, String>> =
+    Lazy::new(|| KeyRing::load(&KeyConfig::from_env()).map_err(|e| format!("{:#}", e)));
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,
     pub iat: i64,
     pub exp: i64,
     pub iss: String,
+    /// ID unik token, dipakai untuk pencabutan. Kosong pada token lama.
+    #[serde(default)]
+    pub jti: String,
+    /// ID sesi login yang menerbitkan token ini.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    /// Peran pengguna saat token diterbitkan. Hanya informasi untuk klien; otorisasi
+    /// di server memakai peran terbaru dari database.
+    #[serde(default)]
+    pub role: Role,
+}
+
+impl Claims {
+    pub fn user_id(&self) -> Result<Uuid> {
+        Ok(Uuid::parse_str(&self.sub)?)
+    }
+
+    pub fn issued_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.iat, 0).unwrap_or_default()
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.exp, 0).unwrap_or_default()
+    }
 }
 
 pub fn generate_token(user_id: Uuid) -> Result<String> {
+    encode_token(user_id, None, Role::User)
+}
+
+/// Buat access token yang terikat ke sebuah sesi login.
+pub fn generate_session_token(user_id: Uuid, session_id: Uuid, role: Role) -> Result<String> {
+    encode_token(user_id, Some(session_id), role)
+}
+
+fn encode_token(user_id: Uuid, session_id: Option<Uuid>, role: Role) -> Result<String> {
     let now = Utc::now();
     let expiration = get_jwt_expiration();
     let claims = Claims {
@@ -23,23 +80,311 @@ pub fn generate_token(user_id: Uuid) -> Result<String> {
         iat: now.timestamp(),
         exp: (now + Duration::seconds(expiration)).timestamp(),
         iss: "chat_app".to_string(),
+        jti: Uuid::new_v4().to_string(),
+        sid: session_id,
+        role,
     };
 
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(get_jwt_secret().as_bytes()),
-    )?;
-
-    Ok(token)
+    key_ring()?.sign(&claims)
 }
 
 pub fn validate_token(token: &str) -> Result<Claims> {
-    let decoded = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(get_jwt_secret().as_bytes()),
-        &Validation::new(Algorithm::HS256),
-    )?;
+    key_ring()?.verify(token)
+}
+
+/// Muat dan periksa kunci JWT. Dipanggil saat startup agar konfigurasi yang salah
+/// menggagalkan proses, bukan request pertama.
+pub fn init_keys() -> Result<()> {
+    let ring = key_ring()?;
+    match &ring.signing_kid {
+        Some(kid) => tracing::info!("JWT signing with {:?} (kid {})", ring.algorithm, kid),
+        None => tracing::info!("JWT signing with {:?}", ring.algorithm),
+    }
+
+    Ok(())
+}
+
+/// Kunci publik verifikasi dalam format JWKS. Kosong jika memakai HS256.
+pub fn jwks() -> Result<JwkSet> {
+    Ok(key_ring()?.jwks())
+}
+
+fn key_ring() -> Result<&'static KeyRing> {
+    KEY_RING
+        .as_ref()
+        .map_err(|e| anyhow!("Konfigurasi kunci JWT tidak valid: {}", e))
+}
+
+/// Konfigurasi mentah kunci JWT, biasanya dibaca dari environment.
+#[derive(Debug, Clone, Default)]
+pub struct KeyConfig {
+    pub algorithm: String,
+    pub secret: String,
+    pub private_key_file: String,
+    pub key_id: String,
+    /// Daftar `kid:path.pem` dipisah koma.
+    pub public_keys: String,
+    pub production: bool,
+}
+
+impl KeyConfig {
+    pub fn from_env() -> Self {
+        Self {
+            algorithm: get_jwt_algorithm(),
+            secret: get_jwt_secret(),
+            private_key_file: get_jwt_private_key_file(),
+            key_id: get_jwt_key_id(),
+            public_keys: get_jwt_public_keys(),
+            production: is_production(),
+        }
+    }
+}
+
+struct VerificationKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    decoding_key: DecodingKey,
+    /// `None` untuk kunci rahasia HS256 yang tidak boleh dipublikasikan.
+    jwk: Option<Jwk>,
+}
+
+/// Kunci penandatangan aktif beserta semua kunci yang masih diterima saat verifikasi.
+///
+/// Rotasi kunci: tambahkan kunci publik baru ke `JWT_PUBLIC_KEYS`, pindahkan
+/// `JWT_KEY_ID`/`JWT_PRIVATE_KEY_FILE` ke kunci baru, lalu hapus kunci lama setelah
+/// semua access token yang ditandatanganinya kedaluwarsa.
+pub struct KeyRing {
+    algorithm: Algorithm,
+    signing_kid: Option<String>,
+    encoding_key: EncodingKey,
+    verification_keys: Vec<VerificationKey>,
+}
+
+impl KeyRing {
+    pub fn load(config: &KeyConfig) -> Result<Self> {
+        let algorithm: Algorithm = config
+            .algorithm
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("JWT_ALGORITHM tidak dikenal: {}", config.algorithm))?;
+        let kid = Some(config.key_id.trim().to_string()).filter(|kid| !kid.is_empty());
+
+        let ring = match algorithm {
+            Algorithm::HS256 => Self::load_secret(config, kid)?,
+            Algorithm::RS256 | Algorithm::EdDSA => Self::load_key_pair(config, algorithm, kid)?,
+            other => bail!("JWT_ALGORITHM {:?} tidak didukung", other),
+        };
+
+        // Pastikan kunci privat cocok dengan kunci publik yang dipakai verifikasi.
+        let probe = Claims {
+            sub: Uuid::nil().to_string(),
+            iat: Utc::now().timestamp(),
+            exp: (Utc::now() + Duration::minutes(1)).timestamp(),
+            iss: "chat_app".to_string(),
+            jti: String::new(),
+            sid: None,
+            role: Role::User,
+        };
+        ring.verify(&ring.sign(&probe)?)
+            .context("Kunci privat JWT tidak cocok dengan kunci publiknya")?;
+
+        Ok(ring)
+    }
+
+    fn load_secret(config: &KeyConfig, kid: Option<String>) -> Result<Self> {
+        if config.secret.is_empty() {
+            bail!("JWT_SECRET wajib diisi untuk HS256");
+        }
+        if config.production && config.secret == DEFAULT_JWT_SECRET {
+            bail!("JWT_SECRET default tidak boleh dipakai di production");
+        }
+
+        Ok(Self {
+            algorithm: Algorithm::HS256,
+            signing_kid: kid.clone(),
+            encoding_key: EncodingKey::from_secret(config.secret.as_bytes()),
+            verification_keys: vec![VerificationKey {
+                kid,
+                algorithm: Algorithm::HS256,
+                decoding_key: DecodingKey::from_secret(config.secret.as_bytes()),
+                jwk: None,
+            }],
+        })
+    }
+
+    fn load_key_pair(
+        config: &KeyConfig,
+        algorithm: Algorithm,
+        kid: Option<String>,
+    ) -> Result<Self> {
+        let kid = kid.ok_or_else(|| anyhow!("JWT_KEY_ID wajib diisi untuk {:?}", algorithm))?;
+        if config.private_key_file.trim().is_empty() {
+            bail!("JWT_PRIVATE_KEY_FILE wajib diisi untuk {:?}", algorithm);
+        }
+
+        let private_pem = fs::read(config.private_key_file.trim())
+            .with_context(|| format!("Gagal membaca {}", config.private_key_file))?;
+        let encoding_key = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(&private_pem)?,
+            _ => EncodingKey::from_ed_pem(&private_pem)?,
+        };
+
+        let mut verification_keys = Vec::new();
+        for entry in config
+            .public_keys
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            let (key_id, path) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Format JWT_PUBLIC_KEYS harus kid:path, bukan {}", entry))?;
+            let key = load_public_key(key_id.trim(), path.trim())?;
+            if verification_keys
+                .iter()
+                .any(|existing: &VerificationKey| existing.kid == key.kid)
+            {
+                bail!("kid {} muncul lebih dari sekali di JWT_PUBLIC_KEYS", key_id);
+            }
+            verification_keys.push(key);
+        }
+
+        match verification_keys
+            .iter()
+            .find(|key| key.kid.as_deref() == Some(kid.as_str()))
+        {
+            Some(key) if key.algorithm == algorithm => {}
+            Some(key) => bail!(
+                "Kunci publik {} memakai {:?}, bukan {:?}",
+                kid,
+                key.algorithm,
+                algorithm
+            ),
+            None => bail!(
+                "Kunci publik untuk JWT_KEY_ID {} tidak ada di JWT_PUBLIC_KEYS",
+                kid
+            ),
+        }
+
+        Ok(Self {
+            algorithm,
+            signing_kid: Some(kid),
+            encoding_key,
+            verification_keys,
+        })
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    pub fn sign(&self, claims: &Claims) -> Result<String> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = self.signing_kid.clone();
+
+        Ok(encode(&header, claims, &self.encoding_key)?)
+    }
+
+    /// Verifikasi token dengan kunci sesuai `kid` di header. Token tanpa `kid` hanya
+    /// diperiksa dengan kunci penandatangan aktif.
+    pub fn verify(&self, token: &str) -> Result<Claims> {
+        let header = decode_header(token)?;
+        let kid = header.kid.as_ref().or(self.signing_kid.as_ref());
+        let key = self
+            .verification_keys
+            .iter()
+            .find(|key| key.kid.as_ref() == kid)
+            .ok_or_else(|| anyhow!("Kunci JWT tidak dikenal"))?;
+
+        // Algoritma dikunci per kunci, sehingga header `alg` tidak bisa dipakai untuk
+        // menukar algoritma verifikasi.
+        let decoded = decode::<Claims>(token, &key.decoding_key, &Validation::new(key.algorithm))?;
+
+        Ok(decoded.claims)
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .verification_keys
+                .iter()
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Baca kunci publik PEM (SPKI atau PKCS#1) dan tentukan algoritmanya dari jenis kunci.
+fn load_public_key(kid: &str, path: &str) -> Result<VerificationKey> {
+    if kid.is_empty() {
+        bail!("kid kosong untuk kunci publik {}", path);
+    }
+
+    let pem = fs::read_to_string(path).with_context(|| format!("Gagal membaca {}", path))?;
+    let (label, document) =
+        Document::from_pem(&pem).map_err(|e| anyhow!("PEM tidak valid di {}: {}", path, e))?;
+
+    let (algorithm, parameters) = match label {
+        "RSA PUBLIC KEY" => {
+            let key = RsaPublicKey::from_pkcs1_der(document.as_bytes())
+                .map_err(|e| anyhow!("Kunci RSA tidak valid di {}: {}", path, e))?;
+            (Algorithm::RS256, rsa_parameters(&key))
+        }
+        "PUBLIC KEY" => {
+            let info = SubjectPublicKeyInfoRef::try_from(document.as_bytes())
+                .map_err(|e| anyhow!("Kunci publik tidak valid di {}: {}", path, e))?;
+
+            if info.algorithm.oid == RSA_ENCRYPTION_OID {
+                let key = RsaPublicKey::from_public_key_der(document.as_bytes())
+                    .map_err(|e| anyhow!("Kunci RSA tidak valid di {}: {}", path, e))?;
+                (Algorithm::RS256, rsa_parameters(&key))
+            } else if info.algorithm.oid == ED25519_OID {
+                let x = info
+                    .subject_public_key
+                    .as_bytes()
+                    .filter(|bytes| bytes.len() == 32)
+                    .ok_or_else(|| anyhow!("Kunci Ed25519 tidak valid di {}", path))?;
+                let parameters = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: URL_SAFE_NO_PAD.encode(x),
+                });
+                (Algorithm::EdDSA, parameters)
+            } else {
+                bail!(
+                    "Jenis kunci di {} tidak didukung (hanya RSA dan Ed25519)",
+                    path
+                );
+            }
+        }
+        other => bail!("Label PEM {} di {} bukan kunci publik", other, path),
+    };
+
+    let jwk = Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(match algorithm {
+                Algorithm::RS256 => KeyAlgorithm::RS256,
+                _ => KeyAlgorithm::EdDSA,
+            }),
+            key_id: Some(kid.to_string()),
+            ..Default::default()
+        },
+        algorithm: parameters,
+    };
+
+    Ok(VerificationKey {
+        kid: Some(kid.to_string()),
+        algorithm,
+        decoding_key: DecodingKey::from_jwk(&jwk)?,
+        jwk: Some(jwk),
+    })
+}
 
-    Ok(decoded.claims)
+fn rsa_parameters(key: &RsaPublicKey) -> AlgorithmParameters {
+    AlgorithmParameters::RSA(RSAKeyParameters {
+        key_type: RSAKeyType::RSA,
+        n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+        e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+    })
 }
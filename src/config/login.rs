@@ -0,0 +1,59 @@
+use crate::config::get_env_var;
+
+pub const DEFAULT_LOGIN_MAX_FAILURES: i32 = 10;
+pub const DEFAULT_LOGIN_IP_MAX_FAILURES: i32 = 50;
+pub const DEFAULT_LOGIN_LOCKOUT_DURATION: i64 = 900;
+pub const DEFAULT_LOGIN_BACKOFF_AFTER: i32 = 3;
+pub const DEFAULT_LOGIN_BACKOFF_BASE: i64 = 1;
+
+/// Aturan pembatasan percobaan login yang gagal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginPolicy {
+    /// Jumlah kegagalan per username sebelum dikunci sementara.
+    pub max_failures: i32,
+    /// Jumlah kegagalan per alamat IP sebelum dikunci sementara.
+    pub ip_max_failures: i32,
+    /// Lama penguncian (detik). Kegagalan yang lebih lama dari ini tidak dihitung lagi.
+    pub lockout_duration: i64,
+    /// Jumlah kegagalan sebelum jeda eksponensial mulai berlaku.
+    pub backoff_after: i32,
+    /// Jeda (detik) untuk kegagalan pertama setelah `backoff_after`, lalu berlipat dua.
+    pub backoff_base: i64,
+}
+
+impl Default for LoginPolicy {
+    fn default() -> Self {
+        Self {
+            max_failures: DEFAULT_LOGIN_MAX_FAILURES,
+            ip_max_failures: DEFAULT_LOGIN_IP_MAX_FAILURES,
+            lockout_duration: DEFAULT_LOGIN_LOCKOUT_DURATION,
+            backoff_after: DEFAULT_LOGIN_BACKOFF_AFTER,
+            backoff_base: DEFAULT_LOGIN_BACKOFF_BASE,
+        }
+    }
+}
+
+impl LoginPolicy {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            max_failures: positive("LOGIN_MAX_FAILURES", defaults.max_failures),
+            ip_max_failures: positive("LOGIN_IP_MAX_FAILURES", defaults.ip_max_failures),
+            lockout_duration: positive("LOGIN_LOCKOUT_DURATION", defaults.lockout_duration),
+            backoff_after: positive("LOGIN_BACKOFF_AFTER", defaults.backoff_after),
+            backoff_base: positive("LOGIN_BACKOFF_BASE", defaults.backoff_base),
+        }
+    }
+}
+
+fn positive<T>(key: &str, default: T) -> T
+where
+    T: std::str::FromStr + PartialOrd + Default + ToString,
+{
+    get_env_var(key, &default.to_string())
+        .parse()
+        .ok()
+        .filter(|value| *value > T::default())
+        .unwrap_or(default)
+}
@@ -0,0 +1,128 @@
+use std::str::FromStr;
+
+use crate::config::get_env_var;
+
+pub const DEFAULT_SMTP_PORT: u16 = 587;
+pub const DEFAULT_MAIL_FROM: &str = "Chat App <no-reply@localhost>";
+pub const DEFAULT_PASSWORD_RESET_TTL: i64 = 3600;
+pub const DEFAULT_EMAIL_VERIFICATION_TTL: i64 = 86400;
+
+/// Cara pengiriman email.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MailerKind {
+    /// Hanya menulis isi email ke log, untuk development.
+    Log,
+    Smtp,
+}
+
+impl FromStr for MailerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "log" => Ok(Self::Log),
+            "smtp" => Ok(Self::Smtp),
+            other => Err(format!("Mailer tidak dikenal: {}", other)),
+        }
+    }
+}
+
+/// Enkripsi koneksi SMTP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Tanpa TLS, hanya untuk SMTP sink lokal.
+    None,
+    StartTls,
+    Tls,
+}
+
+impl FromStr for SmtpSecurity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "starttls" => Ok(Self::StartTls),
+            "tls" => Ok(Self::Tls),
+            other => Err(format!("SMTP_SECURITY tidak dikenal: {}", other)),
+        }
+    }
+}
+
+/// Konfigurasi SMTP, dibaca dari environment.
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub security: SmtpSecurity,
+}
+
+impl SmtpConfig {
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Self {
+            host: get_env_var("SMTP_HOST", "localhost"),
+            port: get_env_var("SMTP_PORT", &DEFAULT_SMTP_PORT.to_string())
+                .parse()
+                .unwrap_or(DEFAULT_SMTP_PORT),
+            username: get_env_var("SMTP_USERNAME", ""),
+            password: get_env_var("SMTP_PASSWORD", ""),
+            security: get_env_var("SMTP_SECURITY", "starttls").parse()?,
+        })
+    }
+}
+
+/// `MAILER`: `log` (default) atau `smtp`.
+pub fn get_mailer_kind() -> Result<MailerKind, String> {
+    get_env_var("MAILER", "log").parse()
+}
+
+/// Alamat pengirim, boleh dalam format `Nama <alamat>`.
+pub fn get_mail_from() -> String {
+    get_env_var("MAIL_FROM", DEFAULT_MAIL_FROM)
+}
+
+/// Alamat halaman frontend untuk reset password; token ditambahkan sebagai `?token=`.
+pub fn get_password_reset_url() -> String {
+    get_env_var("PASSWORD_RESET_URL", "http://localhost:3000/reset-password")
+}
+
+/// Masa berlaku token reset password (detik).
+pub fn get_password_reset_ttl() -> i64 {
+    get_env_var(
+        "PASSWORD_RESET_TTL",
+        &DEFAULT_PASSWORD_RESET_TTL.to_string(),
+    )
+    .parse()
+    .ok()
+    .filter(|secs| *secs > 0)
+    .unwrap_or(DEFAULT_PASSWORD_RESET_TTL)
+}
+
+/// Alamat halaman frontend untuk verifikasi email; token ditambahkan sebagai `?token=`.
+pub fn get_email_verification_url() -> String {
+    get_env_var(
+        "EMAIL_VERIFICATION_URL",
+        "http://localhost:3000/verify-email",
+    )
+}
+
+/// Masa berlaku token verifikasi email (detik).
+pub fn get_email_verification_ttl() -> i64 {
+    get_env_var(
+        "EMAIL_VERIFICATION_TTL",
+        &DEFAULT_EMAIL_VERIFICATION_TTL.to_string(),
+    )
+    .parse()
+    .ok()
+    .filter(|secs| *secs > 0)
+    .unwrap_or(DEFAULT_EMAIL_VERIFICATION_TTL)
+}
+
+/// Wajibkan email terverifikasi sebelum mengirim pesan ke channel publik.
+pub fn get_require_verified_email() -> bool {
+    get_env_var("REQUIRE_VERIFIED_EMAIL", "false")
+        .parse()
+        .unwrap_or(false)
+}
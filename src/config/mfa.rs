@@ -0,0 +1,29 @@
+use crate::config::get_env_var;
+
+pub const DEFAULT_MFA_ISSUER: &str = "Chat App";
+pub const DEFAULT_MFA_CHALLENGE_TTL: i64 = 300;
+pub const DEFAULT_MFA_MAX_ATTEMPTS: i32 = 5;
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Nama penerbit yang ditampilkan aplikasi authenticator.
+pub fn get_mfa_issuer() -> String {
+    get_env_var("MFA_ISSUER", DEFAULT_MFA_ISSUER)
+}
+
+/// Masa berlaku token tantangan MFA setelah password benar (detik).
+pub fn get_mfa_challenge_ttl() -> i64 {
+    get_env_var("MFA_CHALLENGE_TTL", &DEFAULT_MFA_CHALLENGE_TTL.to_string())
+        .parse()
+        .ok()
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_MFA_CHALLENGE_TTL)
+}
+
+/// Jumlah percobaan kode yang salah sebelum tantangan MFA dibatalkan.
+pub fn get_mfa_max_attempts() -> i32 {
+    get_env_var("MFA_MAX_ATTEMPTS", &DEFAULT_MFA_MAX_ATTEMPTS.to_string())
+        .parse()
+        .ok()
+        .filter(|attempts| *attempts > 0)
+        .unwrap_or(DEFAULT_MFA_MAX_ATTEMPTS)
+}
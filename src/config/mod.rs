@@ -1,11 +1,25 @@
+pub mod account;
 pub mod database;
 pub mod jwt;
+pub mod login;
+pub mod mail;
+pub mod mfa;
+pub mod oidc;
+pub mod password;
+pub mod presence;
+pub mod profile;
+pub mod retention;
+pub mod user_cache;
+pub mod websocket;
 
 use dotenv::dotenv;
 use std::env;
 
 pub const DEFAULT_PORT: u16 = 8080;
 pub const DEFAULT_HOST: &str = "127.0.0.1";
+pub const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
+pub const DEFAULT_JWT_SECRET: &str = "super_secret_key_for_jwt_please_change_in_production";
+pub const DEFAULT_JWT_ALGORITHM: &str = "HS256";
 
 pub fn get_env_var(key: &str, default: &str) -> String {
     dotenv().ok();
@@ -30,14 +44,67 @@ pub fn get_database_url() -> String {
 }
 
 pub fn get_jwt_secret() -> String {
-    get_env_var(
-        "JWT_SECRET",
-        "super_secret_key_for_jwt_please_change_in_production",
-    )
+    get_env_var("JWT_SECRET", DEFAULT_JWT_SECRET)
+}
+
+/// Algoritma tanda tangan access token: `HS256`, `RS256`, atau `EdDSA`.
+pub fn get_jwt_algorithm() -> String {
+    get_env_var("JWT_ALGORITHM", DEFAULT_JWT_ALGORITHM)
+}
+
+/// Path file PEM kunci privat untuk `RS256`/`EdDSA`.
+pub fn get_jwt_private_key_file() -> String {
+    get_env_var("JWT_PRIVATE_KEY_FILE", "")
+}
+
+/// `kid` kunci yang sedang dipakai untuk menandatangani token.
+pub fn get_jwt_key_id() -> String {
+    get_env_var("JWT_KEY_ID", "")
+}
+
+/// Kunci publik untuk verifikasi, format `kid:path.pem` dipisah koma.
+pub fn get_jwt_public_keys() -> String {
+    get_env_var("JWT_PUBLIC_KEYS", "")
+}
+
+/// Nama environment aplikasi (`development`, `production`, ...).
+pub fn get_app_env() -> String {
+    get_env_var("APP_ENV", "development")
 }
 
+pub fn is_production() -> bool {
+    get_app_env().eq_ignore_ascii_case("production")
+}
+
+/// Username yang dijadikan admin selama belum ada admin sama sekali.
+pub fn get_bootstrap_admin() -> Option<String> {
+    let username = get_env_var("BOOTSTRAP_ADMIN_USERNAME", "");
+    let username = username.trim();
+    (!username.is_empty()).then(|| username.to_string())
+}
+
+/// Masa berlaku access token (detik). Dibuat pendek karena dapat diperbarui lewat refresh token.
 pub fn get_jwt_expiration() -> i64 {
-    get_env_var("JWT_EXPIRATION", "86400")
+    get_env_var("JWT_EXPIRATION", "900").parse().unwrap_or(900)
+}
+
+/// Masa berlaku refresh token (detik).
+pub fn get_refresh_token_expiration() -> i64 {
+    get_env_var("REFRESH_TOKEN_EXPIRATION", "2592000")
+        .parse()
+        .unwrap_or(2592000)
+}
+
+/// Batas waktu (detik) untuk menutup koneksi dan membersihkan state saat shutdown.
+pub fn get_shutdown_timeout() -> u64 {
+    get_env_var("SHUTDOWN_TIMEOUT", &DEFAULT_SHUTDOWN_TIMEOUT.to_string())
+        .parse()
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT)
+}
+
+/// Percayai header `X-Forwarded-For` untuk alamat IP klien (aktifkan jika di belakang proxy).
+pub fn get_trust_proxy_headers() -> bool {
+    get_env_var("TRUST_PROXY_HEADERS", "false")
         .parse()
-        .unwrap_or(86400)
+        .unwrap_or(false)
 }
@@ -0,0 +1,67 @@
+use tracing::warn;
+
+use crate::config::get_env_var;
+
+pub const DEFAULT_OIDC_SCOPES: &str = "openid email profile";
+pub const DEFAULT_OIDC_STATE_TTL: i64 = 600;
+
+/// Konfigurasi satu penyedia OpenID Connect.
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    /// Nama pendek yang dipakai di URL, mis. `corp` untuk `/auth/oidc/corp/...`.
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    /// Kosong untuk public client (hanya PKCE).
+    pub client_secret: Option<String>,
+    /// Halaman frontend yang menerima `code` dan `state` dari penyedia.
+    pub redirect_uri: String,
+    pub scopes: String,
+}
+
+/// Penyedia dari `OIDC_PROVIDERS` (dipisah koma). Setiap penyedia dikonfigurasi lewat
+/// `OIDC_<NAMA>_ISSUER`, `_CLIENT_ID`, `_CLIENT_SECRET`, `_REDIRECT_URI`, dan `_SCOPES`.
+pub fn get_oidc_providers() -> Vec<OidcProviderConfig> {
+    get_env_var("OIDC_PROVIDERS", "")
+        .split(',')
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| {
+            let prefix = format!("OIDC_{}", name.to_ascii_uppercase().replace('-', "_"));
+            let var = |key: &str| get_env_var(&format!("{}_{}", prefix, key), "");
+
+            let config = OidcProviderConfig {
+                issuer: var("ISSUER").trim_end_matches('/').to_string(),
+                client_id: var("CLIENT_ID"),
+                client_secret: Some(var("CLIENT_SECRET")).filter(|secret| !secret.is_empty()),
+                redirect_uri: var("REDIRECT_URI"),
+                scopes: Some(var("SCOPES"))
+                    .filter(|scopes| !scopes.is_empty())
+                    .unwrap_or_else(|| DEFAULT_OIDC_SCOPES.to_string()),
+                name,
+            };
+
+            if config.issuer.is_empty()
+                || config.client_id.is_empty()
+                || config.redirect_uri.is_empty()
+            {
+                warn!(
+                    "OIDC provider {} ignored: {}_ISSUER, {}_CLIENT_ID and {}_REDIRECT_URI are required",
+                    config.name, prefix, prefix, prefix
+                );
+                return None;
+            }
+
+            Some(config)
+        })
+        .collect()
+}
+
+/// Batas waktu (detik) antara memulai login SSO dan menyelesaikannya.
+pub fn get_oidc_state_ttl() -> i64 {
+    get_env_var("OIDC_STATE_TTL", &DEFAULT_OIDC_STATE_TTL.to_string())
+        .parse()
+        .ok()
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_OIDC_STATE_TTL)
+}
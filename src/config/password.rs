@@ -0,0 +1,44 @@
+use crate::config::get_env_var;
+
+pub const DEFAULT_PASSWORD_MIN_LENGTH: usize = 8;
+/// bcrypt hanya memakai 72 byte pertama; password yang lebih panjang ditolak.
+pub const MAX_PASSWORD_BYTES: usize = 72;
+
+/// Aturan password untuk registrasi dan penggantian password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// File daftar password yang pernah bocor, satu password per baris.
+    pub breached_list_file: Option<String>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: DEFAULT_PASSWORD_MIN_LENGTH,
+            breached_list_file: None,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn from_env() -> Self {
+        let min_length = get_env_var(
+            "PASSWORD_MIN_LENGTH",
+            &DEFAULT_PASSWORD_MIN_LENGTH.to_string(),
+        )
+        .parse()
+        .ok()
+        .filter(|len| (1..=MAX_PASSWORD_BYTES).contains(len))
+        .unwrap_or(DEFAULT_PASSWORD_MIN_LENGTH);
+
+        let breached_list_file = Some(get_env_var("PASSWORD_BREACHED_LIST_FILE", ""))
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty());
+
+        Self {
+            min_length,
+            breached_list_file,
+        }
+    }
+}
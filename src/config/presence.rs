@@ -0,0 +1,33 @@
+use crate::config::get_env_var;
+
+pub const DEFAULT_PRESENCE_RECONCILE_INTERVAL: u64 = 60;
+pub const DEFAULT_PRESENCE_HEARTBEAT_TTL: u64 = 180;
+
+/// Interval (detik) rekonsiliasi status online dengan registry koneksi.
+pub fn get_presence_reconcile_interval() -> u64 {
+    get_env_var(
+        "PRESENCE_RECONCILE_INTERVAL",
+        &DEFAULT_PRESENCE_RECONCILE_INTERVAL.to_string(),
+    )
+    .parse()
+    .ok()
+    .filter(|secs| *secs > 0)
+    .unwrap_or(DEFAULT_PRESENCE_RECONCILE_INTERVAL)
+}
+
+/// Umur maksimum (detik) heartbeat sebelum pengguna dianggap offline pada mode multi-node.
+pub fn get_presence_heartbeat_ttl() -> u64 {
+    get_env_var(
+        "PRESENCE_HEARTBEAT_TTL",
+        &DEFAULT_PRESENCE_HEARTBEAT_TTL.to_string(),
+    )
+    .parse()
+    .unwrap_or(DEFAULT_PRESENCE_HEARTBEAT_TTL)
+}
+
+/// ID node untuk mode multi-node. Jika kosong, server berjalan sebagai satu-satunya node.
+pub fn get_presence_node_id() -> Option<String> {
+    let node_id = get_env_var("PRESENCE_NODE_ID", "");
+    let node_id = node_id.trim();
+    (!node_id.is_empty()).then(|| node_id.to_string())
+}
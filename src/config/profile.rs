@@ -0,0 +1,25 @@
+use crate::config::get_env_var;
+
+pub const DEFAULT_AVATAR_MAX_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+pub const DEFAULT_AVATAR_SIZE: u32 = 256;
+
+/// Ukuran maksimum file avatar yang diunggah (byte), sebelum diperkecil.
+pub fn get_avatar_max_upload_bytes() -> usize {
+    get_env_var(
+        "AVATAR_MAX_UPLOAD_BYTES",
+        &DEFAULT_AVATAR_MAX_UPLOAD_BYTES.to_string(),
+    )
+    .parse()
+    .ok()
+    .filter(|bytes| *bytes > 0)
+    .unwrap_or(DEFAULT_AVATAR_MAX_UPLOAD_BYTES)
+}
+
+/// Sisi avatar persegi yang disimpan server (piksel), antara 32 dan 1024.
+pub fn get_avatar_size() -> u32 {
+    get_env_var("AVATAR_SIZE", &DEFAULT_AVATAR_SIZE.to_string())
+        .parse()
+        .ok()
+        .filter(|size| (32..=1024).contains(size))
+        .unwrap_or(DEFAULT_AVATAR_SIZE)
+}
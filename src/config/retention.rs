@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::config::get_env_var;
+
+pub const DEFAULT_RETENTION_PURGE_INTERVAL: u64 = 3600;
+pub const DEFAULT_RETENTION_BATCH_SIZE: i64 = 1000;
+
+/// Apa yang dilakukan pada pesan yang melewati masa retensinya.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionAction {
+    /// Pesan dihapus permanen.
+    Delete,
+    /// Pesan dipindahkan ke tabel `archived_messages` dan tidak lagi tampil di aplikasi.
+    Archive,
+}
+
+impl FromStr for RetentionAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "delete" => Ok(RetentionAction::Delete),
+            "archive" => Ok(RetentionAction::Archive),
+            other => Err(format!("Aksi retensi tidak dikenal: {other}")),
+        }
+    }
+}
+
+/// Masa retensi default pesan (hari) untuk percakapan tanpa kebijakan sendiri. `0` atau
+/// kosong berarti pesan disimpan selamanya.
+pub fn get_message_retention_days() -> Option<i32> {
+    get_env_var("MESSAGE_RETENTION_DAYS", "0")
+        .parse()
+        .ok()
+        .filter(|days| *days > 0)
+}
+
+/// `MESSAGE_RETENTION_ACTION`: `delete` (default) atau `archive`. Nilai lain ditolak saat
+/// startup, bukan jatuh ke `delete` yang menghapus pesan permanen.
+pub fn get_message_retention_action() -> Result<RetentionAction, String> {
+    get_env_var("MESSAGE_RETENTION_ACTION", "delete").parse()
+}
+
+/// Jeda antar putaran job retensi (detik).
+pub fn get_retention_purge_interval() -> u64 {
+    get_env_var(
+        "RETENTION_PURGE_INTERVAL",
+        &DEFAULT_RETENTION_PURGE_INTERVAL.to_string(),
+    )
+    .parse()
+    .ok()
+    .filter(|secs| *secs > 0)
+    .unwrap_or(DEFAULT_RETENTION_PURGE_INTERVAL)
+}
+
+/// Jumlah pesan yang dihapus per transaksi, supaya tabel tidak terkunci terlalu lama.
+pub fn get_retention_batch_size() -> i64 {
+    get_env_var(
+        "RETENTION_BATCH_SIZE",
+        &DEFAULT_RETENTION_BATCH_SIZE.to_string(),
+    )
+    .parse()
+    .ok()
+    .filter(|size| *size > 0)
+    .unwrap_or(DEFAULT_RETENTION_BATCH_SIZE)
+}
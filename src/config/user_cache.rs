@@ -0,0 +1,24 @@
+use crate::config::get_env_var;
+
+pub const DEFAULT_USER_CACHE_CAPACITY: usize = 10_000;
+pub const DEFAULT_USER_CACHE_TTL: u64 = 300;
+
+/// Jumlah maksimal pengguna di cache; yang paling lama tidak dipakai dibuang lebih dulu.
+pub fn get_user_cache_capacity() -> usize {
+    get_env_var(
+        "USER_CACHE_CAPACITY",
+        &DEFAULT_USER_CACHE_CAPACITY.to_string(),
+    )
+    .parse()
+    .ok()
+    .filter(|capacity| *capacity > 0)
+    .unwrap_or(DEFAULT_USER_CACHE_CAPACITY)
+}
+
+/// Umur maksimal entri cache (detik). Membatasi data basi di node lain, karena invalidasi
+/// hanya berlaku di node yang mengubah pengguna.
+pub fn get_user_cache_ttl() -> u64 {
+    get_env_var("USER_CACHE_TTL", &DEFAULT_USER_CACHE_TTL.to_string())
+        .parse()
+        .unwrap_or(DEFAULT_USER_CACHE_TTL)
+}
@@ -0,0 +1,53 @@
+use std::str::FromStr;
+
+use crate::config::get_env_var;
+
+pub const DEFAULT_WS_BUFFER_SIZE: usize = 100;
+pub const DEFAULT_WS_AUTH_TIMEOUT: u64 = 10;
+
+/// Kebijakan ketika buffer keluar milik sebuah koneksi WebSocket penuh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowConsumerPolicy {
+    /// Frame baru dibuang, koneksi tetap hidup.
+    Drop,
+    /// Koneksi ditutup dengan close frame.
+    Disconnect,
+    /// Frame dibuang sampai buffer kosong, lalu klien diminta melakukan resume.
+    Resume,
+}
+
+impl FromStr for SlowConsumerPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "drop" => Ok(SlowConsumerPolicy::Drop),
+            "disconnect" => Ok(SlowConsumerPolicy::Disconnect),
+            "resume" => Ok(SlowConsumerPolicy::Resume),
+            other => Err(format!("Kebijakan slow consumer tidak dikenal: {other}")),
+        }
+    }
+}
+
+pub fn get_ws_buffer_size() -> usize {
+    get_env_var("WS_BUFFER_SIZE", &DEFAULT_WS_BUFFER_SIZE.to_string())
+        .parse()
+        .ok()
+        .filter(|size| *size > 0)
+        .unwrap_or(DEFAULT_WS_BUFFER_SIZE)
+}
+
+pub fn get_slow_consumer_policy() -> SlowConsumerPolicy {
+    get_env_var("WS_SLOW_CONSUMER_POLICY", "drop")
+        .parse()
+        .unwrap_or(SlowConsumerPolicy::Drop)
+}
+
+/// Batas waktu (detik) bagi klien untuk mengirim frame `Authenticate`.
+pub fn get_ws_auth_timeout() -> u64 {
+    get_env_var("WS_AUTH_TIMEOUT", &DEFAULT_WS_AUTH_TIMEOUT.to_string())
+        .parse()
+        .ok()
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_WS_AUTH_TIMEOUT)
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use tracing::{info, warn};
+
+use crate::{
+    config::account::{get_account_deletion_grace_period, get_reauth_max_age},
+    handlers::mfa::verify_second_factor,
+    jobs,
+    mail::{Email, send_in_background},
+    middleware::auth::{AppState, AuthUser, Credential},
+    models::{
+        account_deletion::{AccountDeletion, AccountDeletionResponse, DeleteAccountRequest},
+        data_export::DataExport,
+        errors::AppError,
+        mfa::UserTotp,
+        session::Session,
+        user::User,
+    },
+};
+
+/// Jadwalkan penghapusan akun setelah masa tenggang. Akun tetap bisa dipakai sampai
+/// jadwal tersebut dan penghapusan bisa dibatalkan lewat `DELETE /users/me/deletion`.
+pub async fn delete_account(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(credential): Extension<Credential>,
+    auth_user: AuthUser,
+    Json(request): Json<DeleteAccountRequest>,
+) -> Result<(StatusCode, Json<AccountDeletionResponse>), AppError> {
+    let user = auth_user.0;
+    if user.is_bot {
+        return Err(AppError::Forbidden(
+            "Bot dihapus bersama akun pemiliknya".to_string(),
+        ));
+    }
+
+    confirm_identity(&state, &user, &credential, &request).await?;
+
+    let deletion = AccountDeletion::new(
+        user.id,
+        request.messages,
+        get_account_deletion_grace_period(),
+    )
+    .schedule(&state.db)
+    .await?;
+    info!(
+        "User {} scheduled account deletion for {}",
+        user.username, deletion.scheduled_for
+    );
+
+    if let Some(email) = user.email.clone().filter(|_| user.is_email_verified()) {
+        send_in_background(
+            state.mailer.clone(),
+            Email {
+                to: email,
+                subject: "Akun Chat App akan dihapus".to_string(),
+                body: format!(
+                    "Halo {},\n\nAkun Anda dijadwalkan untuk dihapus pada {}. \
+                     Sampai waktu tersebut Anda masih bisa login dan membatalkan penghapusan.\n\n\
+                     Jika Anda tidak meminta penghapusan akun, segera login dan ganti password Anda.\n",
+                    user.username,
+                    deletion.scheduled_for.format("%Y-%m-%d %H:%M UTC")
+                ),
+            },
+        );
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(deletion.into_response())))
+}
+
+/// Password dan kode 2FA yang dikirim harus benar. Tanpa keduanya, sesi token harus baru saja
+/// login supaya akun SSO yang tidak punya password tetap bisa dihapus.
+async fn confirm_identity(
+    state: &AppState,
+    user: &User,
+    credential: &Credential,
+    request: &DeleteAccountRequest,
+) -> Result<(), AppError> {
+    if let Some(password) = request.password.as_deref().filter(|p| !p.is_empty()) {
+        if !user.verify_password(password)? {
+            return Err(AppError::Auth("Password salah".to_string()));
+        }
+        return Ok(());
+    }
+
+    if let Some(code) = request.code.as_deref().filter(|c| !c.is_empty()) {
+        let totp = UserTotp::find(user.id, &state.db)
+            .await?
+            .filter(UserTotp::is_enabled)
+            .ok_or_else(|| AppError::Validation("2FA belum aktif".to_string()))?;
+        if !verify_second_factor(&totp, code, &state.db).await? {
+            return Err(AppError::Auth("Kode 2FA salah".to_string()));
+        }
+        return Ok(());
+    }
+
+    let recent = match credential {
+        Credential::Session(claims) => match claims.sid {
+            Some(session_id) => Session::find_active(session_id, user.id, &state.db)
+                .await?
+                .is_some_and(|session| session.is_recent_login(get_reauth_max_age(), Utc::now())),
+            None => false,
+        },
+        Credential::ApiToken(_) => false,
+    };
+    if !recent {
+        return Err(AppError::Auth(
+            "Masukkan password atau kode 2FA, atau login ulang".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn cancel_account_deletion(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, AppError> {
+    let user = auth_user.0;
+    if !AccountDeletion::cancel(user.id, &state.db).await? {
+        return Err(AppError::NotFound(
+            "Tidak ada penghapusan akun yang dijadwalkan".to_string(),
+        ));
+    }
+
+    info!("User {} cancelled account deletion", user.username);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Unduh arsip ekspor data pribadi. Jika arsip belum ada, ekspor baru dimasukkan ke antrean
+/// background job dan endpoint membalas `202` dengan statusnya; klien mengulang request
+/// yang sama sampai arsip siap.
+pub async fn export_data(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Response, AppError> {
+    let user = auth_user.0;
+    let latest = DataExport::find_latest(user.id, &state.db).await?;
+
+    if let Some(export) = &latest
+        && export.is_downloadable()
+        && let Some(path) = &export.file_path
+    {
+        match tokio::fs::read(path).await {
+            Ok(archive) => {
+                let filename = format!(
+                    "chat-export-{}-{}.zip",
+                    user.username,
+                    export.requested_at.format("%Y%m%d")
+                );
+                return Ok((
+                    [
+                        (header::CONTENT_TYPE, "application/zip".to_string()),
+                        (
+                            header::CONTENT_DISPOSITION,
+                            format!("attachment; filename=\"{}\"", filename),
+                        ),
+                    ],
+                    archive,
+                )
+                    .into_response());
+            }
+            Err(e) => warn!("Data export file {} unreadable: {}", path, e),
+        }
+    }
+
+    let export = match latest.filter(DataExport::is_in_progress) {
+        Some(export) => export,
+        None => {
+            let export = DataExport::new(user.id).create(&state.db).await?;
+            info!("User {} requested a data export", user.username);
+            jobs::accounts::wake();
+            export
+        }
+    };
+
+    Ok((StatusCode::ACCEPTED, Json(export.into_response())).into_response())
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query},
+    http::StatusCode,
+};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    handlers::{
+        auth::end_all_sessions,
+        websocket::{broadcast_message_deleted, disconnect_user},
+    },
+    middleware::auth::{Admin, AppState, Moderator, RequireRole},
+    models::{
+        admin::{AdminUserResponse, BanUserRequest, ListUsersQuery, UpdateRoleRequest},
+        errors::AppError,
+        message::Message,
+        role::Role,
+        user::User,
+        user_cache,
+    },
+};
+
+pub async fn list_users(
+    Extension(state): Extension<Arc<AppState>>,
+    _: RequireRole<Admin>,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<Json<Vec<AdminUserResponse>>, AppError> {
+    let (limit, offset) = query.page();
+    let users = User::list(limit, offset, &state.db).await?;
+
+    Ok(Json(
+        users.into_iter().map(AdminUserResponse::from).collect(),
+    ))
+}
+
+/// Ubah peran pengguna. Admin tidak bisa menurunkan perannya sendiri, supaya
+/// selalu ada setidaknya satu admin.
+pub async fn update_role(
+    Extension(state): Extension<Arc<AppState>>,
+    require_admin: RequireRole<Admin>,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<UpdateRoleRequest>,
+) -> Result<Json<AdminUserResponse>, AppError> {
+    let admin = require_admin.0;
+    if user_id == admin.id && request.role != Role::Admin {
+        return Err(AppError::Forbidden(
+            "Tidak bisa menurunkan peran sendiri".to_string(),
+        ));
+    }
+
+    let user = find_user(user_id, &state).await?;
+    User::set_role(user.id, request.role, &state.db).await?;
+    user_cache::invalidate(user.id);
+    info!(
+        "Admin {} changed role of {} from {} to {}",
+        admin.username, user.username, user.role, request.role
+    );
+
+    Ok(Json(AdminUserResponse::from(User {
+        role: request.role,
+        ..user
+    })))
+}
+
+/// Blokir pengguna dan akhiri semua sesi serta koneksi WebSocket-nya dan bot-botnya.
+pub async fn ban_user(
+    Extension(state): Extension<Arc<AppState>>,
+    require_admin: RequireRole<Admin>,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<BanUserRequest>,
+) -> Result<StatusCode, AppError> {
+    let admin = require_admin.0;
+    let user = find_user(user_id, &state).await?;
+    if user.id == admin.id || user.role == Role::Admin {
+        return Err(AppError::Forbidden("Admin tidak bisa diblokir".to_string()));
+    }
+
+    let reason = request
+        .reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    user.ban(reason.as_deref(), &state.db).await?;
+    let mut closed = end_all_sessions(user.id, &state.db).await?;
+    // Token bot ditolak selama pemiliknya diblokir; tutup juga koneksi yang sudah terbuka.
+    for bot in User::list_bots(user.id, &state.db).await? {
+        closed += disconnect_user(bot.id);
+    }
+    info!(
+        "Admin {} banned {} ({} WebSocket closed)",
+        admin.username, user.username, closed
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn unban_user(
+    Extension(state): Extension<Arc<AppState>>,
+    require_admin: RequireRole<Admin>,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let admin = require_admin.0;
+    let user = find_user(user_id, &state).await?;
+    user.unban(&state.db).await?;
+    info!("Admin {} unbanned {}", admin.username, user.username);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Hapus pesan siapa pun. Boleh dilakukan moderator dan admin.
+pub async fn delete_message(
+    Extension(state): Extension<Arc<AppState>>,
+    require_moderator: RequireRole<Moderator>,
+    Path(message_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let moderator = require_moderator.0;
+    let message = Message::delete(message_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Pesan tidak ditemukan".to_string()))?;
+
+    broadcast_message_deleted(&message);
+    info!(
+        "{} {} deleted message {} from {}",
+        moderator.role, moderator.username, message.id, message.sender_id
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_user(user_id: Uuid, state: &AppState) -> Result<User, AppError> {
+    User::find_by_id(user_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Pengguna tidak ditemukan".to_string()))
+}
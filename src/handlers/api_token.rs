@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    handlers::websocket::disconnect_api_token,
+    middleware::auth::{AppState, AuthUser},
+    models::{
+        api_token::{
+            ApiTokenResponse, CreateApiTokenRequest, CreatedApiTokenResponse, PersonalAccessToken,
+        },
+        errors::AppError,
+        user::User,
+    },
+};
+
+/// Daftar token aktif milik pengguna dan bot-botnya. Token mentah tidak pernah ditampilkan lagi.
+pub async fn list_tokens(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<ApiTokenResponse>>, AppError> {
+    let api_tokens = PersonalAccessToken::list_for_owner(auth_user.0.id, &state.db).await?;
+
+    Ok(Json(
+        api_tokens
+            .into_iter()
+            .map(PersonalAccessToken::into_response)
+            .collect(),
+    ))
+}
+
+/// Terbitkan personal access token untuk pengguna sendiri, atau untuk bot miliknya jika
+/// `bot_id` diisi.
+pub async fn create_token(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<CreateApiTokenRequest>,
+) -> Result<(StatusCode, Json<CreatedApiTokenResponse>), AppError> {
+    let owner = auth_user.0;
+    request.validate().into_result()?;
+
+    let user_id = match request.bot_id {
+        Some(bot_id) => find_own_bot(bot_id, &owner, &state).await?.id,
+        None => owner.id,
+    };
+
+    let (token, api_token) = PersonalAccessToken::new(
+        user_id,
+        owner.id,
+        &request.name,
+        &request.scopes,
+        request.expires_in_days,
+    );
+    let api_token = api_token.create(&state.db).await?;
+    info!(
+        "User {} created API token {} for {}",
+        owner.username, api_token.id, user_id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiTokenResponse {
+            token,
+            details: api_token.into_response(),
+        }),
+    ))
+}
+
+/// Cabut token dan tutup koneksi WebSocket yang memakainya.
+pub async fn revoke_token(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(token_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    if !PersonalAccessToken::revoke(token_id, auth_user.0.id, &state.db).await? {
+        return Err(AppError::NotFound("Token tidak ditemukan".to_string()));
+    }
+
+    disconnect_api_token(token_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_own_bot(bot_id: Uuid, owner: &User, state: &AppState) -> Result<User, AppError> {
+    User::find_by_id(bot_id, &state.db)
+        .await?
+        .filter(|bot| bot.is_bot && bot.bot_owner_id == Some(owner.id))
+        .ok_or_else(|| AppError::NotFound("Bot tidak ditemukan".to_string()))
+}
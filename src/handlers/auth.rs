@@ -1,70 +1,534 @@
 use std::sync::Arc;
 
-use axum::{Extension, Json};
+use axum::{Extension, Json, extract::rejection::JsonRejection, http::StatusCode};
+use chrono::{Duration, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use sqlx::postgres::PgPool;
+use tracing::{info, warn};
+use uuid::Uuid;
 
 use crate::{
-    config::jwt::generate_token,
-    middleware::auth::AppState,
+    config::{
+        get_jwt_expiration,
+        jwt::{self, Claims, generate_session_token},
+        login::LoginPolicy,
+        mail::{get_email_verification_url, get_password_reset_url},
+        mfa::get_mfa_challenge_ttl,
+        password::PasswordPolicy,
+    },
+    handlers::websocket::{disconnect_session, disconnect_token, disconnect_user},
+    mail::{Email, send_in_background},
+    middleware::{
+        auth::{AppState, AuthUser, ensure_not_banned},
+        client::ClientInfo,
+    },
     models::{
-        errors::AppError,
-        user::{LoginRequest, RegisterRequest, TokenResponse, User},
+        email_verification::{EmailVerificationToken, VerifyEmailRequest},
+        errors::{AppError, ValidationErrors},
+        login_throttle::{LockoutScope, LoginLockout, LoginThrottle},
+        mfa::{MfaChallenge, MfaChallengeResponse, UserTotp},
+        password_reset::{ForgotPasswordRequest, PasswordResetToken, ResetPasswordRequest},
+        refresh_token::{RefreshRequest, RefreshToken},
+        revoked_token::RevokedToken,
+        session::Session,
+        user::{
+            LoginRequest, LoginResponse, LogoutRequest, RegisterRequest, TokenResponse, User,
+            verify_dummy_password,
+        },
+    },
+    utils::{
+        email::normalize_email,
+        validation::{breached_passwords, validate_password},
     },
 };
 
 pub async fn register(
     Extension(state): Extension<Arc<AppState>>,
+    client: ClientInfo,
     Json(request): Json<RegisterRequest>,
 ) -> Result<Json<TokenResponse>, AppError> {
-    if request.username.is_empty() || request.password.is_empty() {
-        return Err(AppError::Validation(
-            "Username dan password wajib diisi".to_string(),
-        ));
-    }
+    let request = RegisterRequest {
+        username: request.username.trim().to_string(),
+        email: request
+            .email
+            .as_deref()
+            .map(str::trim)
+            .filter(|email| !email.is_empty())
+            .map(str::to_string),
+        ..request
+    };
 
-    if User::find_by_username(&request.username, &state.db)
-        .await?
-        .is_some()
+    let mut errors = request.validate(&PasswordPolicy::from_env(), breached_passwords());
+    if errors.errors().iter().all(|e| e.field != "username")
+        && User::find_by_username(&request.username, &state.db)
+            .await?
+            .is_some()
     {
-        return Err(AppError::Validation("Username sudah digunakan".to_string()));
+        errors.add("username", "taken", "Username sudah digunakan");
     }
+    if let Some(email) = &request.email
+        && errors.errors().iter().all(|e| e.field != "email")
+        && User::find_by_email(email, &state.db).await?.is_some()
+    {
+        errors.add("email", "taken", "Email sudah digunakan");
+    }
+    errors.into_result()?;
 
-    let user = User::new(request).await?;
+    let email = request.email.clone();
+    let user = User::new(RegisterRequest { email, ..request }).await?;
     let user = user.create(&state.db).await?;
-    let token = generate_token(user.id)?;
-    let response = TokenResponse {
-        access_token: token,
-        token_type: "Bearer".to_string(),
-        user: user.into_response(),
-    };
+    if let Some(email) = user.email.clone() {
+        send_verification_email(&state, &user, &email).await?;
+    }
+    let session = Session::new(user.id, None, client)
+        .create(&state.db)
+        .await?;
+    let response = issue_tokens(user, session.id, &state.db).await?;
 
     Ok(Json(response))
 }
 
 pub async fn login(
     Extension(state): Extension<Arc<AppState>>,
+    client: ClientInfo,
     Json(request): Json<LoginRequest>,
-) -> Result<Json<TokenResponse>, AppError> {
+) -> Result<Json<LoginResponse>, AppError> {
     if request.username.is_empty() || request.password.is_empty() {
         return Err(AppError::Validation(
             "Username dan password wajib diisi".to_string(),
         ));
     }
 
-    let user = User::find_by_username(&request.username, &state.db)
-        .await?
-        .ok_or_else(|| AppError::Auth("Username atau password salah".to_string()))?;
+    let policy = LoginPolicy::from_env();
+    let throttled = throttle_subjects(&request.username, &client);
+    ensure_login_allowed(&throttled, &policy, &state.db).await?;
 
-    if !user.verify_password(&request.password)? {
+    // Username yang tidak dikenal tetap menjalankan bcrypt agar waktu respons sama.
+    let user = User::find_by_username(&request.username, &state.db).await?;
+    let verified = match &user {
+        Some(user) => user.verify_password(&request.password)?,
+        None => verify_dummy_password(&request.password),
+    };
+
+    // Akun bot hanya bisa dipakai lewat personal access token.
+    let Some(user) = user.filter(|user| verified && !user.is_bot) else {
+        record_login_failure(&throttled, &policy, &client, &state.db).await?;
         return Err(AppError::Auth("Username atau password salah".to_string()));
+    };
+
+    LoginThrottle::clear(&LockoutScope::Username.key(&request.username), &state.db).await?;
+
+    let response = begin_login(&state, user, request.device_name, client).await?;
+
+    Ok(Json(response))
+}
+
+pub async fn refresh(
+    Extension(state): Extension<Arc<AppState>>,
+    Json(request): Json<RefreshRequest>,
+) -> Result<Json<TokenResponse>, AppError> {
+    if request.refresh_token.is_empty() {
+        return Err(AppError::Validation(
+            "Refresh token wajib diisi".to_string(),
+        ));
+    }
+
+    let token = RefreshToken::find_by_token(&request.refresh_token, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Auth("Refresh token tidak valid".to_string()))?;
+
+    // Token yang sudah dirotasi dipakai lagi: anggap bocor dan cabut seluruh keluarganya.
+    if token.is_spent() || !token.mark_rotated(&state.db).await? {
+        let revoked = RefreshToken::revoke_family(token.family_id, &state.db).await?;
+        warn!(
+            "Refresh token reuse for user {}, revoked {} token(s) in family {}",
+            token.user_id, revoked, token.family_id
+        );
+        return Err(AppError::Auth(
+            "Refresh token sudah tidak berlaku".to_string(),
+        ));
+    }
+
+    if token.is_expired() {
+        return Err(AppError::Auth("Refresh token kedaluwarsa".to_string()));
     }
 
+    let user = User::find_by_id(token.user_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Auth("Pengguna tidak ditemukan".to_string()))?;
+
+    // Keluarga refresh token adalah sesi login yang sama.
+    Session::touch(token.family_id, &state.db).await?;
+    let response = issue_tokens(user, token.family_id, &state.db).await?;
+
+    Ok(Json(response))
+}
+
+/// Akhiri sesi token yang sedang dipakai dan tutup koneksi WebSocket yang memakainya.
+/// Body boleh dikosongkan; cukup header `Authorization`.
+pub async fn logout(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+    auth_user: AuthUser,
+    request: Result<Json<LogoutRequest>, JsonRejection>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_user.0;
+    let request = match request {
+        Ok(Json(request)) => request,
+        Err(JsonRejection::MissingJsonContentType(_)) => LogoutRequest::default(),
+        Err(rejection) => return Err(AppError::Validation(rejection.body_text())),
+    };
+
+    if claims.jti.is_empty() {
+        // Token lama tanpa `jti` tidak bisa dicabut satu per satu.
+        RevokedToken::revoke_all_for_user(user.id, &state.db).await?;
+    } else {
+        RevokedToken::revoke(&claims.jti, user.id, claims.expires_at(), &state.db).await?;
+    }
+
+    let mut closed = disconnect_token(&claims.jti);
+    if let Some(session_id) = claims.sid {
+        Session::revoke(session_id, user.id, &state.db).await?;
+        RefreshToken::revoke_family(session_id, &state.db).await?;
+        closed += disconnect_session(session_id);
+    }
+
+    if let Some(refresh_token) = request.refresh_token.filter(|token| !token.is_empty())
+        && let Some(token) = RefreshToken::find_by_token(&refresh_token, &state.db).await?
+        && token.user_id == user.id
+    {
+        RefreshToken::revoke_family(token.family_id, &state.db).await?;
+    }
+
+    info!(
+        "User {} logged out ({} WebSocket closed)",
+        user.username, closed
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Cabut semua token milik pengguna dan tutup seluruh koneksi WebSocket-nya.
+pub async fn logout_all(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, AppError> {
+    let user = auth_user.0;
+
+    let closed = end_all_sessions(user.id, &state.db).await?;
+    info!(
+        "User {} logged out everywhere ({} WebSocket closed)",
+        user.username, closed
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Kirim tautan reset password ke email pengguna.
+///
+/// Selalu membalas `202 Accepted` agar tidak membocorkan email mana yang terdaftar.
+pub async fn forgot_password(
+    Extension(state): Extension<Arc<AppState>>,
+    Json(request): Json<ForgotPasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    let email = request.email.trim();
+    if email.is_empty() {
+        return Err(AppError::Validation("Email wajib diisi".to_string()));
+    }
+
+    if let Some(user) = User::find_by_email(email, &state.db).await?
+        && let Some(address) = user.email.clone()
+    {
+        // Hanya tautan terbaru yang berlaku.
+        PasswordResetToken::invalidate_for_user(user.id, &state.db).await?;
+        let (token, record) = PasswordResetToken::new(user.id);
+        record.create(&state.db).await?;
+
+        send_in_background(
+            state.mailer.clone(),
+            password_reset_email(address, &user.username, &token),
+        );
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Ganti password memakai token dari email, lalu akhiri semua sesi pengguna.
+pub async fn reset_password(
+    Extension(state): Extension<Arc<AppState>>,
+    Json(request): Json<ResetPasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    if request.token.is_empty() || request.new_password.is_empty() {
+        return Err(AppError::Validation(
+            "Token dan password baru wajib diisi".to_string(),
+        ));
+    }
+
+    let invalid = || AppError::Validation("Token reset tidak valid atau kedaluwarsa".to_string());
+
+    let token = PasswordResetToken::find_by_token(&request.token, &state.db)
+        .await?
+        .filter(PasswordResetToken::is_usable)
+        .ok_or_else(invalid)?;
+    let user = User::find_by_id(token.user_id, &state.db)
+        .await?
+        .ok_or_else(invalid)?;
+
+    // Password diperiksa sebelum token dipakai, supaya token tetap berlaku jika ditolak.
+    let mut errors = ValidationErrors::default();
+    errors.extend(validate_password(
+        &request.new_password,
+        &user.username,
+        &PasswordPolicy::from_env(),
+        breached_passwords(),
+    ));
+    errors.into_result()?;
+
+    if !token.consume(&state.db).await? {
+        return Err(invalid());
+    }
+
+    user.update_password(&request.new_password, &state.db)
+        .await?;
+    PasswordResetToken::invalidate_for_user(user.id, &state.db).await?;
+
+    // Password lama mungkin sudah bocor, jadi semua sesi yang ada ikut diakhiri.
+    let closed = end_all_sessions(user.id, &state.db).await?;
+    info!(
+        "User {} reset password ({} WebSocket closed)",
+        user.username, closed
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Verifikasi email memakai token dari email verifikasi.
+pub async fn verify_email(
+    Extension(state): Extension<Arc<AppState>>,
+    Json(request): Json<VerifyEmailRequest>,
+) -> Result<StatusCode, AppError> {
+    if request.token.is_empty() {
+        return Err(AppError::Validation(
+            "Token verifikasi wajib diisi".to_string(),
+        ));
+    }
+
+    let invalid =
+        || AppError::Validation("Token verifikasi tidak valid atau kedaluwarsa".to_string());
+
+    let token = EmailVerificationToken::find_by_token(&request.token, &state.db)
+        .await?
+        .ok_or_else(invalid)?;
+    if !token.is_usable() || !token.consume(&state.db).await? {
+        return Err(invalid());
+    }
+
+    // Gagal jika pengguna sudah mengganti email setelah token dibuat.
+    if !User::mark_email_verified(token.user_id, &token.email, &state.db).await? {
+        return Err(invalid());
+    }
+
+    info!("User {} verified email", token.user_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Kunci publik untuk memverifikasi access token (JWKS). Kosong jika memakai HS256.
+pub async fn jwks() -> Result<Json<JwkSet>, AppError> {
+    Ok(Json(jwt::jwks()?))
+}
+
+/// Username dan (jika diketahui) IP yang dihitung untuk pembatasan login.
+fn throttle_subjects(username: &str, client: &ClientInfo) -> Vec<(LockoutScope, String)> {
+    let mut subjects = vec![(LockoutScope::Username, username.trim().to_lowercase())];
+    if let Some(ip) = &client.ip_address {
+        subjects.push((LockoutScope::Ip, ip.clone()));
+    }
+    subjects
+}
+
+/// Tolak login selama username atau IP masih terkunci atau dalam masa jeda.
+/// Berlaku sama untuk username yang tidak terdaftar.
+async fn ensure_login_allowed(
+    subjects: &[(LockoutScope, String)],
+    policy: &LoginPolicy,
+    db: &PgPool,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+
+    for (scope, subject) in subjects {
+        if let Some(throttle) = LoginThrottle::find(&scope.key(subject), db).await?
+            && let Some(retry_after) = throttle.retry_after(policy, now)
+        {
+            return Err(AppError::TooManyRequests(format!(
+                "Terlalu banyak percobaan login. Coba lagi dalam {} detik",
+                retry_after
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Catat login gagal dan kunci sementara username atau IP yang melewati batas.
+async fn record_login_failure(
+    subjects: &[(LockoutScope, String)],
+    policy: &LoginPolicy,
+    client: &ClientInfo,
+    db: &PgPool,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+
+    for (scope, subject) in subjects {
+        let mut throttle =
+            LoginThrottle::record_failure(&scope.key(subject), policy.lockout_duration, db).await?;
+
+        if throttle.should_lock(scope.max_failures(policy), now) {
+            let locked_until = now + Duration::seconds(policy.lockout_duration);
+            throttle.lock(locked_until, db).await?;
+            LoginLockout::new(*scope, subject, throttle.failures, locked_until, client)
+                .create(db)
+                .await?;
+            warn!(
+                "Login locked for {} {} until {} after {} failure(s)",
+                scope, subject, locked_until, throttle.failures
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Lanjutkan login setelah identitas pengguna terbukti. Dengan 2FA aktif, token baru
+/// diterbitkan setelah kode kedua diverifikasi.
+pub(crate) async fn begin_login(
+    state: &AppState,
+    user: User,
+    device_name: Option<String>,
+    client: ClientInfo,
+) -> Result<LoginResponse, AppError> {
+    if let Some(totp) = UserTotp::find(user.id, &state.db).await?
+        && totp.is_enabled()
+    {
+        let (mfa_token, challenge) = MfaChallenge::new(user.id, device_name);
+        challenge.create(&state.db).await?;
+
+        return Ok(LoginResponse::MfaRequired(MfaChallengeResponse {
+            mfa_required: true,
+            mfa_token,
+            expires_in: get_mfa_challenge_ttl(),
+        }));
+    }
+
+    let response = complete_login(state, user, device_name, client).await?;
+
+    Ok(LoginResponse::Tokens(Box::new(response)))
+}
+
+/// Tandai pengguna online, buka sesi login baru, dan terbitkan tokennya.
+pub(crate) async fn complete_login(
+    state: &AppState,
+    user: User,
+    device_name: Option<String>,
+    client: ClientInfo,
+) -> Result<TokenResponse, AppError> {
+    ensure_not_banned(&user)?;
     user.update_online_status(true, &state.db).await?;
-    let token = generate_token(user.id)?;
-    let response = TokenResponse {
-        access_token: token,
+    let session = Session::new(user.id, device_name, client)
+        .create(&state.db)
+        .await?;
+
+    issue_tokens(user, session.id, &state.db).await
+}
+
+/// Buat pasangan access token dan refresh token untuk sebuah sesi login.
+pub(crate) async fn issue_tokens(
+    user: User,
+    session_id: Uuid,
+    db: &PgPool,
+) -> Result<TokenResponse, AppError> {
+    let access_token = generate_session_token(user.id, session_id, user.role)?;
+    let (refresh_token, record) = RefreshToken::new(user.id, Some(session_id));
+    record.create(db).await?;
+
+    Ok(TokenResponse {
+        access_token,
         token_type: "Bearer".to_string(),
+        expires_in: get_jwt_expiration(),
+        refresh_token,
         user: user.into_response(),
-    };
+    })
+}
 
-    Ok(Json(response))
+/// Cabut semua token dan sesi pengguna, lalu tutup seluruh koneksi WebSocket-nya.
+pub(crate) async fn end_all_sessions(user_id: Uuid, db: &PgPool) -> Result<usize, AppError> {
+    RevokedToken::revoke_all_for_user(user_id, db).await?;
+    RefreshToken::revoke_all_for_user(user_id, db).await?;
+    Session::revoke_all_for_user(user_id, db).await?;
+
+    Ok(disconnect_user(user_id))
+}
+
+/// Periksa format email baru dan pastikan belum dipakai pengguna lain.
+pub(crate) async fn validate_new_email(
+    email: &str,
+    user_id: Option<Uuid>,
+    db: &PgPool,
+) -> Result<String, AppError> {
+    let email = normalize_email(email)
+        .ok_or_else(|| AppError::Validation("Format email tidak valid".to_string()))?;
+
+    if let Some(existing) = User::find_by_email(&email, db).await?
+        && Some(existing.id) != user_id
+    {
+        return Err(AppError::Validation("Email sudah digunakan".to_string()));
+    }
+
+    Ok(email)
+}
+
+/// Kirim tautan verifikasi ke `email`. Token verifikasi lama milik pengguna dibatalkan.
+pub(crate) async fn send_verification_email(
+    state: &AppState,
+    user: &User,
+    email: &str,
+) -> Result<(), AppError> {
+    EmailVerificationToken::invalidate_for_user(user.id, &state.db).await?;
+    let (token, record) = EmailVerificationToken::new(user.id, email);
+    record.create(&state.db).await?;
+
+    send_in_background(
+        state.mailer.clone(),
+        Email {
+            to: email.to_string(),
+            subject: "Verifikasi email Chat App".to_string(),
+            body: format!(
+                "Halo {},\n\nBuka tautan berikut untuk memverifikasi email Anda:\n\n{}\n\n\
+                 Jika Anda tidak mendaftar atau mengganti email di Chat App, abaikan email ini.\n",
+                user.username,
+                link_with_token(&get_email_verification_url(), &token)
+            ),
+        },
+    );
+
+    Ok(())
+}
+
+fn password_reset_email(to: String, username: &str, token: &str) -> Email {
+    Email {
+        to,
+        subject: "Reset password Chat App".to_string(),
+        body: format!(
+            "Halo {},\n\nKami menerima permintaan untuk mereset password akun Anda. \
+             Buka tautan berikut untuk membuat password baru:\n\n{}\n\n\
+             Jika Anda tidak meminta reset password, abaikan email ini.\n",
+            username,
+            link_with_token(&get_password_reset_url(), token)
+        ),
+    }
+}
+
+fn link_with_token(base_url: &str, token: &str) -> String {
+    let separator = if base_url.contains('?') { '&' } else { '?' };
+    format!("{}{}token={}", base_url, separator, token)
 }
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    middleware::auth::{AppState, AuthUser},
+    models::{
+        block::{BlockKind, BlockedUserResponse, UserBlock},
+        contact::Contact,
+        errors::AppError,
+        user::User,
+    },
+};
+
+pub async fn list_blocks(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<BlockedUserResponse>>, AppError> {
+    let users = UserBlock::list(auth_user.0.id, BlockKind::Block, &state.db).await?;
+    Ok(Json(users))
+}
+
+pub async fn list_mutes(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<BlockedUserResponse>>, AppError> {
+    let users = UserBlock::list(auth_user.0.id, BlockKind::Mute, &state.db).await?;
+    Ok(Json(users))
+}
+
+/// Blokir pengguna: pesan pribadinya ditolak dan pesan publiknya disembunyikan.
+pub async fn block_user(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    add(&state, &auth_user.0, user_id, BlockKind::Block).await
+}
+
+pub async fn unblock_user(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    remove(&state, &auth_user.0, user_id, BlockKind::Block).await
+}
+
+/// Bisukan pengguna: pesannya tetap tampil, tetapi dikirim tanpa notifikasi.
+pub async fn mute_user(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    add(&state, &auth_user.0, user_id, BlockKind::Mute).await
+}
+
+pub async fn unmute_user(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    remove(&state, &auth_user.0, user_id, BlockKind::Mute).await
+}
+
+async fn add(
+    state: &AppState,
+    user: &User,
+    target_id: Uuid,
+    kind: BlockKind,
+) -> Result<StatusCode, AppError> {
+    if target_id == user.id {
+        return Err(AppError::Validation(
+            "Tidak bisa memblokir atau membisukan diri sendiri".to_string(),
+        ));
+    }
+
+    User::find_by_id(target_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Pengguna tidak ditemukan".to_string()))?;
+
+    UserBlock::new(user.id, target_id, kind)
+        .create(&state.db)
+        .await?;
+    if kind == BlockKind::Block {
+        // Memblokir juga memutus kontak dan membatalkan permintaan kontak di antara keduanya
+        Contact::remove(user.id, target_id, &state.db).await?;
+    }
+    info!(
+        "User {} added {} on {}",
+        user.username,
+        kind.as_str(),
+        target_id
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn remove(
+    state: &AppState,
+    user: &User,
+    target_id: Uuid,
+    kind: BlockKind,
+) -> Result<StatusCode, AppError> {
+    if !UserBlock::remove(user.id, target_id, kind, &state.db).await? {
+        return Err(AppError::NotFound(match kind {
+            BlockKind::Block => "Pengguna ini tidak diblokir".to_string(),
+            BlockKind::Mute => "Pengguna ini tidak dibisukan".to_string(),
+        }));
+    }
+
+    info!(
+        "User {} removed {} on {}",
+        user.username,
+        kind.as_str(),
+        target_id
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, http::StatusCode};
+use tracing::info;
+
+use crate::{
+    middleware::auth::{AppState, AuthUser},
+    models::{
+        errors::{AppError, ValidationErrors},
+        user::{CreateBotRequest, User, UserResponse},
+    },
+    utils::validation::validate_username,
+};
+
+/// Jumlah maksimum bot per pengguna.
+pub const MAX_BOTS_PER_USER: usize = 10;
+
+pub async fn list_bots(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<UserResponse>>, AppError> {
+    let bots = User::list_bots(auth_user.0.id, &state.db).await?;
+
+    Ok(Json(bots.into_iter().map(User::into_response).collect()))
+}
+
+/// Buat akun bot milik pengguna. Bot tidak bisa login; token-nya diterbitkan lewat
+/// `POST /users/me/tokens` dengan `bot_id`.
+pub async fn create_bot(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<CreateBotRequest>,
+) -> Result<(StatusCode, Json<UserResponse>), AppError> {
+    let owner = auth_user.0;
+    if owner.is_bot {
+        return Err(AppError::Forbidden(
+            "Bot tidak bisa membuat bot lain".to_string(),
+        ));
+    }
+
+    let username = request.username.trim().to_string();
+    let mut errors = ValidationErrors::default();
+    if let Err(error) = validate_username(&username) {
+        errors.extend([error]);
+    } else if User::find_by_username(&username, &state.db)
+        .await?
+        .is_some()
+    {
+        errors.add("username", "taken", "Username sudah digunakan");
+    }
+    errors.into_result()?;
+
+    if User::list_bots(owner.id, &state.db).await?.len() >= MAX_BOTS_PER_USER {
+        return Err(AppError::Validation(format!(
+            "Maksimal {MAX_BOTS_PER_USER} bot per pengguna"
+        )));
+    }
+
+    let bot = User::new_bot(owner.id, username)?.create(&state.db).await?;
+    info!("User {} created bot {}", owner.username, bot.username);
+
+    Ok((StatusCode::CREATED, Json(bot.into_response())))
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    middleware::auth::{AppState, AuthUser},
+    models::{
+        block::{BlockKind, UserBlock},
+        contact::{Contact, ContactResponse, ContactStatus},
+        errors::AppError,
+        user::User,
+    },
+};
+
+/// Pesan yang sama ke arah blokir mana pun, supaya pengguna tidak tahu bahwa ia diblokir.
+const CONTACT_REQUEST_REJECTED: &str = "Permintaan kontak tidak dapat dikirim ke pengguna ini";
+
+pub async fn list_contacts(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<ContactResponse>>, AppError> {
+    let contacts = Contact::list(auth_user.0.id, ContactStatus::Accepted, &state.db).await?;
+    Ok(Json(contacts))
+}
+
+/// Permintaan kontak yang masih menunggu, baik yang masuk maupun yang dikirim.
+pub async fn list_contact_requests(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<ContactResponse>>, AppError> {
+    let requests = Contact::list(auth_user.0.id, ContactStatus::Pending, &state.db).await?;
+    Ok(Json(requests))
+}
+
+/// Kirim permintaan kontak. Jika pengguna itu sudah lebih dulu mengirim permintaan,
+/// permintaannya langsung diterima.
+pub async fn send_contact_request(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<ContactResponse>), AppError> {
+    let user = auth_user.0;
+    if user_id == user.id {
+        return Err(AppError::Validation(
+            "Tidak bisa menambahkan diri sendiri sebagai kontak".to_string(),
+        ));
+    }
+
+    let target = User::find_by_id(user_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Pengguna tidak ditemukan".to_string()))?;
+
+    if UserBlock::between(user.id, target.id, &state.db)
+        .await?
+        .iter()
+        .any(|block| block.kind == BlockKind::Block)
+    {
+        return Err(AppError::Forbidden(CONTACT_REQUEST_REJECTED.to_string()));
+    }
+
+    match Contact::find_between(user.id, target.id, &state.db).await? {
+        Some(contact) if contact.status == ContactStatus::Accepted => Err(AppError::Validation(
+            "Pengguna ini sudah menjadi kontak".to_string(),
+        )),
+        Some(contact) if contact.requester_id == user.id => Err(AppError::Validation(
+            "Permintaan kontak sudah dikirim".to_string(),
+        )),
+        Some(_) => {
+            let contact = Contact::accept(target.id, user.id, &state.db)
+                .await?
+                .ok_or_else(|| {
+                    AppError::NotFound("Permintaan kontak tidak ditemukan".to_string())
+                })?;
+            info!("User {} accepted contact {}", user.username, target.id);
+
+            Ok((
+                StatusCode::OK,
+                Json(contact.into_response(user.id, &target)),
+            ))
+        }
+        None => {
+            let contact = Contact::new(user.id, target.id).create(&state.db).await?;
+            info!(
+                "User {} sent contact request to {}",
+                user.username, target.id
+            );
+
+            Ok((
+                StatusCode::CREATED,
+                Json(contact.into_response(user.id, &target)),
+            ))
+        }
+    }
+}
+
+pub async fn accept_contact_request(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_user.0;
+    Contact::accept(user_id, user.id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Permintaan kontak tidak ditemukan".to_string()))?;
+    info!("User {} accepted contact {}", user.username, user_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn decline_contact_request(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_user.0;
+    if !Contact::decline(user_id, user.id, &state.db).await? {
+        return Err(AppError::NotFound(
+            "Permintaan kontak tidak ditemukan".to_string(),
+        ));
+    }
+    info!(
+        "User {} declined contact request from {}",
+        user.username, user_id
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Hapus kontak, atau batalkan permintaan kontak yang sudah dikirim.
+pub async fn remove_contact(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_user.0;
+    if !Contact::remove(user.id, user_id, &state.db).await? {
+        return Err(AppError::NotFound("Pengguna ini bukan kontak".to_string()));
+    }
+    info!("User {} removed contact {}", user.username, user_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query},
+    http::StatusCode,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::auth::{AppState, AuthUser},
+    models::{
+        block::{BlockKind, UserBlock},
+        contact::Contact,
+        conversation::{Conversation, ConversationResponse, ConversationsQuery},
+        errors::AppError,
+        user::User,
+    },
+};
+
+/// Daftar percakapan untuk sidebar klien: pesan pribadi dan channel publik, urut dari
+/// aktivitas terbaru. Profil lawan bicara, kontak, dan bisukan dimuat sekali untuk satu
+/// halaman, bukan per baris.
+pub async fn list_conversations(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Query(query): Query<ConversationsQuery>,
+) -> Result<Json<Vec<ConversationResponse>>, AppError> {
+    let viewer_id = auth_user.0.id;
+    let (limit, offset) = query.page();
+
+    let conversations = Conversation::list(viewer_id, limit, offset, &state.db).await?;
+
+    let partner_ids: Vec<Uuid> = conversations
+        .iter()
+        .filter_map(|conversation| conversation.partner_id)
+        .collect();
+    let mut partners: HashMap<Uuid, User> = User::find_by_ids(&partner_ids, &state.db)
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user))
+        .collect();
+    let contacts: HashSet<Uuid> = Contact::contact_ids(viewer_id, &state.db)
+        .await?
+        .into_iter()
+        .collect();
+    let muted: HashSet<Uuid> = UserBlock::list(viewer_id, BlockKind::Mute, &state.db)
+        .await?
+        .into_iter()
+        .map(|mute| mute.user_id)
+        .collect();
+
+    let response = conversations
+        .into_iter()
+        .filter_map(|conversation| {
+            let partner = match conversation.partner_id {
+                Some(partner_id) => {
+                    let user = partners.remove(&partner_id)?;
+                    let is_contact = contacts.contains(&partner_id);
+                    Some(user.into_profile_for(viewer_id, is_contact))
+                }
+                None => None,
+            };
+
+            Some(ConversationResponse {
+                kind: conversation.kind(),
+                muted: conversation
+                    .partner_id
+                    .is_some_and(|partner_id| muted.contains(&partner_id)),
+                last_message: conversation.preview(),
+                unread_count: conversation.unread_count,
+                partner,
+            })
+        })
+        .collect();
+
+    Ok(Json(response))
+}
+
+/// Tandai pesan pribadi dari `partner_id` sudah dibaca.
+pub async fn mark_direct_read(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(partner_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    Conversation::mark_direct_read(auth_user.0.id, partner_id, &state.db).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Tandai channel publik sudah dibaca sampai sekarang.
+pub async fn mark_public_read(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, AppError> {
+    Conversation::mark_public_read(auth_user.0.id, &state.db).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
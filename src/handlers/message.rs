@@ -1,79 +1,70 @@
 use std::sync::Arc;
 
-use axum::{Extension, Json, extract::Path};
+use axum::{
+    Extension, Json,
+    extract::{Path, Query},
+};
+use sqlx::postgres::PgPool;
 use uuid::Uuid;
 
 use crate::{
+    config::mail::get_require_verified_email,
     middleware::auth::{AppState, AuthUser},
     models::{
+        block::DirectMessageAccess,
         errors::AppError,
         message::{Message, MessageRequest, MessageResponse},
+        message_search::{MessageSearchHit, MessageSearchQuery, MessageSearchResponse},
         user::User,
+        user_cache::{self, CachedUser},
     },
 };
 
+/// Error untuk pesan pribadi yang ditolak penerimanya. Sengaja tidak menyebut alasannya.
+pub const DIRECT_MESSAGE_REJECTED: &str = "Pesan tidak dapat dikirim ke pengguna ini";
+
 pub async fn get_conversation(
     Extension(state): Extension<Arc<AppState>>,
     auth_user: AuthUser,
     Path(receiver_id): Path<Uuid>,
 ) -> Result<Json<Vec<MessageResponse>>, AppError> {
-    let user_id = auth_user.0.id;
-
-    let messages = Message::get_conversation(user_id, receiver_id, 50, &state.db).await?;
-
-    let mut response_messages = Vec::new();
-    for message in messages {
-        let sender = User::find_by_id(message.sender_id, &state.db)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Pengirim tidak ditemukan".to_string()))?;
-
-        let receiver_user = if let Some(receiver_id) = message.receiver_id {
-            let receiver = User::find_by_id(receiver_id, &state.db).await?;
-            receiver.map(|r| r.username)
-        } else {
-            None
-        };
-
-        response_messages.push(MessageResponse {
-            id: message.id,
-            sender_id: message.sender_id,
-            sender_username: sender.username,
-            receiver_id: message.receiver_id,
-            receiver_username: receiver_user,
-            content: message.content,
-            is_read: message.is_read,
-            created_at: message.created_at,
-        });
-    }
-
-    Ok(Json(response_messages))
+    let messages = Message::get_conversation(auth_user.0.id, receiver_id, 50, &state.db).await?;
+    Ok(Json(messages))
 }
 
 pub async fn get_public_messages(
     Extension(state): Extension<Arc<AppState>>,
-    _: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<Json<Vec<MessageResponse>>, AppError> {
-    let messages = Message::get_public_messages(50, &state.db).await?;
-
-    let mut response_messages = Vec::new();
-    for message in messages {
-        let sender = User::find_by_id(message.sender_id, &state.db)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Pengirim tidak ditemukan".to_string()))?;
-
-        response_messages.push(MessageResponse {
-            id: message.id,
-            sender_id: message.sender_id,
-            sender_username: sender.username,
-            receiver_id: None,
-            receiver_username: None,
-            content: message.content,
-            is_read: message.is_read,
-            created_at: message.created_at,
-        });
-    }
+    let messages = Message::get_public_messages(auth_user.0.id, 50, &state.db).await?;
+    Ok(Json(messages))
+}
 
-    Ok(Json(response_messages))
+/// Pencarian full-text pada pesan yang boleh dilihat pengguna, terbaru lebih dulu.
+pub async fn search_messages(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Query(query): Query<MessageSearchQuery>,
+) -> Result<Json<MessageSearchResponse>, AppError> {
+    query.validate().into_result()?;
+
+    // Ambil satu hasil lebih untuk tahu apakah masih ada halaman berikutnya
+    let limit = query.limit();
+    let mut hits = MessageSearchHit::search(auth_user.0.id, &query, limit + 1, &state.db).await?;
+    let next_cursor = if hits.len() as i64 > limit {
+        hits.truncate(limit as usize);
+        hits.last().map(|hit| hit.cursor().encode())
+    } else {
+        None
+    };
+
+    Ok(Json(MessageSearchResponse {
+        results: hits
+            .into_iter()
+            .map(MessageSearchHit::into_result)
+            .collect(),
+        next_cursor,
+    }))
 }
 
 pub async fn send_message(
@@ -83,11 +74,16 @@ pub async fn send_message(
 ) -> Result<Json<MessageResponse>, AppError> {
     let user = auth_user.0;
 
-    if let Some(receiver_id) = request.receiver_id {
-        let _receiver = User::find_by_id(receiver_id, &state.db)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Penerima tidak ditemukan".to_string()))?;
-    }
+    let receiver_username = match request.receiver_id {
+        Some(receiver_id) => {
+            let target = check_direct_message(&user, receiver_id, &state.db).await?;
+            Some(target.receiver.username)
+        }
+        None => {
+            ensure_can_post_public(&user)?;
+            None
+        }
+    };
 
     let message = Message::new(user.id, request);
 
@@ -95,13 +91,6 @@ pub async fn send_message(
 
     let sender_username = user.username;
 
-    let receiver_username = if let Some(receiver_id) = saved_message.receiver_id {
-        let receiver = User::find_by_id(receiver_id, &state.db).await?;
-        receiver.map(|r| r.username)
-    } else {
-        None
-    };
-
     let response = MessageResponse {
         id: saved_message.id,
         sender_id: saved_message.sender_id,
@@ -115,3 +104,54 @@ pub async fn send_message(
 
     Ok(Json(response))
 }
+
+/// Penerima pesan pribadi yang sudah lolos pemeriksaan.
+pub struct DirectMessageTarget {
+    pub receiver: CachedUser,
+    /// Penerima membisukan pengirim: pesan dikirim tanpa notifikasi.
+    pub muted: bool,
+}
+
+/// Pastikan `sender` boleh mengirim pesan pribadi ke `receiver_id`. Jika penerima memblokir
+/// pengirim atau pengaturan privasinya tidak mengizinkan, pesan ditolak dengan error umum
+/// supaya pengirim tidak tahu alasannya.
+pub async fn check_direct_message(
+    sender: &User,
+    receiver_id: Uuid,
+    db: &PgPool,
+) -> Result<DirectMessageTarget, AppError> {
+    let access = DirectMessageAccess::load(sender.id, receiver_id, db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Penerima tidak ditemukan".to_string()))?;
+
+    if access.blocked_by_sender {
+        return Err(AppError::Forbidden(
+            "Buka blokir pengguna ini untuk mengirim pesan".to_string(),
+        ));
+    }
+    if !access.allowed() {
+        return Err(AppError::Forbidden(DIRECT_MESSAGE_REJECTED.to_string()));
+    }
+
+    // Cache hanya dipakai untuk data tampilan penerima
+    let receiver = user_cache::find_cached(receiver_id, db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Penerima tidak ditemukan".to_string()))?;
+
+    Ok(DirectMessageTarget {
+        receiver,
+        muted: access.muted_by_receiver,
+    })
+}
+
+/// Tolak pesan publik dari pengguna yang emailnya belum diverifikasi jika
+/// `REQUIRE_VERIFIED_EMAIL` aktif.
+pub fn ensure_can_post_public(user: &User) -> Result<(), AppError> {
+    if get_require_verified_email() && !user.is_email_verified() {
+        return Err(AppError::Forbidden(
+            "Verifikasi email sebelum mengirim pesan ke channel publik".to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, http::StatusCode};
+use chrono::Utc;
+use sqlx::postgres::PgPool;
+use tracing::{info, warn};
+
+use crate::{
+    config::mfa::{get_mfa_issuer, get_mfa_max_attempts},
+    handlers::auth::complete_login,
+    middleware::{
+        auth::{AppState, AuthUser},
+        client::ClientInfo,
+    },
+    models::{
+        errors::AppError,
+        mfa::{
+            ConfirmTotpRequest, DisableTotpRequest, EnrollTotpResponse, MfaChallenge,
+            MfaVerifyRequest, RecoveryCode, RecoveryCodesResponse, UserTotp,
+        },
+        user::{TokenResponse, User},
+    },
+    utils::totp::otpauth_uri,
+};
+
+/// Mulai pendaftaran TOTP. 2FA baru aktif setelah dikonfirmasi dengan kode pertama.
+pub async fn enroll_totp(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<Json<EnrollTotpResponse>, AppError> {
+    let user = auth_user.0;
+
+    let totp = UserTotp::start_enrollment(user.id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Validation("2FA sudah aktif".to_string()))?;
+
+    Ok(Json(EnrollTotpResponse {
+        otpauth_uri: otpauth_uri(&get_mfa_issuer(), &user.username, &totp.secret),
+        secret: totp.secret,
+    }))
+}
+
+/// Konfirmasi pendaftaran TOTP dan terbitkan kode pemulihan.
+pub async fn confirm_totp(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<ConfirmTotpRequest>,
+) -> Result<Json<RecoveryCodesResponse>, AppError> {
+    let user = auth_user.0;
+
+    let totp = UserTotp::find(user.id, &state.db)
+        .await?
+        .filter(|totp| !totp.is_enabled())
+        .ok_or_else(|| AppError::Validation("Mulai pendaftaran 2FA terlebih dahulu".to_string()))?;
+
+    let step = totp
+        .verify_code(&request.code, Utc::now().timestamp())
+        .ok_or_else(|| AppError::Validation("Kode 2FA salah".to_string()))?;
+    if !UserTotp::enable(user.id, step, &state.db).await? {
+        return Err(AppError::Validation("2FA sudah aktif".to_string()));
+    }
+
+    let (recovery_codes, records) = RecoveryCode::generate_set(user.id);
+    RecoveryCode::replace_for_user(user.id, &records, &state.db).await?;
+
+    info!("User {} enabled TOTP 2FA", user.username);
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// Matikan 2FA. Memerlukan password dan kode TOTP atau kode pemulihan.
+pub async fn disable_totp(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<DisableTotpRequest>,
+) -> Result<StatusCode, AppError> {
+    let user = auth_user.0;
+
+    if !user.verify_password(&request.password)? {
+        return Err(AppError::Auth("Password salah".to_string()));
+    }
+
+    let totp = UserTotp::find(user.id, &state.db)
+        .await?
+        .filter(UserTotp::is_enabled)
+        .ok_or_else(|| AppError::Validation("2FA belum aktif".to_string()))?;
+
+    if !verify_second_factor(&totp, &request.code, &state.db).await? {
+        return Err(AppError::Auth("Kode 2FA salah".to_string()));
+    }
+
+    UserTotp::delete(user.id, &state.db).await?;
+    RecoveryCode::delete_for_user(user.id, &state.db).await?;
+
+    info!("User {} disabled TOTP 2FA", user.username);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Langkah kedua login: tukar `mfa_token` dan kode 2FA dengan pasangan token.
+pub async fn verify_mfa(
+    Extension(state): Extension<Arc<AppState>>,
+    client: ClientInfo,
+    Json(request): Json<MfaVerifyRequest>,
+) -> Result<Json<TokenResponse>, AppError> {
+    if request.mfa_token.is_empty() || request.code.is_empty() {
+        return Err(AppError::Validation(
+            "Token MFA dan kode wajib diisi".to_string(),
+        ));
+    }
+
+    let invalid = || AppError::Auth("Tantangan MFA tidak valid atau kedaluwarsa".to_string());
+    let max_attempts = get_mfa_max_attempts();
+
+    let challenge = MfaChallenge::find_by_token(&request.mfa_token, &state.db)
+        .await?
+        .filter(|challenge| challenge.is_usable(max_attempts))
+        .ok_or_else(invalid)?;
+
+    let totp = UserTotp::find(challenge.user_id, &state.db)
+        .await?
+        .filter(UserTotp::is_enabled)
+        .ok_or_else(invalid)?;
+
+    if !verify_second_factor(&totp, &request.code, &state.db).await? {
+        challenge.record_failure(&state.db).await?;
+        warn!("Invalid MFA code for user {}", challenge.user_id);
+        return Err(AppError::Auth("Kode 2FA salah".to_string()));
+    }
+
+    if !challenge.consume(max_attempts, &state.db).await? {
+        return Err(invalid());
+    }
+
+    let user = User::find_by_id(challenge.user_id, &state.db)
+        .await?
+        .ok_or_else(invalid)?;
+    let response = complete_login(&state, user, challenge.device_name, client).await?;
+
+    Ok(Json(response))
+}
+
+/// Terima kode TOTP yang belum pernah dipakai, atau satu kode pemulihan.
+pub(crate) async fn verify_second_factor(
+    totp: &UserTotp,
+    code: &str,
+    db: &PgPool,
+) -> Result<bool, AppError> {
+    if let Some(step) = totp.verify_code(code, Utc::now().timestamp()) {
+        return Ok(UserTotp::record_step(totp.user_id, step, db).await?);
+    }
+
+    Ok(RecoveryCode::consume(totp.user_id, code, db).await?)
+}
@@ -1,4 +1,14 @@
+pub mod account;
+pub mod admin;
+pub mod api_token;
 pub mod auth;
+pub mod block;
+pub mod bot;
+pub mod contact;
+pub mod conversation;
 pub mod message;
+pub mod mfa;
+pub mod oidc;
+pub mod retention;
 pub mod user;
 pub mod websocket;
@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::Path};
+use chrono::Utc;
+use rand::Rng;
+use sqlx::postgres::PgPool;
+use tracing::{info, warn};
+
+use crate::{
+    config::oidc::get_oidc_state_ttl,
+    handlers::auth::begin_login,
+    middleware::{auth::AppState, client::ClientInfo},
+    models::{
+        errors::AppError,
+        oidc::{
+            Identity, OidcAuthRequest, OidcAuthorizeResponse, OidcCallbackRequest,
+            OidcProvidersResponse,
+        },
+        user::{LoginResponse, RegisterRequest, User},
+    },
+    oidc::{self, IdTokenClaims, OidcProvider},
+    utils::{
+        email::normalize_email,
+        token::generate_opaque_token,
+        validation::{MAX_USERNAME_LENGTH, validate_username},
+    },
+};
+
+/// Daftar penyedia SSO yang dikonfigurasi.
+pub async fn list_providers() -> Json<OidcProvidersResponse> {
+    Json(OidcProvidersResponse {
+        providers: oidc::provider_names(),
+    })
+}
+
+/// Mulai login SSO: simpan state, nonce, dan code verifier lalu kembalikan URL
+/// otorisasi penyedia.
+pub async fn authorize(
+    Extension(state): Extension<Arc<AppState>>,
+    Path(provider_name): Path<String>,
+) -> Result<Json<OidcAuthorizeResponse>, AppError> {
+    let provider = find_provider(&provider_name)?;
+
+    let request = provider.authorization_request().await.map_err(|e| {
+        warn!("OIDC discovery for {} failed: {:#}", provider.name(), e);
+        AppError::ServiceUnavailable("Penyedia SSO tidak dapat dihubungi".to_string())
+    })?;
+    OidcAuthRequest::new(provider.name(), &request)
+        .create(&state.db)
+        .await?;
+
+    Ok(Json(OidcAuthorizeResponse {
+        authorization_url: request.url,
+        expires_in: get_oidc_state_ttl(),
+    }))
+}
+
+/// Selesaikan login SSO. Balasannya sama dengan `POST /auth/login`, termasuk
+/// tantangan 2FA jika pengguna mengaktifkannya.
+pub async fn callback(
+    Extension(state): Extension<Arc<AppState>>,
+    Path(provider_name): Path<String>,
+    client: ClientInfo,
+    Json(request): Json<OidcCallbackRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let provider = find_provider(&provider_name)?;
+
+    if request.code.is_empty() || request.state.is_empty() {
+        return Err(AppError::Validation(
+            "Code dan state wajib diisi".to_string(),
+        ));
+    }
+
+    let auth_request = OidcAuthRequest::take(provider.name(), &request.state, &state.db)
+        .await?
+        .ok_or_else(|| AppError::Auth("Login SSO kedaluwarsa, silakan ulangi".to_string()))?;
+
+    let claims = provider
+        .exchange_code(
+            &request.code,
+            &auth_request.code_verifier,
+            &auth_request.nonce,
+        )
+        .await
+        .map_err(|e| {
+            warn!("OIDC login via {} failed: {:#}", provider.name(), e);
+            AppError::Auth("Login SSO gagal".to_string())
+        })?;
+
+    let user = match Identity::find(&claims.iss, &claims.sub, &state.db).await? {
+        Some(identity) => {
+            identity
+                .touch(verified_email(&claims).as_deref(), &state.db)
+                .await?;
+            User::find_by_id(identity.user_id, &state.db)
+                .await?
+                .ok_or_else(|| AppError::Auth("Pengguna tidak ditemukan".to_string()))?
+        }
+        None => provision_user(&provider, &claims, &state.db).await?,
+    };
+
+    let response = begin_login(&state, user, request.device_name, client).await?;
+
+    Ok(Json(response))
+}
+
+fn find_provider(name: &str) -> Result<Arc<OidcProvider>, AppError> {
+    oidc::provider(name)
+        .ok_or_else(|| AppError::NotFound("Penyedia SSO tidak ditemukan".to_string()))
+}
+
+/// Buat akun lokal untuk identitas yang belum pernah login. Akun tidak pernah
+/// ditautkan otomatis ke pengguna lain berdasarkan email.
+async fn provision_user(
+    provider: &OidcProvider,
+    claims: &IdTokenClaims,
+    db: &PgPool,
+) -> Result<User, AppError> {
+    let username = available_username(claims, db).await?;
+
+    // Email hanya disalin jika sudah diverifikasi penyedia dan belum dipakai akun lain.
+    let email = match verified_email(claims) {
+        Some(email) if User::find_by_email(&email, db).await?.is_none() => Some(email),
+        _ => None,
+    };
+
+    let user = User::new(RegisterRequest {
+        username,
+        // Password acak: akun SSO login lewat penyedia sampai password diatur ulang.
+        password: generate_opaque_token(),
+        email: email.clone(),
+    })
+    .await?;
+    let user = User {
+        email_verified_at: email.as_ref().map(|_| Utc::now()),
+        ..user
+    }
+    .create(db)
+    .await?;
+
+    Identity::new(user.id, &claims.iss, &claims.sub, verified_email(claims))
+        .create(db)
+        .await?;
+
+    info!(
+        "Provisioned user {} from OIDC provider {}",
+        user.username,
+        provider.name()
+    );
+
+    Ok(user)
+}
+
+fn verified_email(claims: &IdTokenClaims) -> Option<String> {
+    claims
+        .email
+        .as_deref()
+        .filter(|_| claims.email_verified)
+        .and_then(normalize_email)
+}
+
+/// Username dari `preferred_username` atau bagian lokal email, diberi akhiran
+/// angka jika sudah dipakai atau tidak memenuhi aturan username.
+async fn available_username(claims: &IdTokenClaims, db: &PgPool) -> Result<String, AppError> {
+    let base = username_base(claims);
+
+    if User::find_by_username(&base, db).await?.is_none() {
+        return Ok(base);
+    }
+
+    for _ in 0..5 {
+        let suffix = rand::thread_rng().gen_range(1000..10000);
+        let candidate = format!("{}{}", base, suffix);
+        if User::find_by_username(&candidate, db).await?.is_none() {
+            return Ok(candidate);
+        }
+    }
+
+    Err(AppError::Internal(
+        "Gagal membuat username untuk akun SSO".to_string(),
+    ))
+}
+
+fn username_base(claims: &IdTokenClaims) -> String {
+    let source = claims
+        .preferred_username
+        .as_deref()
+        .or_else(|| claims.email.as_deref().and_then(|e| e.split('@').next()))
+        .unwrap_or_default();
+
+    let username: String = source
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        .take(MAX_USERNAME_LENGTH - 4)
+        .collect();
+
+    // Nama yang terlalu pendek, diawali tanda baca, atau dicadangkan diganti `user`.
+    if validate_username(&username).is_ok() {
+        username
+    } else {
+        "user".to_string()
+    }
+}
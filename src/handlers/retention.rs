@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::Path, http::StatusCode};
+use chrono::Utc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    config::retention::{get_message_retention_action, get_message_retention_days},
+    middleware::auth::{Admin, AppState, RequireRole},
+    models::{
+        errors::AppError,
+        retention::{
+            ConversationKey, LegalHold, LegalHoldRequest, RetentionPolicy, RetentionPolicyRequest,
+            RetentionReport, RetentionSettingsResponse, expired_messages_report,
+        },
+        user::User,
+    },
+};
+
+/// Default dari konfigurasi server beserta semua kebijakan per percakapan.
+pub async fn get_retention_settings(
+    Extension(state): Extension<Arc<AppState>>,
+    _: RequireRole<Admin>,
+) -> Result<Json<RetentionSettingsResponse>, AppError> {
+    Ok(Json(RetentionSettingsResponse {
+        default_retention_days: get_message_retention_days(),
+        action: get_message_retention_action().map_err(AppError::Internal)?,
+        policies: RetentionPolicy::list(&state.db).await?,
+    }))
+}
+
+/// Atur masa retensi satu percakapan (`public` atau `direct:<id>:<id>`).
+pub async fn set_retention_policy(
+    Extension(state): Extension<Arc<AppState>>,
+    require_admin: RequireRole<Admin>,
+    Path(conversation_key): Path<String>,
+    Json(request): Json<RetentionPolicyRequest>,
+) -> Result<Json<RetentionPolicy>, AppError> {
+    let admin = require_admin.0;
+    let key = parse_conversation_key(&conversation_key)?;
+    request.validate().into_result()?;
+
+    let policy = RetentionPolicy::new(key, request.retention_days, admin.id)
+        .save(&state.db)
+        .await?;
+    info!(
+        "Admin {} set retention of {} to {}",
+        admin.username,
+        key,
+        policy
+            .retention_days
+            .map_or("forever".to_string(), |days| format!("{} day(s)", days))
+    );
+
+    Ok(Json(policy))
+}
+
+/// Hapus kebijakan sehingga percakapan kembali memakai default server.
+pub async fn delete_retention_policy(
+    Extension(state): Extension<Arc<AppState>>,
+    require_admin: RequireRole<Admin>,
+    Path(conversation_key): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let admin = require_admin.0;
+    let key = parse_conversation_key(&conversation_key)?;
+    if !RetentionPolicy::remove(key, &state.db).await? {
+        return Err(AppError::NotFound(
+            "Kebijakan retensi tidak ditemukan".to_string(),
+        ));
+    }
+    info!(
+        "Admin {} removed retention policy of {}",
+        admin.username, key
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Dry run: pesan yang akan diproses job retensi jika dijalankan sekarang.
+pub async fn get_retention_report(
+    Extension(state): Extension<Arc<AppState>>,
+    _: RequireRole<Admin>,
+) -> Result<Json<RetentionReport>, AppError> {
+    let now = Utc::now();
+    let default_days = get_message_retention_days();
+    let entries = expired_messages_report(default_days, now, &state.db).await?;
+
+    Ok(Json(RetentionReport::new(
+        now,
+        default_days,
+        get_message_retention_action().map_err(AppError::Internal)?,
+        entries,
+    )))
+}
+
+pub async fn list_legal_holds(
+    Extension(state): Extension<Arc<AppState>>,
+    _: RequireRole<Admin>,
+) -> Result<Json<Vec<LegalHold>>, AppError> {
+    Ok(Json(LegalHold::list_active(&state.db).await?))
+}
+
+/// Tahan semua pesan milik satu pengguna atau di satu percakapan dari job retensi.
+pub async fn create_legal_hold(
+    Extension(state): Extension<Arc<AppState>>,
+    require_admin: RequireRole<Admin>,
+    Json(request): Json<LegalHoldRequest>,
+) -> Result<(StatusCode, Json<LegalHold>), AppError> {
+    let admin = require_admin.0;
+    request.validate().into_result()?;
+    if let Some(user_id) = request.user_id
+        && User::find_by_id(user_id, &state.db).await?.is_none()
+    {
+        return Err(AppError::NotFound("Pengguna tidak ditemukan".to_string()));
+    }
+
+    let hold = LegalHold::new(request, admin.id).create(&state.db).await?;
+    info!(
+        "Admin {} placed legal hold {} on {}",
+        admin.username,
+        hold.id,
+        hold.user_id
+            .map(|user_id| format!("user {}", user_id))
+            .or_else(|| hold.conversation_key.clone())
+            .unwrap_or_default()
+    );
+
+    Ok((StatusCode::CREATED, Json(hold)))
+}
+
+pub async fn release_legal_hold(
+    Extension(state): Extension<Arc<AppState>>,
+    require_admin: RequireRole<Admin>,
+    Path(hold_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let admin = require_admin.0;
+    if !LegalHold::release(hold_id, &state.db).await? {
+        return Err(AppError::NotFound("Legal hold tidak ditemukan".to_string()));
+    }
+    info!("Admin {} released legal hold {}", admin.username, hold_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn parse_conversation_key(key: &str) -> Result<ConversationKey, AppError> {
+    ConversationKey::parse(key).ok_or_else(|| {
+        AppError::Validation("Percakapan harus `public` atau `direct:<id>:<id>`".to_string())
+    })
+}
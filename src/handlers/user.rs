@@ -1,23 +1,71 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
-use axum::{Extension, Json};
+use axum::{
+    Extension, Json,
+    body::Bytes,
+    extract::{Path, Query},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use tracing::info;
+use uuid::Uuid;
 
 use crate::{
+    config::{jwt::Claims, profile::get_avatar_size},
+    handlers::{
+        auth::{send_verification_email, validate_new_email},
+        websocket::{broadcast_user_updated, disconnect_session},
+    },
     middleware::auth::{AppState, AuthUser},
     models::{
+        contact::{Contact, Privacy},
+        directory::SearchUsersQuery,
+        email_verification::ChangeEmailRequest,
         errors::AppError,
+        profile::{Avatar, ProfileResponse, UpdateProfileRequest},
+        refresh_token::RefreshToken,
+        session::{Session, SessionResponse},
         user::{User, UserResponse},
+        user_cache,
     },
+    utils::avatar::resize_avatar,
 };
 
 pub async fn get_online_users(
     Extension(state): Extension<Arc<AppState>>,
-    _: AuthUser,
+    auth_user: AuthUser,
 ) -> Result<Json<Vec<UserResponse>>, AppError> {
-    let users = User::get_online_users(&state.db).await?;
+    let users = User::get_online_users(auth_user.0.id, &state.db).await?;
     Ok(Json(users))
 }
 
+/// Direktori pengguna dengan pencarian, filter, dan paging.
+pub async fn search_users(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Query(query): Query<SearchUsersQuery>,
+) -> Result<Json<Vec<ProfileResponse>>, AppError> {
+    query.validate().into_result()?;
+
+    let viewer_id = auth_user.0.id;
+    let users = User::search(viewer_id, &query, &state.db).await?;
+    let contacts: HashSet<Uuid> = Contact::contact_ids(viewer_id, &state.db)
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(Json(
+        users
+            .into_iter()
+            .map(|user| {
+                let is_contact = contacts.contains(&user.id);
+                user.into_profile_for(viewer_id, is_contact)
+            })
+            .collect(),
+    ))
+}
+
 pub async fn update_online_status(
     Extension(state): Extension<Arc<AppState>>,
     auth_user: AuthUser,
@@ -37,3 +85,191 @@ pub async fn update_online_status(
 pub async fn get_current_user(auth_user: AuthUser) -> Result<Json<UserResponse>, AppError> {
     Ok(Json(auth_user.0.into_response()))
 }
+
+/// Ubah profil sendiri. Perubahan dikirim ke semua klien sebagai event `UserUpdated`.
+pub async fn update_profile(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<UpdateProfileRequest>,
+) -> Result<Json<UserResponse>, AppError> {
+    request.validate().into_result()?;
+
+    let user = request.apply(auth_user.0).update_profile(&state.db).await?;
+    user_cache::store(&user);
+    broadcast_user_updated(user.clone());
+
+    Ok(Json(user.into_response()))
+}
+
+/// Profil publik pengguna lain. Status online disembunyikan jika pengaturan privasinya
+/// tidak mengizinkan.
+pub async fn get_user_profile(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<ProfileResponse>, AppError> {
+    let viewer_id = auth_user.0.id;
+    let user = User::find_by_id(user_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Pengguna tidak ditemukan".to_string()))?;
+
+    let is_contact = user.privacy == Privacy::Contacts
+        && Contact::are_contacts(viewer_id, user.id, &state.db).await?;
+
+    Ok(Json(user.into_profile_for(viewer_id, is_contact)))
+}
+
+/// Unggah avatar baru sebagai body mentah (PNG, JPEG, WebP, atau GIF). Server memotongnya
+/// menjadi persegi, memperkecilnya, dan menyimpannya sebagai PNG.
+pub async fn upload_avatar(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    body: Bytes,
+) -> Result<Json<UserResponse>, AppError> {
+    let user = auth_user.0;
+    if body.is_empty() {
+        return Err(AppError::Validation("File avatar wajib diisi".to_string()));
+    }
+
+    let size = get_avatar_size();
+    let png = tokio::task::spawn_blocking(move || resize_avatar(&body, size))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map_err(|_| {
+            AppError::Validation(
+                "File bukan gambar yang didukung (PNG, JPEG, WebP, atau GIF)".to_string(),
+            )
+        })?;
+
+    let avatar = Avatar::new(user.id, png).save(&state.db).await?;
+    info!("User {} updated avatar", user.username);
+
+    let user = User {
+        avatar_updated_at: Some(avatar.updated_at),
+        updated_at: avatar.updated_at,
+        ..user
+    };
+    user_cache::store(&user);
+    broadcast_user_updated(user.clone());
+
+    Ok(Json(user.into_response()))
+}
+
+pub async fn delete_avatar(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, AppError> {
+    let user = auth_user.0;
+    if !Avatar::delete(user.id, &state.db).await? {
+        return Err(AppError::NotFound("Avatar tidak ditemukan".to_string()));
+    }
+
+    let user = User {
+        avatar_updated_at: None,
+        ..user
+    };
+    user_cache::store(&user);
+    broadcast_user_updated(user);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Gambar avatar. Publik supaya bisa dipakai langsung di `<img src>`.
+pub async fn get_avatar(
+    Extension(state): Extension<Arc<AppState>>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let avatar = Avatar::find(user_id, &state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Avatar tidak ditemukan".to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, avatar.content_type),
+            (header::CACHE_CONTROL, "public, max-age=86400".to_string()),
+        ],
+        avatar.data,
+    )
+        .into_response())
+}
+
+pub async fn list_sessions(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<SessionResponse>>, AppError> {
+    let sessions = Session::list_active_for_user(auth_user.0.id, &state.db).await?;
+
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|session| session.into_response(claims.sid))
+            .collect(),
+    ))
+}
+
+/// Cabut sesi: refresh token-nya dibatalkan, access token-nya ditolak, dan WebSocket-nya ditutup.
+pub async fn revoke_session(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    if !Session::revoke(session_id, auth_user.0.id, &state.db).await? {
+        return Err(AppError::NotFound("Sesi tidak ditemukan".to_string()));
+    }
+
+    RefreshToken::revoke_family(session_id, &state.db).await?;
+    disconnect_session(session_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Ganti email. Email baru harus diverifikasi ulang lewat tautan yang dikirim ke alamat tersebut.
+pub async fn change_email(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+    Json(request): Json<ChangeEmailRequest>,
+) -> Result<Json<UserResponse>, AppError> {
+    let user = auth_user.0;
+    let email = validate_new_email(&request.email, Some(user.id), &state.db).await?;
+
+    let unchanged = user
+        .email
+        .as_deref()
+        .is_some_and(|current| current.eq_ignore_ascii_case(&email));
+    if unchanged && user.is_email_verified() {
+        return Ok(Json(user.into_response()));
+    }
+
+    user.update_email(Some(&email), &state.db).await?;
+    send_verification_email(&state, &user, &email).await?;
+
+    Ok(Json(
+        User {
+            email: Some(email),
+            email_verified_at: None,
+            ..user
+        }
+        .into_response(),
+    ))
+}
+
+/// Kirim ulang tautan verifikasi ke email pengguna.
+pub async fn resend_email_verification(
+    Extension(state): Extension<Arc<AppState>>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, AppError> {
+    let user = auth_user.0;
+
+    let email = user
+        .email
+        .clone()
+        .ok_or_else(|| AppError::Validation("Pengguna belum memiliki email".to_string()))?;
+    if user.is_email_verified() {
+        return Err(AppError::Validation("Email sudah diverifikasi".to_string()));
+    }
+
+    send_verification_email(&state, &user, &email).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
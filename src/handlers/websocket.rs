@@ -1,11 +1,16 @@
-use std::sync::Arc;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+};
 
 use axum::{
+    Json,
     extract::{
-        Query, State,
-        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+        State,
+        ws::{CloseFrame, Message as WsMessage, WebSocket, WebSocketUpgrade, close_code},
     },
-    response::IntoResponse,
+    http::{HeaderMap, header},
+    response::Response,
 };
 use dashmap::DashMap;
 use futures::{
@@ -14,20 +19,112 @@ use futures::{
 };
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc::{self, Receiver, Sender};
-use tracing::{debug, error, info};
+use sqlx::postgres::PgPool;
+use tokio::sync::{
+    mpsc::{self, Receiver, Sender, error::TrySendError},
+    watch,
+};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use crate::{
-    config::jwt::validate_token,
-    middleware::auth::AppState,
+    config::websocket::{
+        SlowConsumerPolicy, get_slow_consumer_policy, get_ws_auth_timeout, get_ws_buffer_size,
+    },
+    handlers::message::{check_direct_message, ensure_can_post_public},
+    middleware::auth::{Admin, AppState, Credential, RequireRole, authenticate},
     models::{
+        api_token::{PersonalAccessToken, TokenScope},
+        block::{BlockList, UserBlock},
+        contact::{Contact, Privacy},
+        errors::AppError,
         message::{Message, MessageRequest, MessageResponse},
+        profile::ProfileResponse,
+        session::Session,
         user::User,
     },
 };
 
-static CONNECTIONS: Lazy<DashMap<Uuid, Sender<WebSocketMessage>>> = Lazy::new(DashMap::new);
+/// Batas waktu menunggu klien membalas close frame sebelum socket diputus paksa.
+const CLOSE_HANDSHAKE_TIMEOUT: tokio::time::Duration = tokio::time::Duration::from_secs(5);
+
+static CONNECTIONS: Lazy<DashMap<Uuid, Connection>> = Lazy::new(DashMap::new);
+static WS_BUFFER_SIZE: Lazy<usize> = Lazy::new(get_ws_buffer_size);
+static WS_AUTH_TIMEOUT: Lazy<tokio::time::Duration> =
+    Lazy::new(|| tokio::time::Duration::from_secs(get_ws_auth_timeout()));
+static SLOW_CONSUMER_POLICY: Lazy<SlowConsumerPolicy> = Lazy::new(get_slow_consumer_policy);
+static METRICS: WsMetrics = WsMetrics::new();
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Handle sebuah koneksi aktif di registry.
+#[derive(Clone)]
+struct Connection {
+    id: Uuid,
+    /// ID kredensial yang dipakai untuk membuka koneksi: `jti` access token, atau ID
+    /// personal access token (lihat [`Credential::id`]).
+    jti: String,
+    session_id: Option<Uuid>,
+    tx: Sender<WebSocketMessage>,
+    needs_resume: Arc<AtomicBool>,
+    close_tx: Arc<watch::Sender<Option<CloseRequest>>>,
+}
+
+/// Permintaan penutupan koneksi dari server.
+#[derive(Clone)]
+struct CloseRequest {
+    frame: CloseFrame,
+    /// Kirim dulu frame yang masih mengantre sebelum close frame.
+    flush: bool,
+}
+
+impl Connection {
+    fn close(&self, code: u16, reason: &str, flush: bool) {
+        let _ = self.close_tx.send(Some(CloseRequest {
+            frame: CloseFrame {
+                code,
+                reason: reason.into(),
+            },
+            flush,
+        }));
+    }
+}
+
+struct WsMetrics {
+    frames_sent: AtomicU64,
+    frames_dropped: AtomicU64,
+    slow_consumer_disconnects: AtomicU64,
+    resume_requests: AtomicU64,
+}
+
+impl WsMetrics {
+    const fn new() -> Self {
+        Self {
+            frames_sent: AtomicU64::new(0),
+            frames_dropped: AtomicU64::new(0),
+            slow_consumer_disconnects: AtomicU64::new(0),
+            resume_requests: AtomicU64::new(0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WsMetricsSnapshot {
+    pub active_connections: usize,
+    pub frames_sent: u64,
+    pub frames_dropped: u64,
+    pub slow_consumer_disconnects: u64,
+    pub resume_requests: u64,
+}
+
+pub fn metrics_snapshot() -> WsMetricsSnapshot {
+    WsMetricsSnapshot {
+        active_connections: CONNECTIONS.len(),
+        frames_sent: METRICS.frames_sent.load(Ordering::Relaxed),
+        frames_dropped: METRICS.frames_dropped.load(Ordering::Relaxed),
+        slow_consumer_disconnects: METRICS.slow_consumer_disconnects.load(Ordering::Relaxed),
+        resume_requests: METRICS.resume_requests.load(Ordering::Relaxed),
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", content = "data")]
@@ -35,6 +132,9 @@ pub enum WebSocketMessage {
     Text {
         content: String,
         receiver_id: Option<Uuid>,
+        /// Pengirim dibisukan penerima: tampilkan pesannya tanpa notifikasi.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        silent: bool,
     },
     UserStatus {
         user_id: Uuid,
@@ -44,93 +144,261 @@ pub enum WebSocketMessage {
     Error {
         message: String,
     },
+    /// Frame pertama dari klien yang tidak mengirim token lewat `Sec-WebSocket-Protocol`.
+    Authenticate {
+        token: String,
+    },
+    Authenticated {
+        user_id: Uuid,
+    },
+    /// Sebagian frame dibuang karena klien terlalu lambat; klien perlu memuat ulang riwayat.
+    ResumeRequired,
+    /// Pesan dihapus moderator; klien perlu menghapusnya dari tampilan.
+    MessageDeleted {
+        message_id: Uuid,
+    },
+    /// Profil pengguna berubah; klien perlu memperbarui nama dan avatar yang disimpan.
+    UserUpdated {
+        user: ProfileResponse,
+    },
 }
 
-#[derive(Debug, Deserialize)]
-pub struct WebSocketParams {
-    token: String,
+/// Subprotocol yang dipilih server saat handshake WebSocket.
+pub const WS_PROTOCOL: &str = "chat";
+
+/// Prefix entri `Sec-WebSocket-Protocol` yang membawa token akses, mis. `bearer.<jwt>`.
+pub const WS_TOKEN_PROTOCOL_PREFIX: &str = "bearer.";
+
+/// Kode close aplikasi (rentang 4000-4999) yang dikirim server.
+pub mod app_close_code {
+    /// Token tidak valid atau frame pertama bukan `Authenticate`.
+    pub const UNAUTHORIZED: u16 = 4001;
+    /// Token yang dipakai koneksi dicabut (logout).
+    pub const TOKEN_REVOKED: u16 = 4003;
+    /// Klien tidak mengirim `Authenticate` sebelum batas waktu.
+    pub const AUTH_TIMEOUT: u16 = 4008;
 }
 
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
-    Query(params): Query<WebSocketParams>,
+    headers: HeaderMap,
     State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
-    let claims = match validate_token(&params.token) {
-        Ok(claims) => claims,
-        Err(_) => {
-            return ws.on_upgrade(|_socket| async {
-                error!("Token tidak valid");
-            });
+) -> Result<Response, AppError> {
+    if is_shutting_down() {
+        return Err(AppError::ServiceUnavailable(
+            "Server sedang restart".to_string(),
+        ));
+    }
+
+    let ws = ws.protocols([WS_PROTOCOL]);
+
+    match protocol_token(&headers) {
+        Some(token) => {
+            let (user, credential) = authenticate_ws(token, &state.db).await?;
+            Ok(ws.on_upgrade(move |socket| start_session(socket, user, credential, state)))
         }
-    };
+        None => Ok(ws.on_upgrade(move |socket| authenticate_first_frame(socket, state))),
+    }
+}
 
-    let user_id = match Uuid::parse_str(&claims.sub) {
-        Ok(id) => id,
+/// Ambil token dari entri `bearer.<jwt>` pada header `Sec-WebSocket-Protocol`.
+pub fn protocol_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|protocol| protocol.trim().strip_prefix(WS_TOKEN_PROTOCOL_PREFIX))
+        .filter(|token| !token.is_empty())
+}
+
+async fn authenticate_first_frame(mut socket: WebSocket, state: Arc<AppState>) {
+    let token = match tokio::time::timeout(*WS_AUTH_TIMEOUT, read_authenticate(&mut socket)).await {
+        Ok(Some(token)) => token,
+        Ok(None) => {
+            close_socket(
+                socket,
+                app_close_code::UNAUTHORIZED,
+                "Frame pertama harus Authenticate",
+            )
+            .await;
+            return;
+        }
         Err(_) => {
-            return ws.on_upgrade(|_socket| async {
-                error!("ID pengguna tidak valid");
-            });
+            close_socket(
+                socket,
+                app_close_code::AUTH_TIMEOUT,
+                "Batas waktu autentikasi habis",
+            )
+            .await;
+            return;
         }
     };
 
-    let user = match User::find_by_id(user_id, &state.db).await {
-        Ok(Some(user)) => user,
-        _ => {
-            return ws.on_upgrade(|_socket| async {
-                error!("Pengguna tidak ditemukan");
-            });
+    let (user, credential) = match authenticate_ws(&token, &state.db).await {
+        Ok(authenticated) => authenticated,
+        Err(e) => {
+            debug!("Autentikasi WebSocket gagal: {}", e);
+            close_socket(socket, app_close_code::UNAUTHORIZED, "Token tidak valid").await;
+            return;
         }
     };
 
+    let ack = WebSocketMessage::Authenticated { user_id: user.id };
+    if let Ok(ack) = serde_json::to_string(&ack)
+        && socket.send(WsMessage::Text(ack.into())).await.is_err()
+    {
+        return;
+    }
+
+    start_session(socket, user, credential, state).await;
+}
+
+/// Seperti [`authenticate`], tetapi personal access token wajib memiliki scope
+/// `messages:read` untuk membuka WebSocket.
+async fn authenticate_ws(token: &str, db: &PgPool) -> Result<(User, Credential), AppError> {
+    let (user, credential) = authenticate(token, db).await?;
+
+    if !credential.allows(TokenScope::MessagesRead) {
+        return Err(AppError::Forbidden(format!(
+            "Token tidak memiliki scope {}",
+            TokenScope::MessagesRead
+        )));
+    }
+
+    Ok((user, credential))
+}
+
+async fn read_authenticate(socket: &mut WebSocket) -> Option<String> {
+    while let Some(Ok(msg)) = socket.recv().await {
+        match msg {
+            WsMessage::Text(text) => {
+                return match serde_json::from_str(&text) {
+                    Ok(WebSocketMessage::Authenticate { token }) => Some(token),
+                    _ => None,
+                };
+            }
+            WsMessage::Ping(_) | WsMessage::Pong(_) => continue,
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+async fn close_socket(mut socket: WebSocket, code: u16, reason: &str) {
+    let _ = socket
+        .send(WsMessage::Close(Some(CloseFrame {
+            code,
+            reason: reason.into(),
+        })))
+        .await;
+}
+
+async fn start_session(
+    socket: WebSocket,
+    user: User,
+    credential: Credential,
+    state: Arc<AppState>,
+) {
+    if let Some(session_id) = credential.session_id()
+        && let Err(e) = Session::touch(session_id, &state.db).await
+    {
+        error!("Error updating session: {}", e);
+    }
+
     if let Err(e) = user.update_online_status(true, &state.db).await {
         error!("Error updating online status: {}", e);
     }
 
     info!("User {} connected (id: {})", user.username, user.id);
 
-    broadcast_user_status(user.id, &user.username, true).await;
+    broadcast_user_status(&user, true, &state.db).await;
 
-    ws.on_upgrade(move |socket| handle_socket(socket, user, state))
+    handle_socket(socket, user, credential, state).await;
 }
 
-async fn handle_socket(socket: WebSocket, user: User, state: Arc<AppState>) {
-    let (tx, rx) = mpsc::channel(100);
+async fn handle_socket(
+    socket: WebSocket,
+    user: User,
+    credential: Credential,
+    state: Arc<AppState>,
+) {
+    let (tx, rx) = mpsc::channel(*WS_BUFFER_SIZE);
+    let (close_tx, close_rx) = watch::channel(None);
+    let connection = Connection {
+        id: Uuid::new_v4(),
+        jti: credential.id(),
+        session_id: credential.session_id(),
+        tx,
+        needs_resume: Arc::new(AtomicBool::new(false)),
+        close_tx: Arc::new(close_tx),
+    };
+    let connection_id = connection.id;
+    let needs_resume = connection.needs_resume.clone();
 
-    CONNECTIONS.insert(user.id, tx);
+    CONNECTIONS.insert(user.id, connection);
 
     let (sender, receiver) = socket.split();
 
-    let user_copy = user.clone();
-    let state_copy = state.clone();
-
-    tokio::spawn(handle_incoming(receiver, user_copy, state_copy));
-    tokio::spawn(handle_outgoing(sender, rx, user.clone()));
+    let mut incoming = tokio::spawn(handle_incoming(
+        receiver,
+        user.clone(),
+        credential,
+        state.clone(),
+    ));
+    let mut outgoing = tokio::spawn(handle_outgoing(
+        sender,
+        rx,
+        close_rx,
+        needs_resume,
+        user.clone(),
+    ));
+
+    tokio::select! {
+        _ = &mut incoming => outgoing.abort(),
+        _ = &mut outgoing => {
+            if tokio::time::timeout(CLOSE_HANDSHAKE_TIMEOUT, &mut incoming)
+                .await
+                .is_err()
+            {
+                incoming.abort();
+            }
+        }
+    }
 
-    let user_id = user.id;
-    let username = user.username.clone();
-    let db = state.db.clone();
+    remove_connection(user.id, connection_id);
 
-    tokio::spawn(async move {
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    // Saat shutdown, status offline diperbarui sekaligus oleh `drain_connections`.
+    if is_shutting_down() {
+        return;
+    }
 
-        CONNECTIONS.remove(&user_id);
+    if CONNECTIONS.contains_key(&user.id) {
+        info!("User {} closed a replaced connection", user.username);
+        return;
+    }
 
-        if let Err(e) = user.update_online_status(false, &db).await {
-            error!("Error updating offline status: {}", e);
-        }
+    if let Err(e) = user.update_online_status(false, &state.db).await {
+        error!("Error updating offline status: {}", e);
+    }
 
-        broadcast_user_status(user_id, &username, false).await;
+    broadcast_user_status(&user, false, &state.db).await;
 
-        info!("User {} disconnected", username);
-    });
+    info!("User {} disconnected", user.username);
 }
 
-async fn handle_incoming(mut receiver: SplitStream<WebSocket>, user: User, state: Arc<AppState>) {
+async fn handle_incoming(
+    mut receiver: SplitStream<WebSocket>,
+    user: User,
+    credential: Credential,
+    state: Arc<AppState>,
+) {
     while let Some(result) = receiver.next().await {
         match result {
+            Ok(WsMessage::Close(_)) => break,
             Ok(msg) => {
-                if let Err(e) = process_message(msg, &user, &state).await {
+                if let Err(e) = process_message(msg, &user, &credential, &state).await {
                     error!("Error processing message: {}", e);
                 }
             }
@@ -149,21 +417,45 @@ async fn handle_incoming(mut receiver: SplitStream<WebSocket>, user: User, state
 async fn handle_outgoing(
     mut sender: SplitSink<WebSocket, WsMessage>,
     mut receiver: Receiver<WebSocketMessage>,
+    mut close_rx: watch::Receiver<Option<CloseRequest>>,
+    needs_resume: Arc<AtomicBool>,
     user: User,
 ) {
-    while let Some(message) = receiver.recv().await {
-        let msg_str = match serde_json::to_string(&message) {
-            Ok(s) => s,
-            Err(e) => {
-                error!("Error serializing message: {}", e);
-                continue;
+    loop {
+        let message = tokio::select! {
+            message = receiver.recv() => match message {
+                Some(message) => message,
+                None => break,
+            },
+            changed = close_rx.changed() => {
+                let request = match changed {
+                    Ok(()) => close_rx.borrow_and_update().clone(),
+                    Err(_) => None,
+                };
+                if let Some(request) = request {
+                    if request.flush {
+                        while let Ok(message) = receiver.try_recv() {
+                            if !send_json(&mut sender, &message, &user).await {
+                                break;
+                            }
+                        }
+                    }
+                    let _ = sender.send(WsMessage::Close(Some(request.frame))).await;
+                }
+                break;
             }
         };
 
-        if let Err(e) = sender.send(WsMessage::Text(msg_str.into())).await {
-            error!("Error sending message to {}: {}", user.username, e);
+        if !send_json(&mut sender, &message, &user).await {
             break;
         }
+
+        if needs_resume.load(Ordering::Acquire) && receiver.is_empty() {
+            if !send_json(&mut sender, &WebSocketMessage::ResumeRequired, &user).await {
+                break;
+            }
+            needs_resume.store(false, Ordering::Release);
+        }
     }
     debug!(
         "Outgoing message handler for user {} stopped",
@@ -171,9 +463,31 @@ async fn handle_outgoing(
     );
 }
 
+async fn send_json(
+    sender: &mut SplitSink<WebSocket, WsMessage>,
+    message: &WebSocketMessage,
+    user: &User,
+) -> bool {
+    let msg_str = match serde_json::to_string(message) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Error serializing message: {}", e);
+            return true;
+        }
+    };
+
+    if let Err(e) = sender.send(WsMessage::Text(msg_str.into())).await {
+        error!("Error sending message to {}: {}", user.username, e);
+        return false;
+    }
+
+    true
+}
+
 async fn process_message(
     msg: WsMessage,
     user: &User,
+    credential: &Credential,
     state: &Arc<AppState>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     match msg {
@@ -183,8 +497,47 @@ async fn process_message(
             if let WebSocketMessage::Text {
                 content,
                 receiver_id,
+                ..
             } = ws_message
             {
+                if !credential.allows(TokenScope::MessagesWrite) {
+                    send_to_user(
+                        user.id,
+                        WebSocketMessage::Error {
+                            message: format!(
+                                "Token tidak memiliki scope {}",
+                                TokenScope::MessagesWrite
+                            ),
+                        },
+                    );
+                    return Ok(());
+                }
+
+                let target = match receiver_id {
+                    Some(receiver_id) => {
+                        match check_direct_message(user, receiver_id, &state.db).await {
+                            Ok(target) => Some(target),
+                            Err(AppError::Forbidden(message) | AppError::NotFound(message)) => {
+                                send_to_user(user.id, WebSocketMessage::Error { message });
+                                return Ok(());
+                            }
+                            Err(e) => return Err(e.into()),
+                        }
+                    }
+                    None => None,
+                };
+
+                if receiver_id.is_none() && ensure_can_post_public(user).is_err() {
+                    // Status verifikasi bisa berubah setelah koneksi dibuka.
+                    let current = User::find_by_id(user.id, &state.db)
+                        .await?
+                        .unwrap_or_else(|| user.clone());
+                    if let Err(AppError::Forbidden(message)) = ensure_can_post_public(&current) {
+                        send_to_user(user.id, WebSocketMessage::Error { message });
+                        return Ok(());
+                    }
+                }
+
                 let msg_request = MessageRequest {
                     content,
                     receiver_id,
@@ -199,37 +552,27 @@ async fn process_message(
                     sender_id: user.id,
                     sender_username: user.username.clone(),
                     receiver_id: saved_message.receiver_id,
-                    receiver_username: if let Some(receiver_id) = saved_message.receiver_id {
-                        let receiver = User::find_by_id(receiver_id, &state.db).await?;
-                        receiver.map(|r| r.username)
-                    } else {
-                        None
-                    },
+                    receiver_username: target
+                        .as_ref()
+                        .map(|target| target.receiver.username.clone()),
                     content: saved_message.content,
                     is_read: saved_message.is_read,
                     created_at: saved_message.created_at,
                 };
-
-                if let Some(receiver_id) = saved_message.receiver_id {
-                    if let Some(receiver_tx) = CONNECTIONS.get(&receiver_id) {
-                        let _ = receiver_tx
-                            .send(WebSocketMessage::Text {
-                                content: serde_json::to_string(&response)?,
-                                receiver_id: Some(user.id),
-                            })
-                            .await;
-                    }
-                } else {
-                    for conn in CONNECTIONS.iter() {
-                        if *conn.key() != user.id {
-                            let _ = conn
-                                .value()
-                                .send(WebSocketMessage::Text {
-                                    content: serde_json::to_string(&response)?,
-                                    receiver_id: None,
-                                })
-                                .await;
-                        }
+                let content = serde_json::to_string(&response)?;
+
+                match target {
+                    Some(target) => send_to_user(
+                        target.receiver.id,
+                        WebSocketMessage::Text {
+                            content,
+                            receiver_id: Some(user.id),
+                            silent: target.muted,
+                        },
+                    ),
+                    None => {
+                        let blocks = UserBlock::targeting(user.id, &state.db).await?;
+                        broadcast_public_message(user.id, content, &blocks);
                     }
                 }
             }
@@ -241,16 +584,269 @@ async fn process_message(
     Ok(())
 }
 
-async fn broadcast_user_status(user_id: Uuid, username: &str, is_online: bool) {
+/// Siarkan perubahan status online ke pengguna yang boleh melihatnya menurut pengaturan
+/// privasi `user`.
+pub async fn broadcast_user_status(user: &User, is_online: bool, db: &PgPool) {
     let status_message = WebSocketMessage::UserStatus {
-        user_id,
-        username: username.to_string(),
+        user_id: user.id,
+        username: user.username.clone(),
         is_online,
     };
 
-    for conn in CONNECTIONS.iter() {
-        if *conn.key() != user_id {
-            let _ = conn.value().send(status_message.clone()).await;
+    match user.privacy {
+        Privacy::Everyone => broadcast(status_message, user.id),
+        Privacy::Contacts => match Contact::contact_ids(user.id, db).await {
+            Ok(contact_ids) => {
+                for contact_id in contact_ids {
+                    send_to_user(contact_id, status_message.clone());
+                }
+            }
+            Err(e) => error!("Error loading contacts of {}: {}", user.username, e),
+        },
+        Privacy::Nobody => {}
+    }
+}
+
+/// Beri tahu klien yang bisa melihat pesan tersebut bahwa pesan sudah dihapus.
+pub fn broadcast_message_deleted(message: &Message) {
+    let event = WebSocketMessage::MessageDeleted {
+        message_id: message.id,
+    };
+
+    match message.receiver_id {
+        Some(receiver_id) => {
+            send_to_user(message.sender_id, event.clone());
+            send_to_user(receiver_id, event);
+        }
+        None => broadcast(event, Uuid::nil()),
+    }
+}
+
+/// Kirim profil terbaru ke semua klien, termasuk koneksi milik pengguna itu sendiri.
+/// Status online hanya disertakan jika privasi pengguna `Everyone`; kontak mendapatkannya
+/// lewat `UserStatus`.
+pub fn broadcast_user_updated(user: User) {
+    let profile = if user.privacy == Privacy::Everyone {
+        user.into_profile()
+    } else {
+        user.into_profile().hide_presence()
+    };
+
+    broadcast(WebSocketMessage::UserUpdated { user: profile }, Uuid::nil());
+}
+
+/// Kirim pesan ke satu pengguna tanpa menunggu buffer kliennya.
+fn send_to_user(user_id: Uuid, message: WebSocketMessage) {
+    let connection = CONNECTIONS.get(&user_id).map(|conn| conn.value().clone());
+
+    if let Some(connection) = connection {
+        deliver(user_id, &connection, message);
+    }
+}
+
+/// Siarkan pesan publik dari `sender_id`. Pengguna yang memblokir pengirim tidak
+/// menerimanya, dan pengguna yang membisukannya menerimanya sebagai `silent`.
+pub fn broadcast_public_message(sender_id: Uuid, content: String, blocks: &BlockList) {
+    let targets: Vec<(Uuid, Connection)> = CONNECTIONS
+        .iter()
+        .filter(|conn| *conn.key() != sender_id && !blocks.hides_from(*conn.key()))
+        .map(|conn| (*conn.key(), conn.value().clone()))
+        .collect();
+
+    for (user_id, connection) in targets {
+        let message = WebSocketMessage::Text {
+            content: content.clone(),
+            receiver_id: None,
+            silent: blocks.silences_for(user_id),
+        };
+        deliver(user_id, &connection, message);
+    }
+}
+
+/// Kirim pesan ke semua koneksi kecuali `except`.
+///
+/// Handle koneksi disalin dulu supaya guard shard DashMap sudah dilepas saat pengiriman.
+fn broadcast(message: WebSocketMessage, except: Uuid) {
+    let targets: Vec<(Uuid, Connection)> = CONNECTIONS
+        .iter()
+        .filter(|conn| *conn.key() != except)
+        .map(|conn| (*conn.key(), conn.value().clone()))
+        .collect();
+
+    for (user_id, connection) in targets {
+        deliver(user_id, &connection, message.clone());
+    }
+}
+
+fn deliver(user_id: Uuid, connection: &Connection, message: WebSocketMessage) {
+    match enqueue(
+        user_id,
+        &connection.tx,
+        &connection.needs_resume,
+        *SLOW_CONSUMER_POLICY,
+        message,
+    ) {
+        Delivery::Sent | Delivery::Dropped => {}
+        Delivery::Disconnect => {
+            connection.close(close_code::AGAIN, "Koneksi terlalu lambat", false);
+            remove_connection(user_id, connection.id);
+        }
+        Delivery::Closed => remove_connection(user_id, connection.id),
+    }
+}
+
+/// Hasil memasukkan frame ke antrean keluar sebuah koneksi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Sent,
+    /// Frame dibuang: buffer penuh dengan kebijakan `drop` atau `resume`, atau koneksi masih
+    /// menunggu resume.
+    Dropped,
+    /// Buffer penuh dengan kebijakan `disconnect`; koneksi harus ditutup.
+    Disconnect,
+    /// Penerima sudah menutup antreannya.
+    Closed,
+}
+
+/// Masukkan frame ke antrean keluar tanpa menunggu, dan terapkan `policy` jika buffer penuh.
+/// Dengan kebijakan `resume`, `needs_resume` diset dan frame berikutnya dibuang sampai klien
+/// menghabiskan antreannya.
+pub fn enqueue(
+    user_id: Uuid,
+    tx: &Sender<WebSocketMessage>,
+    needs_resume: &AtomicBool,
+    policy: SlowConsumerPolicy,
+    message: WebSocketMessage,
+) -> Delivery {
+    if needs_resume.load(Ordering::Acquire) {
+        METRICS.frames_dropped.fetch_add(1, Ordering::Relaxed);
+        return Delivery::Dropped;
+    }
+
+    match tx.try_send(message) {
+        Ok(()) => {
+            METRICS.frames_sent.fetch_add(1, Ordering::Relaxed);
+            Delivery::Sent
+        }
+        Err(TrySendError::Full(_)) => handle_full_buffer(user_id, needs_resume, policy),
+        Err(TrySendError::Closed(_)) => Delivery::Closed,
+    }
+}
+
+fn handle_full_buffer(
+    user_id: Uuid,
+    needs_resume: &AtomicBool,
+    policy: SlowConsumerPolicy,
+) -> Delivery {
+    METRICS.frames_dropped.fetch_add(1, Ordering::Relaxed);
+
+    match policy {
+        SlowConsumerPolicy::Drop => {
+            warn!("Buffer WebSocket user {} penuh, frame dibuang", user_id);
+            Delivery::Dropped
+        }
+        SlowConsumerPolicy::Disconnect => {
+            METRICS
+                .slow_consumer_disconnects
+                .fetch_add(1, Ordering::Relaxed);
+            warn!("Buffer WebSocket user {} penuh, koneksi ditutup", user_id);
+            Delivery::Disconnect
         }
+        SlowConsumerPolicy::Resume => {
+            if !needs_resume.swap(true, Ordering::AcqRel) {
+                METRICS.resume_requests.fetch_add(1, Ordering::Relaxed);
+                warn!("Buffer WebSocket user {} penuh, menunggu resume", user_id);
+            }
+            Delivery::Dropped
+        }
+    }
+}
+
+/// Hapus koneksi dari registry hanya jika masih koneksi yang sama.
+fn remove_connection(user_id: Uuid, connection_id: Uuid) {
+    CONNECTIONS.remove_if(&user_id, |_, conn| conn.id == connection_id);
+}
+
+/// Tutup koneksi yang dibuka dengan access token `jti`. Mengembalikan jumlah koneksi.
+pub fn disconnect_token(jti: &str) -> usize {
+    close_matching(|_, conn| !jti.is_empty() && conn.jti == jti)
+}
+
+/// Tutup koneksi yang dibuka dengan personal access token `token_id`.
+pub fn disconnect_api_token(token_id: Uuid) -> usize {
+    disconnect_token(&PersonalAccessToken::credential_id(token_id))
+}
+
+/// Tutup koneksi yang dibuka dengan token dari sesi `session_id`. Mengembalikan jumlah koneksi.
+pub fn disconnect_session(session_id: Uuid) -> usize {
+    close_matching(|_, conn| conn.session_id == Some(session_id))
+}
+
+/// Tutup semua koneksi milik pengguna. Mengembalikan jumlah koneksi.
+pub fn disconnect_user(user_id: Uuid) -> usize {
+    close_matching(|conn_user_id, _| conn_user_id == user_id)
+}
+
+fn close_matching(matches: impl Fn(Uuid, &Connection) -> bool) -> usize {
+    let targets: Vec<Connection> = CONNECTIONS
+        .iter()
+        .filter(|conn| matches(*conn.key(), conn.value()))
+        .map(|conn| conn.value().clone())
+        .collect();
+
+    for connection in &targets {
+        connection.close(app_close_code::TOKEN_REVOKED, "Token sudah dicabut", true);
+    }
+
+    targets.len()
+}
+
+/// ID pengguna yang saat ini memiliki koneksi hidup di node ini.
+pub fn connected_user_ids() -> Vec<Uuid> {
+    CONNECTIONS.iter().map(|conn| *conn.key()).collect()
+}
+
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::Acquire)
+}
+
+/// Tutup semua koneksi dengan kode restart lalu tandai pengguna yang terhubung offline.
+///
+/// Antrean keluar tiap koneksi dikirim dulu sebelum close frame. Menunggu paling lama
+/// `timeout` sampai registry kosong; status offline tetap diperbarui walau batas waktu habis.
+/// Mengembalikan jumlah pengguna yang terhubung saat shutdown dimulai.
+pub async fn drain_connections(db: &PgPool, timeout: tokio::time::Duration) -> usize {
+    SHUTTING_DOWN.store(true, Ordering::Release);
+
+    let connections: Vec<(Uuid, Connection)> = CONNECTIONS
+        .iter()
+        .map(|conn| (*conn.key(), conn.value().clone()))
+        .collect();
+
+    for (_, connection) in &connections {
+        connection.close(close_code::RESTART, "Server sedang restart", true);
+    }
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    while !CONNECTIONS.is_empty() && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+
+    if !CONNECTIONS.is_empty() {
+        warn!(
+            "{} koneksi WebSocket belum tertutup saat batas waktu habis",
+            CONNECTIONS.len()
+        );
     }
+
+    let user_ids: Vec<Uuid> = connections.iter().map(|(user_id, _)| *user_id).collect();
+    if let Err(e) = User::mark_offline(&user_ids, db).await {
+        error!("Error marking users offline: {}", e);
+    }
+
+    user_ids.len()
+}
+
+pub async fn get_ws_metrics(_: RequireRole<Admin>) -> Json<WsMetricsSnapshot> {
+    Json(metrics_snapshot())
 }
@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use sqlx::postgres::PgPool;
+use tokio::{sync::Notify, task::JoinHandle, time::Duration};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    config::account::{get_data_export_dir, get_data_export_ttl},
+    handlers::websocket::{disconnect_user, is_shutting_down},
+    middleware::auth::AppState,
+    models::{
+        account_deletion::AccountDeletion,
+        data_export::{DataExport, STALE_EXPORT_AFTER},
+        message::Message,
+        profile::Avatar,
+        retention::LegalHold,
+        session::Session,
+        user::User,
+        user_cache,
+    },
+    utils::archive::{ArchiveEntry, build_zip},
+};
+
+const ACCOUNT_JOB_INTERVAL: Duration = Duration::from_secs(60);
+
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// Jalankan job akun lebih awal, mis. setelah ekspor baru diminta.
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Hapus akun yang masa tenggangnya sudah lewat, buat arsip ekspor yang diminta, dan
+/// hapus arsip yang sudah kedaluwarsa.
+pub fn spawn(state: Arc<AppState>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(ACCOUNT_JOB_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = WAKE.notified() => {}
+            }
+
+            if is_shutting_down() {
+                break;
+            }
+
+            run_due_deletions(&state.db).await;
+            run_pending_exports(&state.db).await;
+            purge_expired_exports(&state.db).await;
+        }
+    })
+}
+
+async fn run_due_deletions(db: &PgPool) {
+    let deletions = match AccountDeletion::due(Utc::now(), db).await {
+        Ok(deletions) => deletions,
+        Err(e) => {
+            error!("Error loading account deletions: {}", e);
+            return;
+        }
+    };
+
+    for deletion in deletions {
+        match delete_account(&deletion, db).await {
+            Ok(None) => warn!(
+                "Postponed deletion of account {}: covered by an active legal hold",
+                deletion.user_id
+            ),
+            Ok(Some(deleted)) => info!(
+                "Deleted account {} ({} account(s), messages: {})",
+                deletion.user_id,
+                deleted,
+                deletion.message_mode.as_str()
+            ),
+            Err(e) => error!("Error deleting account {}: {}", deletion.user_id, e),
+        }
+    }
+}
+
+/// Hapus satu akun beserta arsip ekspornya dan tutup koneksi WebSocket-nya.
+/// Mengembalikan jumlah akun yang dihapus (pengguna dan bot-botnya), atau `None` jika akun
+/// atau percakapannya ditahan legal hold; penghapusan dicoba lagi di putaran berikutnya.
+pub async fn delete_account(deletion: &AccountDeletion, db: &PgPool) -> Result<Option<usize>> {
+    if LegalHold::covers_account(deletion.user_id, db).await? {
+        return Ok(None);
+    }
+
+    for export in DataExport::delete_for_user(deletion.user_id, db).await? {
+        remove_export_file(&export).await;
+    }
+
+    let user_ids = deletion.execute(db).await?;
+    for user_id in &user_ids {
+        disconnect_user(*user_id);
+        user_cache::invalidate(*user_id);
+    }
+
+    Ok(Some(user_ids.len()))
+}
+
+async fn run_pending_exports(db: &PgPool) {
+    match DataExport::requeue_stale(Utc::now() - STALE_EXPORT_AFTER, db).await {
+        Ok(0) => {}
+        Ok(requeued) => warn!("Requeued {} stale data export(s)", requeued),
+        Err(e) => error!("Error requeueing data exports: {}", e),
+    }
+
+    loop {
+        let export = match DataExport::claim_next(db).await {
+            Ok(Some(export)) => export,
+            Ok(None) => break,
+            Err(e) => {
+                error!("Error claiming data export: {}", e);
+                break;
+            }
+        };
+
+        let result = match write_export(&export, Path::new(&get_data_export_dir()), db).await {
+            Ok(path) => {
+                info!("Data export {} ready for {}", export.id, export.user_id);
+                export
+                    .mark_ready(&path.to_string_lossy(), get_data_export_ttl(), db)
+                    .await
+            }
+            Err(e) => {
+                error!("Error building data export {}: {}", export.id, e);
+                export.mark_failed(&e.to_string(), db).await
+            }
+        };
+
+        if let Err(e) = result {
+            error!("Error updating data export {}: {}", export.id, e);
+        }
+    }
+}
+
+async fn purge_expired_exports(db: &PgPool) {
+    match DataExport::purge_expired(db).await {
+        Ok(exports) => {
+            for export in &exports {
+                remove_export_file(export).await;
+            }
+            debug!("Purged {} expired data export(s)", exports.len());
+        }
+        Err(e) => error!("Error purging data exports: {}", e),
+    }
+}
+
+/// Isi arsip ekspor: `profile.json`, `sessions.json`, `messages.json` berisi semua
+/// pesan yang dikirim dan diterima pengguna, dan `avatar.png` jika pengguna punya avatar.
+pub async fn export_entries(user_id: Uuid, db: &PgPool) -> Result<Vec<ArchiveEntry>> {
+    let user = User::find_by_id(user_id, db)
+        .await?
+        .ok_or_else(|| anyhow!("Pengguna {} tidak ditemukan", user_id))?;
+    let sessions = Session::list_for_user(user_id, db).await?;
+    let messages = Message::list_for_user(user_id, db).await?;
+
+    let mut entries = vec![
+        ArchiveEntry::json("profile.json", &user)?,
+        ArchiveEntry::json("sessions.json", &sessions)?,
+        ArchiveEntry::json("messages.json", &messages)?,
+    ];
+    if let Some(avatar) = Avatar::find(user_id, db).await? {
+        entries.push(ArchiveEntry {
+            path: "avatar.png".to_string(),
+            contents: avatar.data,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Buat arsip ZIP ekspor di `dir` dan kembalikan path-nya.
+pub async fn write_export(export: &DataExport, dir: &Path, db: &PgPool) -> Result<PathBuf> {
+    let entries = export_entries(export.user_id, db).await?;
+    let archive = tokio::task::spawn_blocking(move || build_zip(&entries)).await??;
+
+    tokio::fs::create_dir_all(dir).await?;
+    let path = dir.join(format!("{}.zip", export.id));
+    tokio::fs::write(&path, archive).await?;
+
+    Ok(path)
+}
+
+async fn remove_export_file(export: &DataExport) {
+    if let Some(path) = &export.file_path
+        && let Err(e) = tokio::fs::remove_file(path).await
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        warn!("Error removing data export file {}: {}", path, e);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod accounts;
+pub mod presence;
+pub mod retention;
+pub mod tokens;
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::postgres::PgPool;
+use tokio::{task::JoinHandle, time::Duration};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    config::presence::{
+        get_presence_heartbeat_ttl, get_presence_node_id, get_presence_reconcile_interval,
+    },
+    handlers::websocket::{broadcast_user_status, connected_user_ids, is_shutting_down},
+    middleware::auth::AppState,
+    models::{presence::PresenceHeartbeat, user::User},
+};
+
+/// Jalankan rekonsiliasi status online saat startup lalu secara berkala.
+pub fn spawn(state: Arc<AppState>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let node_id = get_presence_node_id();
+        let ttl = Duration::from_secs(get_presence_heartbeat_ttl());
+
+        if let Some(node_id) = &node_id
+            && let Err(e) = PresenceHeartbeat::clear_node(node_id, &state.db).await
+        {
+            error!("Error clearing presence heartbeats: {}", e);
+        }
+
+        // Tick pertama langsung selesai, sehingga status basi dari proses sebelumnya
+        // dibersihkan saat startup.
+        let mut ticker =
+            tokio::time::interval(Duration::from_secs(get_presence_reconcile_interval()));
+
+        loop {
+            ticker.tick().await;
+
+            if is_shutting_down() {
+                break;
+            }
+
+            if let Err(e) = reconcile_once(&state.db, node_id.as_deref(), ttl).await {
+                error!("Error reconciling presence: {}", e);
+            }
+        }
+    })
+}
+
+/// Samakan kolom `is_online` dengan koneksi yang benar-benar hidup dan siarkan koreksinya.
+///
+/// Tanpa `node_id`, registry koneksi lokal dianggap sumber kebenaran. Dengan `node_id`,
+/// heartbeat node ini diperbarui lebih dulu dan pengguna tanpa heartbeat yang lebih baru
+/// dari `ttl` di node mana pun ditandai offline. Mengembalikan jumlah pengguna yang dikoreksi.
+pub async fn reconcile_once(db: &PgPool, node_id: Option<&str>, ttl: Duration) -> Result<usize> {
+    let live_ids = connected_user_ids();
+    let changes = presence_changes(&User::online_ids(db).await?, &live_ids);
+
+    let went_offline = match node_id {
+        Some(node_id) => {
+            PresenceHeartbeat::record(node_id, &live_ids, db).await?;
+            let cutoff = Utc::now() - chrono::Duration::from_std(ttl)?;
+            User::reset_stale_presence_by_heartbeat(cutoff, db).await?
+        }
+        None => User::reset_stale_presence(&changes.went_offline, db).await?,
+    };
+    let came_online = User::restore_live_presence(&changes.came_online, db).await?;
+
+    for user in &went_offline {
+        broadcast_user_status(user, false, db).await;
+    }
+    for user in &came_online {
+        broadcast_user_status(user, true, db).await;
+    }
+
+    let corrected = went_offline.len() + came_online.len();
+    if corrected > 0 {
+        info!(
+            "Presence dikoreksi: {} offline, {} online",
+            went_offline.len(),
+            came_online.len()
+        );
+    }
+
+    Ok(corrected)
+}
+
+/// Koreksi status online yang dibutuhkan agar database sama dengan registry koneksi.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PresenceChanges {
+    /// Tercatat online di database tetapi tidak punya koneksi.
+    pub went_offline: Vec<Uuid>,
+    /// Terhubung tetapi tercatat offline di database.
+    pub came_online: Vec<Uuid>,
+}
+
+/// Bandingkan pengguna yang tercatat online (`db_online`) dengan yang benar-benar terhubung
+/// (`live`).
+pub fn presence_changes(db_online: &[Uuid], live: &[Uuid]) -> PresenceChanges {
+    PresenceChanges {
+        went_offline: db_online
+            .iter()
+            .filter(|id| !live.contains(id))
+            .copied()
+            .collect(),
+        came_online: live
+            .iter()
+            .filter(|id| !db_online.contains(id))
+            .copied()
+            .collect(),
+    }
+}
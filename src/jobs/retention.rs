@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use tokio::{task::JoinHandle, time::Duration};
+use tracing::{debug, error, info};
+
+use crate::{
+    config::retention::{
+        RetentionAction, get_message_retention_days, get_retention_batch_size,
+        get_retention_purge_interval,
+    },
+    handlers::websocket::is_shutting_down,
+    middleware::auth::AppState,
+    models::retention::purge_expired_messages,
+};
+
+/// Hapus atau arsipkan pesan yang sudah melewati masa retensinya secara berkala, per batch
+/// supaya tabel pesan tidak terkunci lama. Pesan yang ditahan legal hold dilewati.
+/// `action` sudah divalidasi saat startup.
+pub fn spawn(state: Arc<AppState>, action: RetentionAction) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(get_retention_purge_interval()));
+        let default_days = get_message_retention_days();
+        let batch_size = get_retention_batch_size();
+
+        loop {
+            ticker.tick().await;
+
+            if is_shutting_down() {
+                break;
+            }
+
+            // Batas waktu dibekukan per putaran supaya batch berikutnya tidak terus mengejar
+            // pesan yang baru kedaluwarsa.
+            let now = Utc::now();
+            let mut total = 0;
+            while !is_shutting_down() {
+                match purge_expired_messages(default_days, action, batch_size, now, &state.db).await
+                {
+                    Ok(purged) => {
+                        total += purged;
+                        if purged < batch_size {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        error!("Error purging expired messages: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            if total > 0 {
+                info!(
+                    "Retention: {} {} expired message(s)",
+                    match action {
+                        RetentionAction::Delete => "deleted",
+                        RetentionAction::Archive => "archived",
+                    },
+                    total
+                );
+            } else {
+                debug!("Retention: no expired messages");
+            }
+        }
+    })
+}
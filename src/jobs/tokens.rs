@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use tokio::{task::JoinHandle, time::Duration};
+use tracing::{debug, error};
+
+use crate::{
+    config::login::LoginPolicy,
+    handlers::websocket::is_shutting_down,
+    middleware::auth::AppState,
+    models::{
+        email_verification::EmailVerificationToken, login_throttle::LoginThrottle,
+        mfa::MfaChallenge, oidc::OidcAuthRequest, password_reset::PasswordResetToken,
+        refresh_token::RefreshToken, revoked_token::RevokedToken,
+    },
+};
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Hapus token sekali pakai (refresh, reset password, verifikasi email, tantangan MFA,
+/// login SSO), catatan pencabutan, dan penghitung login gagal yang sudah kedaluwarsa
+/// secara berkala.
+pub fn spawn(state: Arc<AppState>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(CLEANUP_INTERVAL);
+
+        loop {
+            ticker.tick().await;
+
+            if is_shutting_down() {
+                break;
+            }
+
+            match RevokedToken::purge_expired(&state.db).await {
+                Ok(purged) => debug!("Purged {} expired revoked token(s)", purged),
+                Err(e) => error!("Error purging revoked tokens: {}", e),
+            }
+
+            match RefreshToken::purge_expired(&state.db).await {
+                Ok(purged) => debug!("Purged {} expired refresh token(s)", purged),
+                Err(e) => error!("Error purging refresh tokens: {}", e),
+            }
+
+            match PasswordResetToken::purge_expired(&state.db).await {
+                Ok(purged) => debug!("Purged {} expired password reset token(s)", purged),
+                Err(e) => error!("Error purging password reset tokens: {}", e),
+            }
+
+            match EmailVerificationToken::purge_expired(&state.db).await {
+                Ok(purged) => debug!("Purged {} expired email verification token(s)", purged),
+                Err(e) => error!("Error purging email verification tokens: {}", e),
+            }
+
+            match MfaChallenge::purge_expired(&state.db).await {
+                Ok(purged) => debug!("Purged {} expired MFA challenge(s)", purged),
+                Err(e) => error!("Error purging MFA challenges: {}", e),
+            }
+
+            match OidcAuthRequest::purge_expired(&state.db).await {
+                Ok(purged) => debug!("Purged {} expired OIDC login request(s)", purged),
+                Err(e) => error!("Error purging OIDC login requests: {}", e),
+            }
+
+            let lockout_duration = LoginPolicy::from_env().lockout_duration;
+            match LoginThrottle::purge_expired(lockout_duration, &state.db).await {
+                Ok(purged) => debug!("Purged {} expired login throttle(s)", purged),
+                Err(e) => error!("Error purging login throttles: {}", e),
+            }
+        }
+    })
+}
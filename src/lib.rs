@@ -2,8 +2,11 @@
 pub mod app;
 pub mod config;
 pub mod handlers;
+pub mod jobs;
+pub mod mail;
 pub mod middleware;
 pub mod models;
+pub mod oidc;
 pub mod routes;
 pub mod utils;
 
@@ -0,0 +1,62 @@
+pub mod smtp;
+
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use tracing::{error, info};
+
+use crate::config::mail::{MailerKind, SmtpConfig, get_mail_from, get_mailer_kind};
+pub use smtp::SmtpMailer;
+
+/// Email teks sederhana.
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Pengirim email. Implementasi dipilih lewat `MAILER`.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<()>;
+}
+
+/// Mailer untuk development: isi email hanya ditulis ke log.
+#[derive(Debug, Default)]
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        info!(
+            "📧 Email ke {} | {}\n{}",
+            email.to, email.subject, email.body
+        );
+        Ok(())
+    }
+}
+
+/// Buat mailer sesuai konfigurasi environment.
+pub fn create_mailer() -> Result<Arc<dyn Mailer>> {
+    match get_mailer_kind().map_err(|e| anyhow!(e))? {
+        MailerKind::Log => Ok(Arc::new(LogMailer)),
+        MailerKind::Smtp => {
+            let config = SmtpConfig::from_env().map_err(|e| anyhow!(e))?;
+            Ok(Arc::new(SmtpMailer::new(&config, &get_mail_from())?))
+        }
+    }
+}
+
+/// Kirim email tanpa menunggu hasilnya; kegagalan hanya dicatat di log.
+///
+/// Dipakai di handler agar lama respons tidak bergantung pada server SMTP.
+pub fn send_in_background(mailer: Arc<dyn Mailer>, email: Email) {
+    tokio::spawn(async move {
+        let subject = email.subject.clone();
+        if let Err(e) = mailer.send(email).await {
+            error!("Error sending email \"{}\": {}", subject, e);
+        }
+    });
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+
+use crate::config::mail::{SmtpConfig, SmtpSecurity};
+use crate::mail::{Email, Mailer};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Kirim email lewat server SMTP.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &SmtpConfig, from: &str) -> Result<Self> {
+        let builder = match config.security {
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            }
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+        };
+
+        let mut builder = builder.port(config.port).timeout(Some(SMTP_TIMEOUT));
+        if !config.username.is_empty() {
+            builder = builder.credentials(Credentials::new(
+                config.username.clone(),
+                config.password.clone(),
+            ));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: from.parse()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(email.to.parse()?)
+            .subject(email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body)?;
+
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
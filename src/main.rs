@@ -1,20 +1,39 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use backend::{
-    config::{get_host, get_port},
+    config::{
+        get_bootstrap_admin, get_host, get_port, get_shutdown_timeout, jwt::init_keys,
+        retention::get_message_retention_action,
+    },
     create_db_pool,
+    handlers::websocket::drain_connections,
+    jobs,
+    mail::create_mailer,
     middleware::auth::AppState,
+    models::user::User,
     routes::create_routes,
-    utils::setup_tracing,
+    utils::{setup_tracing, shutdown_signal},
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::net::TcpListener;
-use tracing::{error, info};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::Notify, time::Instant};
+use tracing::{error, info, warn};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     setup_tracing();
     info!("🚀 Memulai server aplikasi chat...");
+    if let Err(e) = init_keys() {
+        error!("❌ {}", e);
+        return Err(e);
+    }
+    let retention_action = match get_message_retention_action() {
+        Ok(action) => action,
+        Err(e) => {
+            error!("❌ {}", e);
+            return Err(anyhow!(e));
+        }
+    };
     info!("📊 Menghubungkan ke database...");
     let db_pool = match create_db_pool().await {
         Ok(pool) => {
@@ -27,14 +46,81 @@ async fn main() -> Result<()> {
         }
     };
 
-    let state = Arc::new(AppState { db: db_pool });
-    let app = create_routes(state);
+    if let Some(username) = get_bootstrap_admin() {
+        match User::promote_bootstrap_admin(&username, &db_pool).await {
+            Ok(true) => info!("👑 {} dijadikan admin pertama", username),
+            Ok(false) => {}
+            Err(e) => warn!("⚠️ Gagal menyiapkan admin pertama: {}", e),
+        }
+    }
+
+    let mailer = create_mailer()?;
+    let state = Arc::new(AppState {
+        db: db_pool,
+        mailer,
+    });
+    jobs::presence::spawn(state.clone());
+    jobs::tokens::spawn(state.clone());
+    jobs::accounts::spawn(state.clone());
+    jobs::retention::spawn(state.clone(), retention_action);
+
+    let app = create_routes(state.clone());
     let host = get_host();
     let port = get_port();
     let addr: SocketAddr = format!("{}:{}", host, port).parse()?;
     info!("🌐 Server berjalan di: http://{}", addr);
     let listener = TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+
+    let stop_accepting = Arc::new(Notify::new());
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown({
+        let stop_accepting = stop_accepting.clone();
+        async move { stop_accepting.notified().await }
+    });
+    let mut server = tokio::spawn(server.into_future());
+
+    tokio::select! {
+        result = &mut server => {
+            result??;
+            return Ok(());
+        }
+        _ = shutdown_signal() => {}
+    }
+
+    let timeout = Duration::from_secs(get_shutdown_timeout());
+    let deadline = Instant::now() + timeout;
+    info!(
+        "🛑 Sinyal shutdown diterima, menutup koneksi (maks {:?})...",
+        timeout
+    );
+    stop_accepting.notify_one();
+
+    // Setiap langkah hanya mendapat sisa waktu sampai `deadline`
+    let drained = drain_connections(
+        &state.db,
+        deadline.saturating_duration_since(Instant::now()),
+    )
+    .await;
+    info!("👋 {} pengguna ditandai offline", drained);
+
+    match tokio::time::timeout_at(deadline, &mut server).await {
+        Ok(result) => result??,
+        Err(_) => {
+            warn!("⚠️ Batas waktu shutdown habis, request yang tersisa dihentikan");
+            server.abort();
+        }
+    }
+
+    if tokio::time::timeout_at(deadline, state.db.close())
+        .await
+        .is_err()
+    {
+        warn!("⚠️ Batas waktu shutdown habis sebelum pool database tertutup");
+    }
+    info!("✅ Server berhenti");
 
     Ok(())
 }
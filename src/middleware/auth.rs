@@ -1,11 +1,18 @@
 use crate::{
-    config::jwt::validate_token,
-    models::{errors::AppError, user::User},
+    config::jwt::{Claims, validate_token},
+    mail::Mailer,
+    models::{
+        api_token::{API_TOKEN_PREFIX, PersonalAccessToken, TokenScope},
+        errors::AppError,
+        revoked_token::RevokedToken,
+        role::Role,
+        user::User,
+    },
 };
 use axum::{
     body::Body,
-    extract::{FromRequestParts, State},
-    http::{Request, request::Parts},
+    extract::{FromRequestParts, MatchedPath, State},
+    http::{Method, Request, request::Parts},
     middleware::Next,
     response::Response,
 };
@@ -14,6 +21,7 @@ use axum_extra::{
     headers::{Authorization, authorization::Bearer},
 };
 use sqlx::postgres::PgPool;
+use std::marker::PhantomData;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -23,21 +31,176 @@ pub async fn auth_middleware(
     mut request: Request<Body>,
     next: Next,
 ) -> Result<Response, AppError> {
-    let token = auth.token();
+    let (user, credential) = authenticate(auth.token(), &state.db).await?;
 
+    match &credential {
+        Credential::Session(claims) => {
+            request.extensions_mut().insert(claims.clone());
+        }
+        Credential::ApiToken(api_token) => {
+            let route = request
+                .extensions()
+                .get::<MatchedPath>()
+                .map(MatchedPath::as_str)
+                .unwrap_or_default();
+            authorize_api_token(api_token, request.method(), route)?;
+        }
+    }
+
+    request.extensions_mut().insert(user);
+    request.extensions_mut().insert(credential);
+
+    Ok(next.run(request).await)
+}
+
+/// Kredensial yang dipakai sebuah request atau koneksi WebSocket.
+#[derive(Debug, Clone)]
+pub enum Credential {
+    /// Access token (JWT) dari sebuah sesi login.
+    Session(Claims),
+    /// Personal access token untuk bot dan skrip.
+    ApiToken(PersonalAccessToken),
+}
+
+impl Credential {
+    /// ID kredensial untuk registry koneksi: `jti` JWT, atau ID personal access token.
+    pub fn id(&self) -> String {
+        match self {
+            Credential::Session(claims) => claims.jti.clone(),
+            Credential::ApiToken(api_token) => PersonalAccessToken::credential_id(api_token.id),
+        }
+    }
+
+    pub fn session_id(&self) -> Option<Uuid> {
+        match self {
+            Credential::Session(claims) => claims.sid,
+            Credential::ApiToken(_) => None,
+        }
+    }
+
+    /// JWT sesi memiliki semua scope; personal access token hanya yang diberikan saat dibuat.
+    pub fn allows(&self, scope: TokenScope) -> bool {
+        match self {
+            Credential::Session(_) => true,
+            Credential::ApiToken(api_token) => api_token.has_scope(scope),
+        }
+    }
+}
+
+/// Akses yang dibutuhkan sebuah route jika dipanggil dengan personal access token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenAccess {
+    /// Boleh dengan token apa pun.
+    Any,
+    Scope(TokenScope),
+    /// Hanya dengan JWT sesi, mis. pengelolaan akun, token, dan endpoint admin.
+    SessionOnly,
+}
+
+/// Petakan method dan pola route (`MatchedPath`) ke akses yang dibutuhkan personal access token.
+pub fn token_access(method: &Method, route: &str) -> TokenAccess {
+    match (method.as_str(), route) {
+        ("GET", "/users") | ("GET", "/users/me") | ("GET", "/users/{user_id}") => TokenAccess::Any,
+        ("GET", "/users/online") | ("POST", "/users/status") => {
+            TokenAccess::Scope(TokenScope::Presence)
+        }
+        ("POST", "/messages") => TokenAccess::Scope(TokenScope::MessagesWrite),
+        ("GET", "/messages/public")
+        | ("GET", "/messages/{receiver_id}")
+        | ("GET", "/messages/search")
+        | ("GET", "/conversations")
+        | ("POST", "/conversations/public/read")
+        | ("POST", "/conversations/{partner_id}/read") => {
+            TokenAccess::Scope(TokenScope::MessagesRead)
+        }
+        _ => TokenAccess::SessionOnly,
+    }
+}
+
+pub fn authorize_api_token(
+    api_token: &PersonalAccessToken,
+    method: &Method,
+    route: &str,
+) -> Result<(), AppError> {
+    match token_access(method, route) {
+        TokenAccess::Any => Ok(()),
+        TokenAccess::Scope(scope) if api_token.has_scope(scope) => Ok(()),
+        TokenAccess::Scope(scope) => Err(AppError::Forbidden(format!(
+            "Token tidak memiliki scope {scope}"
+        ))),
+        TokenAccess::SessionOnly => Err(AppError::Forbidden(
+            "Endpoint ini tidak bisa diakses dengan personal access token".to_string(),
+        )),
+    }
+}
+
+/// Autentikasi bearer token: personal access token jika berawalan `pat_`, selain itu JWT sesi.
+pub async fn authenticate(token: &str, db: &PgPool) -> Result<(User, Credential), AppError> {
+    if token.starts_with(API_TOKEN_PREFIX) {
+        let (user, api_token) = authenticate_api_token(token, db).await?;
+        Ok((user, Credential::ApiToken(api_token)))
+    } else {
+        let (user, claims) = authenticate_token(token, db).await?;
+        Ok((user, Credential::Session(claims)))
+    }
+}
+
+/// Validasi personal access token: hash, status pencabutan, masa berlaku, akun yang
+/// diwakili, dan pembuatnya. Token bot ikut ditolak selama pemilik bot diblokir.
+pub async fn authenticate_api_token(
+    token: &str,
+    db: &PgPool,
+) -> Result<(User, PersonalAccessToken), AppError> {
+    let api_token = PersonalAccessToken::find_by_token(token, db)
+        .await?
+        .filter(PersonalAccessToken::is_active)
+        .ok_or_else(|| AppError::Auth("Token tidak valid".to_string()))?;
+
+    let user = User::find_by_id(api_token.user_id, db)
+        .await?
+        .ok_or_else(|| AppError::Auth("Pengguna tidak ditemukan".to_string()))?;
+    ensure_not_banned(&user)?;
+
+    if api_token.owner_id != user.id {
+        let owner = User::find_by_id(api_token.owner_id, db)
+            .await?
+            .ok_or_else(|| AppError::Auth("Pengguna tidak ditemukan".to_string()))?;
+        ensure_not_banned(&owner)?;
+    }
+
+    api_token.touch(db).await?;
+
+    Ok((user, api_token))
+}
+
+/// Validasi access token: tanda tangan, masa berlaku, status pencabutan, dan pemiliknya.
+pub async fn authenticate_token(token: &str, db: &PgPool) -> Result<(User, Claims), AppError> {
     let claims =
         validate_token(token).map_err(|_| AppError::Auth("Token tidak valid".to_string()))?;
 
-    let user_id = Uuid::parse_str(&claims.sub)
+    let user_id = claims
+        .user_id()
         .map_err(|_| AppError::Auth("Token tidak valid".to_string()))?;
 
-    let user = User::find_by_id(user_id, &state.db)
+    if RevokedToken::is_revoked(&claims.jti, user_id, claims.issued_at(), claims.sid, db).await? {
+        return Err(AppError::Auth("Token sudah dicabut".to_string()));
+    }
+
+    let user = User::find_by_id(user_id, db)
         .await?
         .ok_or_else(|| AppError::Auth("Pengguna tidak ditemukan".to_string()))?;
+    ensure_not_banned(&user)?;
 
-    request.extensions_mut().insert(user);
+    Ok((user, claims))
+}
 
-    Ok(next.run(request).await)
+/// Tolak pengguna yang diblokir admin.
+pub fn ensure_not_banned(user: &User) -> Result<(), AppError> {
+    if user.banned_at.is_some() {
+        return Err(AppError::Forbidden("Akun diblokir".to_string()));
+    }
+
+    Ok(())
 }
 
 pub struct AuthUser(pub User);
@@ -59,6 +222,49 @@ where
     }
 }
 
+/// Penanda peran minimum untuk [`RequireRole`].
+pub trait RequiredRole {
+    const ROLE: Role;
+}
+
+pub struct Moderator;
+
+impl RequiredRole for Moderator {
+    const ROLE: Role = Role::Moderator;
+}
+
+pub struct Admin;
+
+impl RequiredRole for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+/// Seperti [`AuthUser`], tetapi menolak pengguna yang perannya di bawah `R`,
+/// mis. `RequireRole<Admin>`. Peran dibaca dari database, bukan dari klaim token.
+pub struct RequireRole<R: RequiredRole>(pub User, PhantomData<R>);
+
+impl<S, R> FromRequestParts<S> for RequireRole<R>
+where
+    S: Send + Sync,
+    R: RequiredRole,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AuthUser(user) = AuthUser::from_request_parts(parts, state).await?;
+
+        if !user.role.satisfies(R::ROLE) {
+            return Err(AppError::Forbidden(format!(
+                "Hanya {} yang boleh mengakses endpoint ini",
+                R::ROLE
+            )));
+        }
+
+        Ok(RequireRole(user, PhantomData))
+    }
+}
+
 pub struct AppState {
     pub db: PgPool,
+    pub mailer: Arc<dyn Mailer>,
 }
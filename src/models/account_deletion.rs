@@ -0,0 +1,270 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+#[cfg(not(any(debug_assertions, ci)))]
+use crate::models::user::User;
+
+/// Pengguna pengganti bersama (`[deleted]`) dari migrasi, yang dipakai sebelum setiap akun
+/// yang dihapus mendapat pengganti sendiri (lihat `User::new_tombstone`). Pesan lama
+/// masih bisa menunjuk ke pengguna ini.
+pub const DELETED_USER_ID: Uuid = Uuid::nil();
+
+/// Nasib pesan yang dikirim pengguna saat akunnya dihapus.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum MessageDeletionMode {
+    /// Pesan tetap ada, pengirimnya diganti pengguna pengganti `[deleted]` milik akun ini.
+    #[default]
+    Anonymize,
+    /// Pesan yang dikirim pengguna ikut dihapus.
+    Delete,
+}
+
+impl MessageDeletionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageDeletionMode::Anonymize => "anonymize",
+            MessageDeletionMode::Delete => "delete",
+        }
+    }
+}
+
+/// Permintaan hapus akun yang menunggu masa tenggang.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct AccountDeletion {
+    pub user_id: Uuid,
+    pub message_mode: MessageDeletionMode,
+    pub requested_at: DateTime<Utc>,
+    /// Waktu paling awal akun dihapus oleh background job.
+    pub scheduled_for: DateTime<Utc>,
+}
+
+/// Konfirmasi identitas sebelum akun dijadwalkan dihapus: password, kode 2FA (TOTP atau
+/// kode pemulihan), atau tanpa keduanya jika sesi baru saja login.
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteAccountRequest {
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub messages: MessageDeletionMode,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountDeletionResponse {
+    pub messages: MessageDeletionMode,
+    pub requested_at: DateTime<Utc>,
+    pub scheduled_for: DateTime<Utc>,
+}
+
+impl AccountDeletion {
+    pub fn new(user_id: Uuid, message_mode: MessageDeletionMode, grace_period: i64) -> Self {
+        let now = Utc::now();
+
+        Self {
+            user_id,
+            message_mode,
+            requested_at: now,
+            scheduled_for: now + Duration::seconds(grace_period),
+        }
+    }
+
+    /// Simpan permintaan. Jika pengguna sudah pernah meminta, hanya `message_mode` yang
+    /// diperbarui dan jadwal semula dipertahankan.
+    pub async fn schedule(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let deletion = sqlx::query_as!(
+            AccountDeletion,
+            r#"
+            INSERT INTO account_deletions (user_id, message_mode, requested_at, scheduled_for)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id) DO UPDATE
+            SET message_mode = EXCLUDED.message_mode
+            RETURNING user_id, message_mode as "message_mode: MessageDeletionMode", requested_at, scheduled_for
+            "#,
+            self.user_id,
+            self.message_mode.as_str(),
+            self.requested_at,
+            self.scheduled_for
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let deletion = self;
+
+        Ok(deletion)
+    }
+
+    /// Batalkan permintaan. Mengembalikan `false` jika tidak ada permintaan.
+    pub async fn cancel(_user_id: Uuid, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            DELETE FROM account_deletions
+            WHERE user_id = $1
+            "#,
+            _user_id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 0;
+
+        Ok(rows == 1)
+    }
+
+    /// Permintaan yang masa tenggangnya sudah lewat.
+    pub async fn due(_now: DateTime<Utc>, _pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let deletions = sqlx::query_as!(
+            AccountDeletion,
+            r#"
+            SELECT user_id, message_mode as "message_mode: MessageDeletionMode", requested_at, scheduled_for
+            FROM account_deletions
+            WHERE scheduled_for <= $1
+            ORDER BY scheduled_for
+            "#,
+            _now
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let deletions = Vec::new();
+
+        Ok(deletions)
+    }
+
+    /// Hapus akun beserta bot-botnya dalam satu transaksi. Pesan yang dikirim dihapus atau
+    /// dianonimkan sesuai `message_mode`; pesan pribadi yang diterima tetap disimpan untuk
+    /// pengirimnya dengan penerima `[deleted]`. Data lain (sesi, token, identitas SSO, 2FA)
+    /// ikut terhapus lewat `ON DELETE CASCADE`.
+    ///
+    /// Mengembalikan ID semua akun yang dihapus.
+    pub async fn execute(&self, _pool: &PgPool) -> Result<Vec<Uuid>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let user_ids = {
+            let mut tx = _pool.begin().await?;
+
+            let user_ids: Vec<Uuid> = sqlx::query_scalar!(
+                r#"
+                SELECT id
+                FROM users
+                WHERE id = $1 OR bot_owner_id = $1
+                FOR UPDATE
+                "#,
+                self.user_id
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+
+            if self.message_mode == MessageDeletionMode::Delete {
+                sqlx::query!(
+                    r#"
+                    DELETE FROM messages
+                    WHERE sender_id = ANY($1)
+                    "#,
+                    &user_ids
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            // Setiap akun mendapat pengganti sendiri, supaya pesan pribadi dari akun-akun
+            // yang dihapus tidak tergabung ke satu percakapan atau satu kunci retensi.
+            for user_id in &user_ids {
+                let has_messages = sqlx::query_scalar!(
+                    r#"
+                    SELECT EXISTS (
+                        SELECT 1 FROM messages WHERE sender_id = $1 OR receiver_id = $1
+                    ) as "exists!"
+                    "#,
+                    user_id
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                if !has_messages {
+                    continue;
+                }
+
+                let tombstone = User::new_tombstone()?;
+                sqlx::query!(
+                    r#"
+                    INSERT INTO users (id, username, password_hash, display_name, banned_at, ban_reason, privacy, last_seen, created_at, updated_at)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                    "#,
+                    tombstone.id,
+                    tombstone.username,
+                    tombstone.password_hash,
+                    tombstone.display_name,
+                    tombstone.banned_at,
+                    tombstone.ban_reason,
+                    tombstone.privacy.as_str(),
+                    tombstone.last_seen,
+                    tombstone.created_at,
+                    tombstone.updated_at
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                sqlx::query!(
+                    r#"
+                    UPDATE messages
+                    SET sender_id = $2
+                    WHERE sender_id = $1
+                    "#,
+                    user_id,
+                    tombstone.id
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                sqlx::query!(
+                    r#"
+                    UPDATE messages
+                    SET receiver_id = $2
+                    WHERE receiver_id = $1
+                    "#,
+                    user_id,
+                    tombstone.id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            sqlx::query!(
+                r#"
+                DELETE FROM users
+                WHERE id = ANY($1)
+                "#,
+                &user_ids
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            user_ids
+        };
+
+        #[cfg(any(debug_assertions, ci))]
+        let user_ids = vec![self.user_id];
+
+        Ok(user_ids)
+    }
+
+    pub fn into_response(self) -> AccountDeletionResponse {
+        AccountDeletionResponse {
+            messages: self.message_mode,
+            requested_at: self.requested_at,
+            scheduled_for: self.scheduled_for,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::role::Role;
+use crate::models::user::User;
+
+pub const DEFAULT_ADMIN_PAGE_SIZE: i64 = 50;
+pub const MAX_ADMIN_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ListUsersQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl ListUsersQuery {
+    /// `(limit, offset)` yang sudah dibatasi ke rentang yang wajar.
+    pub fn page(&self) -> (i64, i64) {
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_ADMIN_PAGE_SIZE)
+            .clamp(1, MAX_ADMIN_PAGE_SIZE);
+        let offset = self.offset.unwrap_or(0).max(0);
+        (limit, offset)
+    }
+}
+
+/// Data pengguna untuk panel admin, termasuk status blokir.
+#[derive(Debug, Serialize)]
+pub struct AdminUserResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub role: Role,
+    pub banned_at: Option<DateTime<Utc>>,
+    pub ban_reason: Option<String>,
+    pub is_bot: bool,
+    pub bot_owner_id: Option<Uuid>,
+    pub is_online: bool,
+    pub last_seen: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<User> for AdminUserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            email_verified: user.is_email_verified(),
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            role: user.role,
+            banned_at: user.banned_at,
+            ban_reason: user.ban_reason,
+            is_bot: user.is_bot,
+            bot_owner_id: user.bot_owner_id,
+            is_online: user.is_online,
+            last_seen: user.last_seen,
+            created_at: user.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub role: Role,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct BanUserRequest {
+    pub reason: Option<String>,
+}
@@ -0,0 +1,334 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::models::errors::ValidationErrors;
+use crate::utils::token::{generate_opaque_token, hash_token};
+
+/// Prefix personal access token. Dipakai middleware untuk membedakannya dari JWT sesi.
+pub const API_TOKEN_PREFIX: &str = "pat_";
+pub const MAX_TOKEN_NAME_LENGTH: usize = 100;
+pub const MAX_TOKEN_EXPIRY_DAYS: i64 = 365;
+
+/// `last_used_at` hanya ditulis ulang jika sudah lebih lama dari ini, supaya bot yang
+/// sering memanggil API tidak menulis ke database di setiap request.
+const LAST_USED_RESOLUTION: Duration = Duration::minutes(1);
+
+/// Hak akses sebuah personal access token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TokenScope {
+    #[serde(rename = "messages:read")]
+    MessagesRead,
+    #[serde(rename = "messages:write")]
+    MessagesWrite,
+    #[serde(rename = "presence")]
+    Presence,
+}
+
+impl TokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenScope::MessagesRead => "messages:read",
+            TokenScope::MessagesWrite => "messages:write",
+            TokenScope::Presence => "presence",
+        }
+    }
+}
+
+impl fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TokenScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "messages:read" => Ok(TokenScope::MessagesRead),
+            "messages:write" => Ok(TokenScope::MessagesWrite),
+            "presence" => Ok(TokenScope::Presence),
+            other => Err(format!("Scope tidak dikenal: {other}")),
+        }
+    }
+}
+
+/// Token akses jangka panjang untuk bot dan skrip.
+///
+/// Hanya hash token yang disimpan; `scopes` disimpan sebagai daftar dipisah spasi.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct PersonalAccessToken {
+    pub id: Uuid,
+    /// Akun yang diwakili token: pemiliknya sendiri atau salah satu botnya.
+    pub user_id: Uuid,
+    /// Pengguna yang membuat dan boleh mencabut token ini.
+    pub owner_id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub scopes: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    /// Masa berlaku dalam hari. Kosong berarti token tidak kedaluwarsa.
+    pub expires_in_days: Option<i64>,
+    /// Terbitkan token untuk bot milik pengguna, bukan untuk pengguna itu sendiri.
+    pub bot_id: Option<Uuid>,
+}
+
+impl CreateApiTokenRequest {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::default();
+
+        let name = self.name.trim();
+        if name.is_empty() {
+            errors.add("name", "required", "Nama token wajib diisi");
+        } else if name.chars().count() > MAX_TOKEN_NAME_LENGTH {
+            errors.add(
+                "name",
+                "too_long",
+                format!("Nama token maksimal {MAX_TOKEN_NAME_LENGTH} karakter"),
+            );
+        }
+        if self.scopes.is_empty() {
+            errors.add("scopes", "required", "Pilih setidaknya satu scope");
+        }
+        if let Some(days) = self.expires_in_days
+            && !(1..=MAX_TOKEN_EXPIRY_DAYS).contains(&days)
+        {
+            errors.add(
+                "expires_in_days",
+                "out_of_range",
+                format!("Masa berlaku harus antara 1 dan {MAX_TOKEN_EXPIRY_DAYS} hari"),
+            );
+        }
+
+        errors
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiTokenResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Balasan pembuatan token. `token` hanya ditampilkan sekali ini.
+#[derive(Debug, Serialize)]
+pub struct CreatedApiTokenResponse {
+    pub token: String,
+    #[serde(flatten)]
+    pub details: ApiTokenResponse,
+}
+
+impl PersonalAccessToken {
+    /// Buat token baru. Token mentah dikembalikan terpisah karena hanya hash-nya yang disimpan.
+    pub fn new(
+        user_id: Uuid,
+        owner_id: Uuid,
+        name: &str,
+        scopes: &[TokenScope],
+        expires_in_days: Option<i64>,
+    ) -> (String, Self) {
+        let token = format!("{API_TOKEN_PREFIX}{}", generate_opaque_token());
+        let now = Utc::now();
+
+        let mut scope_names: Vec<&str> = scopes.iter().map(TokenScope::as_str).collect();
+        scope_names.sort_unstable();
+        scope_names.dedup();
+
+        let api_token = Self {
+            id: Uuid::new_v4(),
+            user_id,
+            owner_id,
+            name: name.trim().to_string(),
+            token_hash: hash_token(&token),
+            scopes: scope_names.join(" "),
+            expires_at: expires_in_days.map(|days| now + Duration::days(days)),
+            last_used_at: None,
+            created_at: now,
+            revoked_at: None,
+        };
+
+        (token, api_token)
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let api_token = sqlx::query_as!(
+            PersonalAccessToken,
+            r#"
+            INSERT INTO personal_access_tokens (id, user_id, owner_id, name, token_hash, scopes, expires_at, last_used_at, created_at, revoked_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, user_id, owner_id, name, token_hash, scopes, expires_at, last_used_at, created_at, revoked_at
+            "#,
+            self.id,
+            self.user_id,
+            self.owner_id,
+            self.name,
+            self.token_hash,
+            self.scopes,
+            self.expires_at,
+            self.last_used_at,
+            self.created_at,
+            self.revoked_at
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let api_token = self;
+
+        Ok(api_token)
+    }
+
+    pub async fn find_by_token(token: &str, _pool: &PgPool) -> Result<Option<Self>> {
+        let _token_hash = hash_token(token);
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let api_token = sqlx::query_as!(
+            PersonalAccessToken,
+            r#"
+            SELECT id, user_id, owner_id, name, token_hash, scopes, expires_at, last_used_at, created_at, revoked_at
+            FROM personal_access_tokens
+            WHERE token_hash = $1
+            "#,
+            _token_hash
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let api_token = None;
+
+        Ok(api_token)
+    }
+
+    /// Token aktif yang dikelola `_owner_id`, termasuk token bot-botnya.
+    pub async fn list_for_owner(_owner_id: Uuid, _pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let api_tokens = sqlx::query_as!(
+            PersonalAccessToken,
+            r#"
+            SELECT id, user_id, owner_id, name, token_hash, scopes, expires_at, last_used_at, created_at, revoked_at
+            FROM personal_access_tokens
+            WHERE owner_id = $1 AND revoked_at IS NULL
+            ORDER BY created_at DESC
+            "#,
+            _owner_id
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let api_tokens = Vec::new();
+
+        Ok(api_tokens)
+    }
+
+    /// Perbarui `last_used_at` jika nilai terakhir sudah cukup lama.
+    pub async fn touch(&self, _pool: &PgPool) -> Result<()> {
+        if !self.needs_touch(Utc::now()) {
+            return Ok(());
+        }
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE personal_access_tokens
+            SET last_used_at = $1
+            WHERE id = $2
+            "#,
+            Utc::now(),
+            self.id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Cabut token milik `_owner_id`. Mengembalikan `false` jika token tidak ditemukan
+    /// atau sudah dicabut.
+    pub async fn revoke(_id: Uuid, _owner_id: Uuid, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE personal_access_tokens
+            SET revoked_at = $1
+            WHERE id = $2 AND owner_id = $3 AND revoked_at IS NULL
+            "#,
+            Utc::now(),
+            _id,
+            _owner_id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 0;
+
+        Ok(rows == 1)
+    }
+
+    pub fn scopes(&self) -> Vec<TokenScope> {
+        self.scopes
+            .split_whitespace()
+            .filter_map(|scope| scope.parse().ok())
+            .collect()
+    }
+
+    pub fn has_scope(&self, scope: TokenScope) -> bool {
+        self.scopes.split_whitespace().any(|s| s == scope.as_str())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && !self.is_expired()
+    }
+
+    pub fn needs_touch(&self, now: DateTime<Utc>) -> bool {
+        self.last_used_at
+            .is_none_or(|last_used_at| now - last_used_at >= LAST_USED_RESOLUTION)
+    }
+
+    /// Kunci kredensial di registry koneksi WebSocket, setara `jti` untuk JWT sesi.
+    pub fn credential_id(id: Uuid) -> String {
+        format!("{API_TOKEN_PREFIX}{id}")
+    }
+
+    pub fn into_response(self) -> ApiTokenResponse {
+        ApiTokenResponse {
+            scopes: self.scopes(),
+            id: self.id,
+            user_id: self.user_id,
+            name: self.name,
+            expires_at: self.expires_at,
+            last_used_at: self.last_used_at,
+            created_at: self.created_at,
+        }
+    }
+}
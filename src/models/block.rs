@@ -0,0 +1,274 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::models::contact::Privacy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum BlockKind {
+    /// Pesan pribadi ditolak dan pesan publik disembunyikan.
+    Block,
+    /// Pesan tetap tampil, tetapi tanpa notifikasi.
+    Mute,
+}
+
+impl BlockKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlockKind::Block => "block",
+            BlockKind::Mute => "mute",
+        }
+    }
+}
+
+/// `user_id` memblokir atau membisukan `target_id`.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct UserBlock {
+    pub user_id: Uuid,
+    pub target_id: Uuid,
+    pub kind: BlockKind,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Keadaan terbaru antara pengirim dan penerima pesan pribadi. Selalu dibaca dari database,
+/// bukan dari cache pengguna, supaya perubahan privasi atau blokir di node lain langsung
+/// berlaku.
+#[derive(Debug, Clone, Copy, FromRow)]
+pub struct DirectMessageAccess {
+    /// Pengaturan privasi penerima.
+    pub privacy: Privacy,
+    pub is_contact: bool,
+    pub blocked_by_sender: bool,
+    pub blocked_by_receiver: bool,
+    pub muted_by_receiver: bool,
+}
+
+impl DirectMessageAccess {
+    /// Mengembalikan `None` jika penerima tidak ada.
+    pub async fn load(
+        _sender_id: Uuid,
+        _receiver_id: Uuid,
+        _pool: &PgPool,
+    ) -> Result<Option<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let access = sqlx::query_as!(
+            DirectMessageAccess,
+            r#"
+            SELECT u.privacy as "privacy: Privacy",
+                   EXISTS (
+                       SELECT 1 FROM contacts c
+                       WHERE c.status = 'accepted'
+                         AND ((c.requester_id = $1 AND c.addressee_id = $2)
+                           OR (c.requester_id = $2 AND c.addressee_id = $1))
+                   ) as "is_contact!",
+                   EXISTS (
+                       SELECT 1 FROM user_blocks b
+                       WHERE b.user_id = $1 AND b.target_id = $2 AND b.kind = 'block'
+                   ) as "blocked_by_sender!",
+                   EXISTS (
+                       SELECT 1 FROM user_blocks b
+                       WHERE b.user_id = $2 AND b.target_id = $1 AND b.kind = 'block'
+                   ) as "blocked_by_receiver!",
+                   EXISTS (
+                       SELECT 1 FROM user_blocks b
+                       WHERE b.user_id = $2 AND b.target_id = $1 AND b.kind = 'mute'
+                   ) as "muted_by_receiver!"
+            FROM users u
+            WHERE u.id = $2
+            "#,
+            _sender_id,
+            _receiver_id
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let access = Some(DirectMessageAccess {
+            privacy: Privacy::Everyone,
+            is_contact: false,
+            blocked_by_sender: false,
+            blocked_by_receiver: false,
+            muted_by_receiver: false,
+        });
+
+        Ok(access)
+    }
+
+    /// Penerima mengizinkan pesan dari pengirim.
+    pub fn allowed(&self) -> bool {
+        !self.blocked_by_receiver && self.privacy.allows(self.is_contact)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockedUserResponse {
+    pub user_id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UserBlock {
+    pub fn new(user_id: Uuid, target_id: Uuid, kind: BlockKind) -> Self {
+        Self {
+            user_id,
+            target_id,
+            kind,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Simpan blokir. Tidak berubah apa-apa jika sudah ada.
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            INSERT INTO user_blocks (user_id, target_id, kind, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, target_id, kind) DO NOTHING
+            "#,
+            self.user_id,
+            self.target_id,
+            self.kind.as_str(),
+            self.created_at
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(self)
+    }
+
+    /// Hapus blokir. Mengembalikan `false` jika tidak ada.
+    pub async fn remove(
+        _user_id: Uuid,
+        _target_id: Uuid,
+        _kind: BlockKind,
+        _pool: &PgPool,
+    ) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            DELETE FROM user_blocks
+            WHERE user_id = $1 AND target_id = $2 AND kind = $3
+            "#,
+            _user_id,
+            _target_id,
+            _kind.as_str()
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 0;
+
+        Ok(rows == 1)
+    }
+
+    /// Pengguna yang diblokir atau dibisukan `_user_id`, urut dari yang terbaru.
+    pub async fn list(
+        _user_id: Uuid,
+        _kind: BlockKind,
+        _pool: &PgPool,
+    ) -> Result<Vec<BlockedUserResponse>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let users = sqlx::query_as!(
+            BlockedUserResponse,
+            r#"
+            SELECT b.target_id as user_id, u.username, u.display_name, b.created_at
+            FROM user_blocks b
+            JOIN users u ON u.id = b.target_id
+            WHERE b.user_id = $1 AND b.kind = $2
+            ORDER BY b.created_at DESC
+            "#,
+            _user_id,
+            _kind.as_str()
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let users = Vec::new();
+
+        Ok(users)
+    }
+
+    /// Semua blokir dan bisukan antara dua pengguna, ke dua arah.
+    pub async fn between(_user1_id: Uuid, _user2_id: Uuid, _pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let blocks = sqlx::query_as!(
+            UserBlock,
+            r#"
+            SELECT user_id, target_id, kind as "kind: BlockKind", created_at
+            FROM user_blocks
+            WHERE (user_id = $1 AND target_id = $2) OR (user_id = $2 AND target_id = $1)
+            "#,
+            _user1_id,
+            _user2_id
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let blocks = Vec::new();
+
+        Ok(blocks)
+    }
+
+    /// Pengguna yang memblokir atau membisukan `_target_id`.
+    pub async fn targeting(_target_id: Uuid, _pool: &PgPool) -> Result<BlockList> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let blocks = sqlx::query_as!(
+            UserBlock,
+            r#"
+            SELECT user_id, target_id, kind as "kind: BlockKind", created_at
+            FROM user_blocks
+            WHERE target_id = $1
+            "#,
+            _target_id
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let blocks = Vec::new();
+
+        Ok(BlockList::from_blocks(&blocks))
+    }
+}
+
+/// Pengguna yang memblokir atau membisukan satu pengirim, untuk menyaring siaran pesannya.
+#[derive(Debug, Default)]
+pub struct BlockList {
+    blocked_by: HashSet<Uuid>,
+    muted_by: HashSet<Uuid>,
+}
+
+impl BlockList {
+    pub fn from_blocks(blocks: &[UserBlock]) -> Self {
+        let mut list = Self::default();
+        for block in blocks {
+            match block.kind {
+                BlockKind::Block => list.blocked_by.insert(block.user_id),
+                BlockKind::Mute => list.muted_by.insert(block.user_id),
+            };
+        }
+        list
+    }
+
+    /// `user_id` memblokir pengirim, jadi pesannya tidak dikirim ke `user_id`.
+    pub fn hides_from(&self, user_id: Uuid) -> bool {
+        self.blocked_by.contains(&user_id)
+    }
+
+    /// `user_id` membisukan pengirim, jadi pesannya dikirim tanpa notifikasi.
+    pub fn silences_for(&self, user_id: Uuid) -> bool {
+        self.muted_by.contains(&user_id)
+    }
+}
@@ -0,0 +1,280 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+/// Ekspor yang masih `processing` setelah selama ini dianggap macet (mis. server restart)
+/// dan dikembalikan ke antrean.
+pub const STALE_EXPORT_AFTER: Duration = Duration::hours(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum ExportStatus {
+    Pending,
+    Processing,
+    Ready,
+    Failed,
+}
+
+impl ExportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportStatus::Pending => "pending",
+            ExportStatus::Processing => "processing",
+            ExportStatus::Ready => "ready",
+            ExportStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Arsip ekspor data pribadi (profil, sesi, dan pesan) seorang pengguna.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: ExportStatus,
+    /// Lokasi arsip ZIP di disk setelah `ready`.
+    pub file_path: Option<String>,
+    pub error: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataExportResponse {
+    pub id: Uuid,
+    pub status: ExportStatus,
+    pub requested_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl DataExport {
+    pub fn new(user_id: Uuid) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            status: ExportStatus::Pending,
+            file_path: None,
+            error: None,
+            requested_at: Utc::now(),
+            started_at: None,
+            completed_at: None,
+            expires_at: None,
+        }
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let export = sqlx::query_as!(
+            DataExport,
+            r#"
+            INSERT INTO data_exports (id, user_id, status, file_path, error, requested_at, started_at, completed_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, user_id, status as "status: ExportStatus", file_path, error, requested_at, started_at, completed_at, expires_at
+            "#,
+            self.id,
+            self.user_id,
+            self.status.as_str(),
+            self.file_path,
+            self.error,
+            self.requested_at,
+            self.started_at,
+            self.completed_at,
+            self.expires_at
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let export = self;
+
+        Ok(export)
+    }
+
+    /// Ekspor terbaru milik pengguna.
+    pub async fn find_latest(_user_id: Uuid, _pool: &PgPool) -> Result<Option<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let export = sqlx::query_as!(
+            DataExport,
+            r#"
+            SELECT id, user_id, status as "status: ExportStatus", file_path, error, requested_at, started_at, completed_at, expires_at
+            FROM data_exports
+            WHERE user_id = $1
+            ORDER BY requested_at DESC
+            LIMIT 1
+            "#,
+            _user_id
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let export = None;
+
+        Ok(export)
+    }
+
+    /// Ambil satu ekspor dari antrean dan tandai `processing`. Aman dipanggil dari beberapa
+    /// node sekaligus.
+    pub async fn claim_next(_pool: &PgPool) -> Result<Option<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let export = sqlx::query_as!(
+            DataExport,
+            r#"
+            UPDATE data_exports
+            SET status = 'processing', started_at = $1
+            WHERE id = (
+                SELECT id FROM data_exports
+                WHERE status = 'pending'
+                ORDER BY requested_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, user_id, status as "status: ExportStatus", file_path, error, requested_at, started_at, completed_at, expires_at
+            "#,
+            Utc::now()
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let export = None;
+
+        Ok(export)
+    }
+
+    /// Kembalikan ekspor yang macet di `processing` sejak sebelum `_cutoff` ke antrean.
+    pub async fn requeue_stale(_cutoff: DateTime<Utc>, _pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let requeued = sqlx::query!(
+            r#"
+            UPDATE data_exports
+            SET status = 'pending', started_at = NULL
+            WHERE status = 'processing' AND started_at < $1
+            "#,
+            _cutoff
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let requeued = 0;
+
+        Ok(requeued)
+    }
+
+    pub async fn mark_ready(&self, _file_path: &str, _ttl: i64, _pool: &PgPool) -> Result<()> {
+        let _now = Utc::now();
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE data_exports
+            SET status = 'ready', file_path = $1, completed_at = $2, expires_at = $3
+            WHERE id = $4
+            "#,
+            _file_path,
+            _now,
+            _now + Duration::seconds(_ttl),
+            self.id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_failed(&self, _error: &str, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE data_exports
+            SET status = 'failed', error = $1, completed_at = $2
+            WHERE id = $3
+            "#,
+            _error,
+            Utc::now(),
+            self.id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Hapus catatan ekspor yang sudah kedaluwarsa dan kembalikan isinya, supaya
+    /// file arsipnya bisa ikut dihapus.
+    pub async fn purge_expired(_pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let exports = sqlx::query_as!(
+            DataExport,
+            r#"
+            DELETE FROM data_exports
+            WHERE expires_at < $1
+            RETURNING id, user_id, status as "status: ExportStatus", file_path, error, requested_at, started_at, completed_at, expires_at
+            "#,
+            Utc::now()
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let exports = Vec::new();
+
+        Ok(exports)
+    }
+
+    /// Hapus semua catatan ekspor milik pengguna, mis. sebelum akunnya dihapus.
+    pub async fn delete_for_user(_user_id: Uuid, _pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let exports = sqlx::query_as!(
+            DataExport,
+            r#"
+            DELETE FROM data_exports
+            WHERE user_id = $1
+            RETURNING id, user_id, status as "status: ExportStatus", file_path, error, requested_at, started_at, completed_at, expires_at
+            "#,
+            _user_id
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let exports = Vec::new();
+
+        Ok(exports)
+    }
+
+    /// Masih menunggu atau sedang dibuat oleh background job.
+    pub fn is_in_progress(&self) -> bool {
+        matches!(
+            self.status,
+            ExportStatus::Pending | ExportStatus::Processing
+        )
+    }
+
+    /// Arsip sudah jadi dan belum kedaluwarsa.
+    pub fn is_downloadable(&self) -> bool {
+        self.status == ExportStatus::Ready
+            && self.file_path.is_some()
+            && self
+                .expires_at
+                .is_some_and(|expires_at| expires_at > Utc::now())
+    }
+
+    pub fn into_response(self) -> DataExportResponse {
+        DataExportResponse {
+            id: self.id,
+            status: self.status,
+            requested_at: self.requested_at,
+            completed_at: self.completed_at,
+            expires_at: self.expires_at,
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use serde::Deserialize;
+
+use crate::models::errors::ValidationErrors;
+use crate::models::role::Role;
+
+pub const DEFAULT_DIRECTORY_PAGE_SIZE: i64 = 20;
+pub const MAX_DIRECTORY_PAGE_SIZE: i64 = 100;
+pub const MAX_SEARCH_QUERY_LENGTH: usize = 64;
+
+/// Parameter `GET /users`. Tanpa `query`, semua pengguna ditampilkan urut username.
+#[derive(Debug, Deserialize, Default)]
+pub struct SearchUsersQuery {
+    /// Dicocokkan dengan awalan dan kemiripan (trigram) username serta nama tampilan.
+    pub query: Option<String>,
+    /// `true` hanya pengguna online, `false` hanya yang offline.
+    pub online: Option<bool>,
+    pub role: Option<Role>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl SearchUsersQuery {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::default();
+
+        if self
+            .query
+            .as_deref()
+            .is_some_and(|query| query.trim().chars().count() > MAX_SEARCH_QUERY_LENGTH)
+        {
+            errors.add(
+                "query",
+                "too_long",
+                format!(
+                    "Kata kunci pencarian maksimal {} karakter",
+                    MAX_SEARCH_QUERY_LENGTH
+                ),
+            );
+        }
+
+        errors
+    }
+
+    /// Kata kunci dalam huruf kecil, atau `None` jika kosong.
+    pub fn search_term(&self) -> Option<String> {
+        self.query
+            .as_deref()
+            .map(|query| query.trim().to_lowercase())
+            .filter(|query| !query.is_empty())
+    }
+
+    /// `(limit, offset)` yang sudah dibatasi ke rentang yang wajar.
+    pub fn page(&self) -> (i64, i64) {
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_DIRECTORY_PAGE_SIZE)
+            .clamp(1, MAX_DIRECTORY_PAGE_SIZE);
+        let offset = self.offset.unwrap_or(0).max(0);
+        (limit, offset)
+    }
+}
+
+/// Pola `LIKE` untuk mencari awalan `term`; `%`, `_`, dan `\` di dalam `term` di-escape
+/// supaya dicocokkan apa adanya.
+pub fn like_prefix_pattern(term: &str) -> String {
+    let mut pattern = String::with_capacity(term.len() + 1);
+    for c in term.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
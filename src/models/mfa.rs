@@ -0,0 +1,463 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use data_encoding::BASE32_NOPAD;
+use rand::{RngCore, rngs::OsRng};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::config::mfa::{RECOVERY_CODE_COUNT, get_mfa_challenge_ttl};
+use crate::utils::token::{generate_opaque_token, hash_token};
+use crate::utils::totp::{generate_secret, matching_step};
+
+/// Secret TOTP milik pengguna. 2FA aktif setelah `enabled_at` terisi.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct UserTotp {
+    pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub enabled_at: Option<DateTime<Utc>>,
+    /// Langkah waktu terakhir yang dipakai, agar kode yang sama tidak bisa dipakai ulang.
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Kode pemulihan sekali pakai untuk login jika perangkat authenticator hilang.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct RecoveryCode {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub code_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+/// Tantangan login langkah kedua. Token mentahnya dikirim ke klien sebagai `mfa_token`.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct MfaChallenge {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub device_name: Option<String>,
+    pub attempts: i32,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnrollTotpResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmTotpRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    /// Hanya ditampilkan sekali; server hanya menyimpan hash-nya.
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DisableTotpRequest {
+    pub password: String,
+    /// Kode TOTP atau kode pemulihan.
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MfaVerifyRequest {
+    pub mfa_token: String,
+    /// Kode TOTP atau kode pemulihan.
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+    /// Masa berlaku `mfa_token` dalam detik.
+    pub expires_in: i64,
+}
+
+impl UserTotp {
+    /// Mulai (atau ulangi) pendaftaran TOTP dengan secret baru.
+    /// Mengembalikan `None` jika 2FA pengguna sudah aktif.
+    pub async fn start_enrollment(user_id: Uuid, _pool: &PgPool) -> Result<Option<Self>> {
+        let totp = Self {
+            user_id,
+            secret: generate_secret(),
+            enabled_at: None,
+            last_used_step: None,
+            created_at: Utc::now(),
+        };
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let totp = sqlx::query_as!(
+            UserTotp,
+            r#"
+            INSERT INTO user_totp (user_id, secret, enabled_at, last_used_step, created_at)
+            VALUES ($1, $2, NULL, NULL, $3)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = EXCLUDED.created_at
+            WHERE user_totp.enabled_at IS NULL
+            RETURNING user_id, secret, enabled_at, last_used_step, created_at
+            "#,
+            totp.user_id,
+            totp.secret,
+            totp.created_at
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let totp = Some(totp);
+
+        Ok(totp)
+    }
+
+    pub async fn find(_user_id: Uuid, _pool: &PgPool) -> Result<Option<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let totp = sqlx::query_as!(
+            UserTotp,
+            r#"
+            SELECT user_id, secret, enabled_at, last_used_step, created_at
+            FROM user_totp
+            WHERE user_id = $1
+            "#,
+            _user_id
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let totp = None;
+
+        Ok(totp)
+    }
+
+    /// Aktifkan 2FA setelah kode pertama terverifikasi.
+    pub async fn enable(_user_id: Uuid, _step: i64, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE user_totp
+            SET enabled_at = $1, last_used_step = $2
+            WHERE user_id = $3 AND enabled_at IS NULL
+            "#,
+            Utc::now(),
+            _step,
+            _user_id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 1;
+
+        Ok(rows == 1)
+    }
+
+    /// Catat langkah waktu yang baru dipakai. Mengembalikan `false` jika langkah itu
+    /// (atau yang lebih baru) sudah pernah dipakai.
+    pub async fn record_step(_user_id: Uuid, _step: i64, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE user_totp
+            SET last_used_step = $1
+            WHERE user_id = $2
+              AND enabled_at IS NOT NULL
+              AND (last_used_step IS NULL OR last_used_step < $1)
+            "#,
+            _step,
+            _user_id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 1;
+
+        Ok(rows == 1)
+    }
+
+    pub async fn delete(_user_id: Uuid, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            DELETE FROM user_totp
+            WHERE user_id = $1
+            "#,
+            _user_id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+
+    /// Langkah waktu yang cocok dengan `code`, kecuali langkah yang sudah pernah dipakai.
+    pub fn verify_code(&self, code: &str, unix_time: i64) -> Option<i64> {
+        matching_step(&self.secret, code, unix_time)
+            .filter(|step| self.last_used_step.is_none_or(|last| *step > last))
+    }
+}
+
+impl RecoveryCode {
+    /// Buat satu set kode pemulihan. Kode mentah dikembalikan terpisah untuk ditampilkan.
+    pub fn generate_set(user_id: Uuid) -> (Vec<String>, Vec<Self>) {
+        let now = Utc::now();
+
+        (0..RECOVERY_CODE_COUNT)
+            .map(|_| {
+                let mut bytes = [0u8; 7];
+                OsRng.fill_bytes(&mut bytes);
+                let raw = BASE32_NOPAD.encode(&bytes).to_ascii_lowercase();
+                let code = format!("{}-{}", &raw[..5], &raw[5..10]);
+
+                let record = Self {
+                    id: Uuid::new_v4(),
+                    user_id,
+                    code_hash: hash_token(&normalize_recovery_code(&code)),
+                    created_at: now,
+                    used_at: None,
+                };
+
+                (code, record)
+            })
+            .unzip()
+    }
+
+    /// Ganti semua kode pemulihan pengguna dengan set baru.
+    pub async fn replace_for_user(_user_id: Uuid, _codes: &[Self], _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        {
+            let ids: Vec<Uuid> = _codes.iter().map(|code| code.id).collect();
+            let hashes: Vec<String> = _codes.iter().map(|code| code.code_hash.clone()).collect();
+            let mut tx = _pool.begin().await?;
+
+            sqlx::query!(
+                r#"
+                DELETE FROM mfa_recovery_codes
+                WHERE user_id = $1
+                "#,
+                _user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                INSERT INTO mfa_recovery_codes (id, user_id, code_hash, created_at, used_at)
+                SELECT id, $2, code_hash, $3, NULL
+                FROM UNNEST($1::uuid[], $4::text[]) AS codes(id, code_hash)
+                "#,
+                &ids,
+                _user_id,
+                Utc::now(),
+                &hashes
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Pakai satu kode pemulihan. Mengembalikan `false` jika kode salah atau sudah dipakai.
+    pub async fn consume(_user_id: Uuid, code: &str, _pool: &PgPool) -> Result<bool> {
+        let _code_hash = hash_token(&normalize_recovery_code(code));
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE mfa_recovery_codes
+            SET used_at = $1
+            WHERE user_id = $2 AND code_hash = $3 AND used_at IS NULL
+            "#,
+            Utc::now(),
+            _user_id,
+            _code_hash
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 0;
+
+        Ok(rows == 1)
+    }
+
+    pub async fn delete_for_user(_user_id: Uuid, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            DELETE FROM mfa_recovery_codes
+            WHERE user_id = $1
+            "#,
+            _user_id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Kode pemulihan boleh diketik dengan huruf besar, spasi, atau tanpa tanda hubung.
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+impl MfaChallenge {
+    pub fn new(user_id: Uuid, device_name: Option<String>) -> (String, Self) {
+        let token = generate_opaque_token();
+        let now = Utc::now();
+
+        let challenge = Self {
+            id: Uuid::new_v4(),
+            user_id,
+            token_hash: hash_token(&token),
+            device_name,
+            attempts: 0,
+            expires_at: now + Duration::seconds(get_mfa_challenge_ttl()),
+            created_at: now,
+            used_at: None,
+        };
+
+        (token, challenge)
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let challenge = sqlx::query_as!(
+            MfaChallenge,
+            r#"
+            INSERT INTO mfa_challenges (id, user_id, token_hash, device_name, attempts, expires_at, created_at, used_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, user_id, token_hash, device_name, attempts, expires_at, created_at, used_at
+            "#,
+            self.id,
+            self.user_id,
+            self.token_hash,
+            self.device_name,
+            self.attempts,
+            self.expires_at,
+            self.created_at,
+            self.used_at
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let challenge = self;
+
+        Ok(challenge)
+    }
+
+    pub async fn find_by_token(token: &str, _pool: &PgPool) -> Result<Option<Self>> {
+        let _token_hash = hash_token(token);
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let challenge = sqlx::query_as!(
+            MfaChallenge,
+            r#"
+            SELECT id, user_id, token_hash, device_name, attempts, expires_at, created_at, used_at
+            FROM mfa_challenges
+            WHERE token_hash = $1
+            "#,
+            _token_hash
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let challenge = None;
+
+        Ok(challenge)
+    }
+
+    /// Catat satu percobaan kode yang salah.
+    pub async fn record_failure(&self, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE mfa_challenges
+            SET attempts = attempts + 1
+            WHERE id = $1
+            "#,
+            self.id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Tandai tantangan selesai. Mengembalikan `false` jika sudah dipakai, kedaluwarsa,
+    /// atau sudah melewati batas percobaan.
+    pub async fn consume(&self, _max_attempts: i32, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE mfa_challenges
+            SET used_at = $1
+            WHERE id = $2 AND used_at IS NULL AND expires_at > $1 AND attempts < $3
+            "#,
+            Utc::now(),
+            self.id,
+            _max_attempts
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = u64::from(self.is_usable(_max_attempts));
+
+        Ok(rows == 1)
+    }
+
+    /// Hapus tantangan yang sudah kedaluwarsa.
+    pub async fn purge_expired(_pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let purged = sqlx::query!(
+            r#"
+            DELETE FROM mfa_challenges
+            WHERE expires_at <= $1
+            "#,
+            Utc::now()
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let purged = 0;
+
+        Ok(purged)
+    }
+
+    pub fn is_usable(&self, max_attempts: i32) -> bool {
+        self.used_at.is_none() && self.expires_at > Utc::now() && self.attempts < max_attempts
+    }
+}
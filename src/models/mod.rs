@@ -1,3 +1,25 @@
+pub mod account_deletion;
+pub mod admin;
+pub mod api_token;
+pub mod block;
+pub mod contact;
+pub mod conversation;
+pub mod data_export;
+pub mod directory;
+pub mod email_verification;
 pub mod errors;
+pub mod login_throttle;
 pub mod message;
+pub mod message_search;
+pub mod mfa;
+pub mod oidc;
+pub mod password_reset;
+pub mod presence;
+pub mod profile;
+pub mod refresh_token;
+pub mod retention;
+pub mod revoked_token;
+pub mod role;
+pub mod session;
 pub mod user;
+pub mod user_cache;
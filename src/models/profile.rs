@@ -0,0 +1,274 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::models::contact::Privacy;
+use crate::models::errors::ValidationErrors;
+use crate::models::role::Role;
+use crate::models::user::User;
+use crate::utils::validation::is_valid_timezone;
+
+pub const MAX_DISPLAY_NAME_LENGTH: usize = 50;
+pub const MAX_BIO_LENGTH: usize = 500;
+pub const MAX_PRONOUNS_LENGTH: usize = 40;
+
+/// Content type semua avatar yang disimpan; server selalu menyimpannya sebagai PNG.
+pub const AVATAR_CONTENT_TYPE: &str = "image/png";
+
+/// URL avatar pengguna. `v` berubah setiap avatar diganti sehingga URL lama tidak
+/// menampilkan gambar dari cache.
+pub fn avatar_url(user_id: Uuid, updated_at: DateTime<Utc>) -> String {
+    format!("/users/{}/avatar?v={}", user_id, updated_at.timestamp())
+}
+
+/// Perubahan profil lewat `PATCH /users/me`. Field yang tidak dikirim tidak berubah;
+/// string kosong menghapus isinya.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateProfileRequest {
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub timezone: Option<String>,
+    pub pronouns: Option<String>,
+    pub privacy: Option<Privacy>,
+}
+
+impl UpdateProfileRequest {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::default();
+
+        if let Some(display_name) = self.display_name.as_deref().map(str::trim) {
+            if display_name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+                errors.add(
+                    "display_name",
+                    "too_long",
+                    format!(
+                        "Nama tampilan maksimal {} karakter",
+                        MAX_DISPLAY_NAME_LENGTH
+                    ),
+                );
+            }
+            if display_name.chars().any(char::is_control) {
+                errors.add(
+                    "display_name",
+                    "invalid_characters",
+                    "Nama tampilan tidak boleh berisi karakter kontrol",
+                );
+            }
+        }
+
+        if let Some(bio) = self.bio.as_deref().map(str::trim) {
+            if bio.chars().count() > MAX_BIO_LENGTH {
+                errors.add(
+                    "bio",
+                    "too_long",
+                    format!("Bio maksimal {} karakter", MAX_BIO_LENGTH),
+                );
+            }
+            if bio.chars().any(|c| c.is_control() && c != '\n') {
+                errors.add(
+                    "bio",
+                    "invalid_characters",
+                    "Bio tidak boleh berisi karakter kontrol selain baris baru",
+                );
+            }
+        }
+
+        if let Some(timezone) = self.timezone.as_deref().map(str::trim)
+            && !timezone.is_empty()
+            && !is_valid_timezone(timezone)
+        {
+            errors.add(
+                "timezone",
+                "invalid",
+                "Zona waktu harus berupa nama IANA, mis. Asia/Jakarta",
+            );
+        }
+
+        if let Some(pronouns) = self.pronouns.as_deref().map(str::trim) {
+            if pronouns.chars().count() > MAX_PRONOUNS_LENGTH {
+                errors.add(
+                    "pronouns",
+                    "too_long",
+                    format!("Kata ganti maksimal {} karakter", MAX_PRONOUNS_LENGTH),
+                );
+            }
+            if pronouns.chars().any(char::is_control) {
+                errors.add(
+                    "pronouns",
+                    "invalid_characters",
+                    "Kata ganti tidak boleh berisi karakter kontrol",
+                );
+            }
+        }
+
+        errors
+    }
+
+    /// Terapkan perubahan ke `user`. Panggil setelah `validate` lolos.
+    pub fn apply(self, user: User) -> User {
+        fn merge(change: Option<String>, current: Option<String>) -> Option<String> {
+            match change {
+                Some(value) => Some(value.trim().to_string()).filter(|value| !value.is_empty()),
+                None => current,
+            }
+        }
+
+        User {
+            display_name: merge(self.display_name, user.display_name),
+            bio: merge(self.bio, user.bio),
+            timezone: merge(self.timezone, user.timezone),
+            pronouns: merge(self.pronouns, user.pronouns),
+            privacy: self.privacy.unwrap_or(user.privacy),
+            ..user
+        }
+    }
+}
+
+/// Profil publik pengguna, tanpa email dan data akun lain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub timezone: Option<String>,
+    pub pronouns: Option<String>,
+    pub avatar_url: Option<String>,
+    pub role: Role,
+    pub is_bot: bool,
+    /// Selalu `false` jika pengaturan privasi pengguna tidak mengizinkan pelihat.
+    pub is_online: bool,
+    /// `None` jika pengaturan privasi pengguna tidak mengizinkan pelihat.
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl ProfileResponse {
+    /// Sembunyikan status online dan `last_seen`.
+    pub fn hide_presence(self) -> Self {
+        Self {
+            is_online: false,
+            last_seen: None,
+            ..self
+        }
+    }
+}
+
+/// Avatar yang sudah diperkecil, disimpan di database supaya semua node bisa menyajikannya.
+#[derive(Debug, FromRow, Clone)]
+pub struct Avatar {
+    pub user_id: Uuid,
+    pub content_type: String,
+    pub data: Vec<u8>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Avatar {
+    pub fn new(user_id: Uuid, data: Vec<u8>) -> Self {
+        Self {
+            user_id,
+            content_type: AVATAR_CONTENT_TYPE.to_string(),
+            data,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Simpan atau ganti avatar dan perbarui `users.avatar_updated_at` dalam satu transaksi.
+    pub async fn save(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        {
+            let mut tx = _pool.begin().await?;
+
+            sqlx::query!(
+                r#"
+                INSERT INTO user_avatars (user_id, content_type, data, updated_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id) DO UPDATE
+                SET content_type = EXCLUDED.content_type, data = EXCLUDED.data, updated_at = EXCLUDED.updated_at
+                "#,
+                self.user_id,
+                self.content_type,
+                self.data,
+                self.updated_at
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                UPDATE users
+                SET avatar_updated_at = $1, updated_at = $1
+                WHERE id = $2
+                "#,
+                self.updated_at,
+                self.user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+        }
+
+        Ok(self)
+    }
+
+    pub async fn find(_user_id: Uuid, _pool: &PgPool) -> Result<Option<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let avatar = sqlx::query_as!(
+            Avatar,
+            r#"
+            SELECT user_id, content_type, data, updated_at
+            FROM user_avatars
+            WHERE user_id = $1
+            "#,
+            _user_id
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let avatar = None;
+
+        Ok(avatar)
+    }
+
+    /// Hapus avatar pengguna. Mengembalikan `false` jika pengguna tidak punya avatar.
+    pub async fn delete(_user_id: Uuid, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = {
+            let mut tx = _pool.begin().await?;
+
+            let rows = sqlx::query!(
+                r#"
+                DELETE FROM user_avatars
+                WHERE user_id = $1
+                "#,
+                _user_id
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            sqlx::query!(
+                r#"
+                UPDATE users
+                SET avatar_updated_at = NULL, updated_at = $1
+                WHERE id = $2
+                "#,
+                Utc::now(),
+                _user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            rows
+        };
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 0;
+
+        Ok(rows == 1)
+    }
+}
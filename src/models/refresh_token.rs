@@ -0,0 +1,197 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::config::get_refresh_token_expiration;
+use crate::utils::token::{generate_opaque_token, hash_token};
+
+/// Refresh token yang dirotasi setiap kali dipakai.
+///
+/// Semua token hasil rotasi dari satu login berbagi `family_id`, sehingga pemakaian
+/// ulang token lama dapat mencabut seluruh rantainya.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+impl RefreshToken {
+    /// Buat refresh token baru. Token mentah dikembalikan terpisah karena hanya hash-nya
+    /// yang disimpan.
+    pub fn new(user_id: Uuid, family_id: Option<Uuid>) -> (String, Self) {
+        let token = generate_opaque_token();
+        let now = Utc::now();
+
+        let refresh_token = Self {
+            id: Uuid::new_v4(),
+            user_id,
+            family_id: family_id.unwrap_or_else(Uuid::new_v4),
+            token_hash: hash_token(&token),
+            expires_at: now + Duration::seconds(get_refresh_token_expiration()),
+            created_at: now,
+            rotated_at: None,
+            revoked_at: None,
+        };
+
+        (token, refresh_token)
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let refresh_token = sqlx::query_as!(
+            RefreshToken,
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, created_at, rotated_at, revoked_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, user_id, family_id, token_hash, expires_at, created_at, rotated_at, revoked_at
+            "#,
+            self.id,
+            self.user_id,
+            self.family_id,
+            self.token_hash,
+            self.expires_at,
+            self.created_at,
+            self.rotated_at,
+            self.revoked_at
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let refresh_token = self;
+
+        Ok(refresh_token)
+    }
+
+    pub async fn find_by_token(token: &str, _pool: &PgPool) -> Result<Option<Self>> {
+        let _token_hash = hash_token(token);
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let refresh_token = sqlx::query_as!(
+            RefreshToken,
+            r#"
+            SELECT id, user_id, family_id, token_hash, expires_at, created_at, rotated_at, revoked_at
+            FROM refresh_tokens
+            WHERE token_hash = $1
+            "#,
+            _token_hash
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let refresh_token = None;
+
+        Ok(refresh_token)
+    }
+
+    /// Tandai token sudah dirotasi. Mengembalikan `false` jika token sudah lebih dulu
+    /// dirotasi atau dicabut oleh request lain.
+    pub async fn mark_rotated(&self, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET rotated_at = $1
+            WHERE id = $2 AND rotated_at IS NULL AND revoked_at IS NULL
+            "#,
+            Utc::now(),
+            self.id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 1;
+
+        Ok(rows == 1)
+    }
+
+    /// Cabut semua token dalam satu keluarga rotasi.
+    pub async fn revoke_family(_family_id: Uuid, _pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let revoked = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = $1
+            WHERE family_id = $2 AND revoked_at IS NULL
+            "#,
+            Utc::now(),
+            _family_id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let revoked = 0;
+
+        Ok(revoked)
+    }
+
+    /// Cabut semua refresh token aktif milik pengguna.
+    pub async fn revoke_all_for_user(_user_id: Uuid, _pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let revoked = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = $1
+            WHERE user_id = $2 AND revoked_at IS NULL
+            "#,
+            Utc::now(),
+            _user_id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let revoked = 0;
+
+        Ok(revoked)
+    }
+
+    /// Hapus refresh token yang sudah kedaluwarsa.
+    pub async fn purge_expired(_pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let purged = sqlx::query!(
+            r#"
+            DELETE FROM refresh_tokens
+            WHERE expires_at <= $1
+            "#,
+            Utc::now()
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let purged = 0;
+
+        Ok(purged)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    /// Token sudah pernah dipakai untuk rotasi atau sudah dicabut.
+    pub fn is_spent(&self) -> bool {
+        self.rotated_at.is_some() || self.revoked_at.is_some()
+    }
+}
@@ -0,0 +1,493 @@
+use std::fmt;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+use crate::config::retention::RetentionAction;
+use crate::models::errors::ValidationErrors;
+
+pub const MAX_RETENTION_DAYS: i32 = 36500;
+pub const MAX_LEGAL_HOLD_REASON_LENGTH: usize = 500;
+
+/// Percakapan yang diatur kebijakan retensi atau legal hold. Disimpan sebagai teks yang sama
+/// dengan kolom `messages.conversation_key`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversationKey {
+    Public,
+    /// Pesan pribadi antara dua pengguna; ID selalu diurutkan supaya arahnya tidak berpengaruh.
+    Direct(Uuid, Uuid),
+}
+
+impl ConversationKey {
+    pub fn direct(user1_id: Uuid, user2_id: Uuid) -> Self {
+        ConversationKey::Direct(user1_id.min(user2_id), user1_id.max(user2_id))
+    }
+
+    /// Terima `public` atau `direct:<id>:<id>` dengan urutan ID apa pun.
+    pub fn parse(key: &str) -> Option<Self> {
+        match key.trim() {
+            "public" => Some(ConversationKey::Public),
+            key => {
+                let (user1_id, user2_id) = key.strip_prefix("direct:")?.split_once(':')?;
+                let (user1_id, user2_id) = (user1_id.parse().ok()?, user2_id.parse().ok()?);
+                (user1_id != user2_id).then(|| Self::direct(user1_id, user2_id))
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConversationKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversationKey::Public => write!(f, "public"),
+            ConversationKey::Direct(user1_id, user2_id) => {
+                write!(f, "direct:{}:{}", user1_id, user2_id)
+            }
+        }
+    }
+}
+
+/// Masa retensi satu percakapan, menggantikan default dari `MESSAGE_RETENTION_DAYS`.
+#[derive(Debug, Serialize, FromRow, Clone)]
+pub struct RetentionPolicy {
+    pub conversation_key: String,
+    /// Kosong berarti pesan disimpan selamanya.
+    pub retention_days: Option<i32>,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetentionPolicyRequest {
+    /// `null` untuk menyimpan pesan selamanya.
+    pub retention_days: Option<i32>,
+}
+
+impl RetentionPolicyRequest {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::default();
+
+        if self
+            .retention_days
+            .is_some_and(|days| !(1..=MAX_RETENTION_DAYS).contains(&days))
+        {
+            errors.add(
+                "retention_days",
+                "out_of_range",
+                format!(
+                    "Masa retensi harus antara 1 dan {} hari, atau null untuk selamanya",
+                    MAX_RETENTION_DAYS
+                ),
+            );
+        }
+
+        errors
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionSettingsResponse {
+    pub default_retention_days: Option<i32>,
+    pub action: RetentionAction,
+    pub policies: Vec<RetentionPolicy>,
+}
+
+impl RetentionPolicy {
+    pub fn new(key: ConversationKey, retention_days: Option<i32>, updated_by: Uuid) -> Self {
+        Self {
+            conversation_key: key.to_string(),
+            retention_days,
+            updated_by: Some(updated_by),
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub async fn save(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            INSERT INTO retention_policies (conversation_key, retention_days, updated_by, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (conversation_key) DO UPDATE
+            SET retention_days = EXCLUDED.retention_days,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            self.conversation_key,
+            self.retention_days,
+            self.updated_by,
+            self.updated_at
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(self)
+    }
+
+    /// Hapus kebijakan sehingga percakapan kembali memakai default. Mengembalikan `false`
+    /// jika tidak ada.
+    pub async fn remove(_key: ConversationKey, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            DELETE FROM retention_policies
+            WHERE conversation_key = $1
+            "#,
+            _key.to_string()
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 0;
+
+        Ok(rows == 1)
+    }
+
+    pub async fn list(_pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let policies = sqlx::query_as!(
+            RetentionPolicy,
+            r#"
+            SELECT conversation_key, retention_days, updated_by, updated_at
+            FROM retention_policies
+            ORDER BY conversation_key
+            "#
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let policies = Vec::new();
+
+        Ok(policies)
+    }
+}
+
+/// Pengecualian dari job retensi untuk semua pesan milik satu pengguna atau di satu
+/// percakapan, sampai `released_at` diisi.
+#[derive(Debug, Serialize, FromRow, Clone)]
+pub struct LegalHold {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub conversation_key: Option<String>,
+    pub reason: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+/// Isi salah satu dari `user_id` atau `conversation`.
+#[derive(Debug, Deserialize, Default)]
+pub struct LegalHoldRequest {
+    pub user_id: Option<Uuid>,
+    /// `public` atau `direct:<id>:<id>`.
+    pub conversation: Option<String>,
+    pub reason: String,
+}
+
+impl LegalHoldRequest {
+    pub fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::default();
+
+        match (self.user_id, self.conversation.as_deref()) {
+            (Some(_), Some(_)) | (None, None) => errors.add(
+                "user_id",
+                "invalid",
+                "Isi salah satu dari user_id atau conversation",
+            ),
+            (None, Some(conversation)) if ConversationKey::parse(conversation).is_none() => errors
+                .add(
+                    "conversation",
+                    "invalid",
+                    "Percakapan harus `public` atau `direct:<id>:<id>`",
+                ),
+            _ => {}
+        }
+
+        let reason = self.reason.trim();
+        if reason.is_empty() {
+            errors.add("reason", "required", "Alasan legal hold wajib diisi");
+        } else if reason.chars().count() > MAX_LEGAL_HOLD_REASON_LENGTH {
+            errors.add(
+                "reason",
+                "too_long",
+                format!("Alasan maksimal {} karakter", MAX_LEGAL_HOLD_REASON_LENGTH),
+            );
+        }
+
+        errors
+    }
+}
+
+impl LegalHold {
+    /// Hold baru dari request yang sudah divalidasi.
+    pub fn new(request: LegalHoldRequest, created_by: Uuid) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id: request.user_id,
+            conversation_key: request
+                .conversation
+                .as_deref()
+                .and_then(ConversationKey::parse)
+                .map(|key| key.to_string()),
+            reason: request.reason.trim().to_string(),
+            created_by: Some(created_by),
+            created_at: Utc::now(),
+            released_at: None,
+        }
+    }
+
+    pub async fn create(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            INSERT INTO legal_holds (id, user_id, conversation_key, reason, created_by, created_at, released_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            self.id,
+            self.user_id,
+            self.conversation_key,
+            self.reason,
+            self.created_by,
+            self.created_at,
+            self.released_at
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(self)
+    }
+
+    /// Hold yang masih berlaku, terbaru lebih dulu.
+    pub async fn list_active(_pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let holds = sqlx::query_as!(
+            LegalHold,
+            r#"
+            SELECT id, user_id, conversation_key, reason, created_by, created_at, released_at
+            FROM legal_holds
+            WHERE released_at IS NULL
+            ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let holds = Vec::new();
+
+        Ok(holds)
+    }
+
+    /// Lepas hold. Mengembalikan `false` jika tidak ada atau sudah dilepas.
+    pub async fn release(_id: Uuid, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE legal_holds
+            SET released_at = $2
+            WHERE id = $1 AND released_at IS NULL
+            "#,
+            _id,
+            Utc::now()
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 0;
+
+        Ok(rows == 1)
+    }
+
+    /// Apakah ada hold aktif pada akun `_user_id` (termasuk bot-botnya) atau pada percakapan
+    /// yang pernah mereka ikuti. Selama ada, akun tidak boleh dihapus karena penghapusan
+    /// mengubah atau menghapus pesan yang ditahan.
+    pub async fn covers_account(_user_id: Uuid, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let held = sqlx::query_scalar!(
+            r#"
+            WITH account AS (
+                SELECT id FROM users WHERE id = $1 OR bot_owner_id = $1
+            )
+            SELECT EXISTS (
+                SELECT 1 FROM legal_holds h
+                WHERE h.released_at IS NULL
+                  AND (
+                      h.user_id IN (SELECT id FROM account)
+                      OR EXISTS (
+                          SELECT 1 FROM messages m
+                          WHERE m.conversation_key = h.conversation_key
+                            AND (m.sender_id IN (SELECT id FROM account)
+                                 OR m.receiver_id IN (SELECT id FROM account))
+                      )
+                  )
+            ) as "held!"
+            "#,
+            _user_id
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let held = false;
+
+        Ok(held)
+    }
+}
+
+/// Ringkasan pesan yang sudah melewati masa retensi di satu percakapan.
+#[derive(Debug, Serialize, FromRow, Clone)]
+pub struct RetentionReportEntry {
+    pub conversation_key: String,
+    pub retention_days: i32,
+    /// Pesan yang akan dihapus atau diarsipkan pada putaran job berikutnya.
+    pub expired_count: i64,
+    /// Pesan yang sudah kedaluwarsa tetapi ditahan legal hold.
+    pub held_count: i64,
+    pub oldest_expired_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionReport {
+    pub generated_at: DateTime<Utc>,
+    pub default_retention_days: Option<i32>,
+    pub action: RetentionAction,
+    pub total_expired: i64,
+    pub total_held: i64,
+    pub conversations: Vec<RetentionReportEntry>,
+}
+
+impl RetentionReport {
+    pub fn new(
+        generated_at: DateTime<Utc>,
+        default_retention_days: Option<i32>,
+        action: RetentionAction,
+        conversations: Vec<RetentionReportEntry>,
+    ) -> Self {
+        Self {
+            generated_at,
+            default_retention_days,
+            action,
+            total_expired: conversations.iter().map(|entry| entry.expired_count).sum(),
+            total_held: conversations.iter().map(|entry| entry.held_count).sum(),
+            conversations,
+        }
+    }
+}
+
+/// Pesan kedaluwarsa per percakapan pada waktu `_now`, tanpa mengubah apa pun.
+pub async fn expired_messages_report(
+    _default_days: Option<i32>,
+    _now: DateTime<Utc>,
+    _pool: &PgPool,
+) -> Result<Vec<RetentionReportEntry>> {
+    #[cfg(not(any(debug_assertions, ci)))]
+    let entries = sqlx::query_as!(
+        RetentionReportEntry,
+        r#"
+        WITH expired AS (
+            SELECT m.conversation_key, m.created_at, r.retention_days,
+                   EXISTS (
+                       SELECT 1 FROM legal_holds h
+                       WHERE h.released_at IS NULL
+                         AND (h.user_id IN (m.sender_id, m.receiver_id) OR h.conversation_key = m.conversation_key)
+                   ) AS held
+            FROM messages m
+            LEFT JOIN retention_policies p ON p.conversation_key = m.conversation_key
+            CROSS JOIN LATERAL (
+                SELECT CASE WHEN p.conversation_key IS NULL THEN $1::int ELSE p.retention_days END AS retention_days
+            ) r
+            WHERE r.retention_days IS NOT NULL
+              AND m.created_at < $2::timestamptz - make_interval(days => r.retention_days)
+        )
+        SELECT conversation_key as "conversation_key!", MAX(retention_days) as "retention_days!",
+               COUNT(*) FILTER (WHERE NOT held) as "expired_count!",
+               COUNT(*) FILTER (WHERE held) as "held_count!",
+               MIN(created_at) as "oldest_expired_at!"
+        FROM expired
+        GROUP BY conversation_key
+        ORDER BY 3 DESC, conversation_key
+        "#,
+        _default_days,
+        _now
+    )
+    .fetch_all(_pool)
+    .await?;
+
+    #[cfg(any(debug_assertions, ci))]
+    let entries = match _default_days {
+        Some(days) => vec![RetentionReportEntry {
+            conversation_key: ConversationKey::Public.to_string(),
+            retention_days: days,
+            expired_count: 3,
+            held_count: 1,
+            oldest_expired_at: _now - chrono::Duration::days(i64::from(days) + 30),
+        }],
+        None => Vec::new(),
+    };
+
+    Ok(entries)
+}
+
+/// Hapus atau arsipkan paling banyak `_batch_size` pesan kedaluwarsa yang tidak ditahan
+/// legal hold, mulai dari yang terlama. Mengembalikan jumlah pesan yang diproses.
+pub async fn purge_expired_messages(
+    _default_days: Option<i32>,
+    _action: RetentionAction,
+    _batch_size: i64,
+    _now: DateTime<Utc>,
+    _pool: &PgPool,
+) -> Result<i64> {
+    #[cfg(not(any(debug_assertions, ci)))]
+    let purged = sqlx::query_scalar!(
+        r#"
+        WITH expired AS (
+            SELECT m.id
+            FROM messages m
+            LEFT JOIN retention_policies p ON p.conversation_key = m.conversation_key
+            WHERE m.created_at < $2::timestamptz - make_interval(
+                      days => CASE WHEN p.conversation_key IS NULL THEN $1::int ELSE p.retention_days END
+                  )
+              AND NOT EXISTS (
+                  SELECT 1 FROM legal_holds h
+                  WHERE h.released_at IS NULL
+                    AND (h.user_id IN (m.sender_id, m.receiver_id) OR h.conversation_key = m.conversation_key)
+              )
+            ORDER BY m.created_at
+            LIMIT $3
+            FOR UPDATE OF m SKIP LOCKED
+        ),
+        deleted AS (
+            DELETE FROM messages m
+            USING expired e
+            WHERE m.id = e.id
+            RETURNING m.id, m.sender_id, m.receiver_id, m.content, m.created_at
+        ),
+        archived AS (
+            INSERT INTO archived_messages (id, sender_id, receiver_id, content, created_at, archived_at)
+            SELECT id, sender_id, receiver_id, content, created_at, $2
+            FROM deleted
+            WHERE $4
+        )
+        SELECT COUNT(*) as "count!" FROM deleted
+        "#,
+        _default_days,
+        _now,
+        _batch_size,
+        _action == RetentionAction::Archive
+    )
+    .fetch_one(_pool)
+    .await?;
+
+    #[cfg(any(debug_assertions, ci))]
+    let purged = 0;
+
+    Ok(purged)
+}
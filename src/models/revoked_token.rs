@@ -0,0 +1,144 @@
+use anyhow::Result;
+use chrono::{DateTime, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, postgres::PgPool};
+use uuid::Uuid;
+
+/// Access token yang dicabut sebelum masa berlakunya habis.
+#[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
+pub struct RevokedToken {
+    pub jti: String,
+    pub user_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: DateTime<Utc>,
+}
+
+impl RevokedToken {
+    /// Cabut satu access token. Baris disimpan sampai `_expires_at`, setelah itu token
+    /// sudah ditolak karena kedaluwarsa.
+    pub async fn revoke(
+        _jti: &str,
+        _user_id: Uuid,
+        _expires_at: DateTime<Utc>,
+        _pool: &PgPool,
+    ) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            INSERT INTO revoked_tokens (jti, user_id, expires_at, revoked_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (jti) DO NOTHING
+            "#,
+            _jti,
+            _user_id,
+            _expires_at,
+            Utc::now()
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Cabut semua access token milik pengguna yang diterbitkan sebelum detik ini.
+    pub async fn revoke_all_for_user(_user_id: Uuid, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            INSERT INTO token_revocation_cutoffs (user_id, revoked_before)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE SET revoked_before = EXCLUDED.revoked_before
+            "#,
+            _user_id,
+            revocation_cutoff(Utc::now())
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Token dicabut jika `jti`-nya dicabut, sesinya (`session_id`) sudah dicabut, atau
+    /// tertangkap batas "log out everywhere" (lihat [`revoked_by_cutoff`]).
+    pub async fn is_revoked(
+        _jti: &str,
+        _user_id: Uuid,
+        issued_at: DateTime<Utc>,
+        session_id: Option<Uuid>,
+        _pool: &PgPool,
+    ) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let (revoked, revoked_before) = {
+            let row = sqlx::query!(
+                r#"
+                SELECT
+                    (
+                        EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)
+                        OR EXISTS (
+                            SELECT 1 FROM sessions
+                            WHERE id = $3 AND revoked_at IS NOT NULL
+                        )
+                    ) AS "revoked!",
+                    (
+                        SELECT revoked_before FROM token_revocation_cutoffs
+                        WHERE user_id = $2
+                    ) AS revoked_before
+                "#,
+                _jti,
+                _user_id,
+                session_id
+            )
+            .fetch_one(_pool)
+            .await?;
+            (row.revoked, row.revoked_before)
+        };
+
+        #[cfg(any(debug_assertions, ci))]
+        let (revoked, revoked_before): (bool, Option<DateTime<Utc>>) = (false, None);
+
+        Ok(revoked
+            || revoked_before
+                .is_some_and(|cutoff| revoked_by_cutoff(cutoff, issued_at, session_id)))
+    }
+
+    /// Hapus catatan pencabutan untuk token yang sudah kedaluwarsa.
+    pub async fn purge_expired(_pool: &PgPool) -> Result<u64> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let purged = sqlx::query!(
+            r#"
+            DELETE FROM revoked_tokens
+            WHERE expires_at <= $1
+            "#,
+            Utc::now()
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let purged = 0;
+
+        Ok(purged)
+    }
+}
+
+/// Batas "log out everywhere" dibulatkan ke bawah ke detik penuh, karena klaim `iat` hanya
+/// berpresisi detik. Tanpa pembulatan, token yang diterbitkan di detik yang sama setelah
+/// pencabutan (mis. login ulang setelah reset password) ikut dianggap dicabut.
+pub fn revocation_cutoff(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.trunc_subsecs(0)
+}
+
+/// Apakah batas `cutoff` mencabut token yang diterbitkan pada `issued_at`.
+///
+/// Karena `cutoff` dibulatkan ke detik penuh, token yang diterbitkan di detik yang sama
+/// sebelum pencabutan lolos dari perbandingan waktu. Token dengan sesi tetap tertangkap
+/// karena sesinya ikut dicabut; token tanpa sesi (`session_id` kosong) tidak bisa dibedakan,
+/// jadi semuanya ditolak begitu batas ada. Server hanya menerbitkan token dengan sesi.
+pub fn revoked_by_cutoff(
+    cutoff: DateTime<Utc>,
+    issued_at: DateTime<Utc>,
+    session_id: Option<Uuid>,
+) -> bool {
+    session_id.is_none() || issued_at < cutoff
+}
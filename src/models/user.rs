@@ -3,10 +3,31 @@ use anyhow::Result;
 use bcrypt::verify;
 use bcrypt::{DEFAULT_COST, hash};
 use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, postgres::PgPool};
 use uuid::Uuid;
 
+use crate::config::password::PasswordPolicy;
+#[cfg(not(any(debug_assertions, ci)))]
+use crate::models::account_deletion::DELETED_USER_ID;
+use crate::models::contact::Privacy;
+use crate::models::directory::SearchUsersQuery;
+#[cfg(not(any(debug_assertions, ci)))]
+use crate::models::directory::like_prefix_pattern;
+use crate::models::errors::ValidationErrors;
+use crate::models::mfa::MfaChallengeResponse;
+use crate::models::profile::{ProfileResponse, avatar_url};
+use crate::models::role::Role;
+use crate::utils::email::normalize_email;
+use crate::utils::token::generate_opaque_token;
+use crate::utils::validation::{BreachedPasswords, validate_password, validate_username};
+
+/// Hash pembanding untuk username yang tidak dikenal, supaya waktu respons login
+/// sama dengan saat password salah.
+static DUMMY_PASSWORD_HASH: Lazy<String> =
+    Lazy::new(|| hash_password(&Uuid::new_v4().to_string()).unwrap_or_default());
+
 #[derive(Debug, Serialize, Deserialize, FromRow, Clone)]
 pub struct User {
     pub id: Uuid,
@@ -14,6 +35,26 @@ pub struct User {
     #[serde(skip_serializing)]
     pub password_hash: String,
     pub email: Option<String>,
+    /// Waktu email terakhir diverifikasi. `None` jika email belum diverifikasi.
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub role: Role,
+    /// Waktu akun diblokir admin. Pengguna yang diblokir tidak bisa login.
+    pub banned_at: Option<DateTime<Utc>>,
+    pub ban_reason: Option<String>,
+    /// Akun bot tidak bisa login dengan password; aksesnya hanya lewat personal access token.
+    pub is_bot: bool,
+    /// Pemilik akun bot. `None` untuk pengguna biasa.
+    pub bot_owner_id: Option<Uuid>,
+    /// Nama yang ditampilkan klien. `None` berarti klien memakai `username`.
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    /// Nama zona waktu IANA, mis. `Asia/Jakarta`.
+    pub timezone: Option<String>,
+    pub pronouns: Option<String>,
+    /// Waktu avatar terakhir diganti. `None` jika pengguna tidak punya avatar.
+    pub avatar_updated_at: Option<DateTime<Utc>>,
+    /// Siapa yang boleh mengirim pesan pribadi dan melihat status online pengguna ini.
+    pub privacy: Privacy,
     pub is_online: bool,
     pub last_seen: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
@@ -27,10 +68,47 @@ pub struct RegisterRequest {
     pub email: Option<String>,
 }
 
+impl RegisterRequest {
+    /// Periksa format semua field tanpa menyentuh database. Keunikan username dan
+    /// email diperiksa terpisah oleh handler.
+    pub fn validate(
+        &self,
+        policy: &PasswordPolicy,
+        breached: &BreachedPasswords,
+    ) -> ValidationErrors {
+        let mut errors = ValidationErrors::default();
+
+        if let Err(error) = validate_username(&self.username) {
+            errors.extend([error]);
+        }
+        errors.extend(validate_password(
+            &self.password,
+            &self.username,
+            policy,
+            breached,
+        ));
+        if let Some(email) = self.email.as_deref()
+            && normalize_email(email).is_none()
+        {
+            errors.add("email", "invalid", "Format email tidak valid");
+        }
+
+        errors
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
+    /// Nama perangkat yang ditampilkan di daftar sesi, mis. "Laptop kantor".
+    pub device_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct LogoutRequest {
+    /// Jika diisi, rantai refresh token milik sesi ini ikut dicabut.
+    pub refresh_token: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -38,17 +116,42 @@ pub struct UserResponse {
     pub id: Uuid,
     pub username: String,
     pub email: Option<String>,
+    pub email_verified: bool,
+    pub role: Role,
+    pub is_bot: bool,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub timezone: Option<String>,
+    pub pronouns: Option<String>,
+    pub avatar_url: Option<String>,
+    pub privacy: Privacy,
     pub is_online: bool,
     pub last_seen: DateTime<Utc>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CreateBotRequest {
+    pub username: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct TokenResponse {
     pub access_token: String,
     pub token_type: String,
+    /// Masa berlaku `access_token` dalam detik.
+    pub expires_in: i64,
+    pub refresh_token: String,
     pub user: UserResponse,
 }
 
+/// Balasan login: pasangan token, atau tantangan MFA jika 2FA pengguna aktif.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Tokens(Box<TokenResponse>),
+    MfaRequired(MfaChallengeResponse),
+}
+
 impl User {
     pub async fn new(request: RegisterRequest) -> Result<Self> {
         let password_hash = hash_password(&request.password)?;
@@ -59,6 +162,79 @@ impl User {
             username: request.username,
             password_hash,
             email: request.email,
+            email_verified_at: None,
+            role: Role::User,
+            banned_at: None,
+            ban_reason: None,
+            is_bot: false,
+            bot_owner_id: None,
+            display_name: None,
+            bio: None,
+            timezone: None,
+            pronouns: None,
+            avatar_updated_at: None,
+            privacy: Privacy::Everyone,
+            is_online: false,
+            last_seen: now,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Buat akun bot milik `owner_id`. Password-nya acak dan tidak pernah diberikan ke
+    /// siapa pun, jadi bot hanya bisa dipakai lewat personal access token.
+    pub fn new_bot(owner_id: Uuid, username: String) -> Result<Self> {
+        let password_hash = hash_password(&generate_opaque_token())?;
+        let now = Utc::now();
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            username,
+            password_hash,
+            email: None,
+            email_verified_at: None,
+            role: Role::User,
+            banned_at: None,
+            ban_reason: None,
+            is_bot: true,
+            bot_owner_id: Some(owner_id),
+            display_name: None,
+            bio: None,
+            timezone: None,
+            pronouns: None,
+            avatar_updated_at: None,
+            privacy: Privacy::Everyone,
+            is_online: false,
+            last_seen: now,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Buat pengguna pengganti untuk satu akun yang dihapus. Pesan akun tersebut dipindahkan
+    /// ke pengganti ini sehingga percakapan dengan akun berbeda tidak tergabung. Username-nya
+    /// unik tetapi tidak lolos validasi registrasi, dan akunnya diblokir.
+    pub fn new_tombstone() -> Result<Self> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+
+        Ok(Self {
+            id,
+            username: format!("[deleted-{}]", id.simple()),
+            password_hash: hash_password(&generate_opaque_token())?,
+            email: None,
+            email_verified_at: None,
+            role: Role::User,
+            banned_at: Some(now),
+            ban_reason: Some("Pengguna pengganti untuk akun yang dihapus".to_string()),
+            is_bot: false,
+            bot_owner_id: None,
+            display_name: Some("[deleted]".to_string()),
+            bio: None,
+            timezone: None,
+            pronouns: None,
+            avatar_updated_at: None,
+            privacy: Privacy::Nobody,
             is_online: false,
             last_seen: now,
             created_at: now,
@@ -71,14 +247,26 @@ impl User {
         let user = sqlx::query_as!(
             User,
             r#"
-            INSERT INTO users (id, username, password_hash, email, is_online, last_seen, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, username, password_hash, email, is_online, last_seen, created_at, updated_at
+            INSERT INTO users (id, username, password_hash, email, email_verified_at, role, banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy, is_online, last_seen, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+            RETURNING id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
             "#,
             self.id,
             self.username,
             self.password_hash,
             self.email,
+            self.email_verified_at,
+            self.role.as_str(),
+            self.banned_at,
+            self.ban_reason,
+            self.is_bot,
+            self.bot_owner_id,
+            self.display_name,
+            self.bio,
+            self.timezone,
+            self.pronouns,
+            self.avatar_updated_at,
+            self.privacy.as_str(),
             self.is_online,
             self.last_seen,
             self.created_at,
@@ -93,14 +281,15 @@ impl User {
         Ok(user)
     }
 
+    /// Cari pengguna berdasarkan username, tanpa membedakan huruf besar/kecil.
     pub async fn find_by_username(_username: &str, _pool: &PgPool) -> Result<Option<Self>> {
         #[cfg(not(any(debug_assertions, ci)))]
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT id, username, password_hash, email, is_online, last_seen, created_at, updated_at
+            SELECT id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
             FROM users
-            WHERE username = $1
+            WHERE LOWER(username) = LOWER($1)
             "#,
             _username
         )
@@ -118,7 +307,7 @@ impl User {
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT id, username, password_hash, email, is_online, last_seen, created_at, updated_at
+            SELECT id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
             FROM users
             WHERE id = $1
             "#,
@@ -133,6 +322,18 @@ impl User {
             username: "test_user".to_string(),
             password_hash: "".to_string(),
             email: None,
+            email_verified_at: None,
+            role: Role::User,
+            banned_at: None,
+            ban_reason: None,
+            is_bot: false,
+            bot_owner_id: None,
+            display_name: None,
+            bio: None,
+            timezone: None,
+            pronouns: None,
+            avatar_updated_at: None,
+            privacy: Privacy::Everyone,
             is_online: true,
             last_seen: Utc::now(),
             created_at: Utc::now(),
@@ -142,16 +343,157 @@ impl User {
         Ok(user)
     }
 
-    pub async fn get_online_users(_pool: &PgPool) -> Result<Vec<UserResponse>> {
+    /// Beberapa pengguna sekaligus dalam satu query. Urutan hasil tidak mengikuti `ids`, dan
+    /// ID yang tidak ditemukan dilewati.
+    pub async fn find_by_ids(ids: &[Uuid], _pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let users = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+            FROM users
+            WHERE id = ANY($1)
+            "#,
+            ids
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let users = {
+            let mut users = Vec::with_capacity(ids.len());
+            for &id in ids {
+                users.extend(Self::find_by_id(id, _pool).await?);
+            }
+            users
+        };
+
+        Ok(users)
+    }
+
+    /// Daftar akun bot milik `_owner_id`, urut dari yang terlama.
+    pub async fn list_bots(_owner_id: Uuid, _pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let bots = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+            FROM users
+            WHERE bot_owner_id = $1
+            ORDER BY created_at
+            "#,
+            _owner_id
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let bots = Vec::new();
+
+        Ok(bots)
+    }
+
+    /// Cari pengguna berdasarkan email, tanpa membedakan huruf besar/kecil.
+    pub async fn find_by_email(_email: &str, _pool: &PgPool) -> Result<Option<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+            FROM users
+            WHERE LOWER(email) = LOWER($1)
+            "#,
+            _email
+        )
+        .fetch_optional(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let user = None;
+
+        Ok(user)
+    }
+
+    /// Ganti password dengan hash baru.
+    pub async fn update_password(&self, new_password: &str, _pool: &PgPool) -> Result<()> {
+        let _password_hash = hash_password(new_password)?;
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET password_hash = $1, updated_at = $2
+            WHERE id = $3
+            "#,
+            _password_hash,
+            Utc::now(),
+            self.id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Ganti email dan reset status verifikasinya.
+    pub async fn update_email(&self, _email: Option<&str>, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET email = $1, email_verified_at = NULL, updated_at = $2
+            WHERE id = $3
+            "#,
+            _email,
+            Utc::now(),
+            self.id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Tandai email terverifikasi, hanya jika email pengguna masih `_email`.
+    pub async fn mark_email_verified(_user_id: Uuid, _email: &str, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE users
+            SET email_verified_at = $1, updated_at = $1
+            WHERE id = $2 AND LOWER(email) = LOWER($3)
+            "#,
+            Utc::now(),
+            _user_id,
+            _email
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 1;
+
+        Ok(rows == 1)
+    }
+
+    /// Pengguna online yang status online-nya boleh dilihat `_viewer_id`.
+    pub async fn get_online_users(_viewer_id: Uuid, _pool: &PgPool) -> Result<Vec<UserResponse>> {
         #[cfg(not(any(debug_assertions, ci)))]
         let users = sqlx::query_as!(
             User,
             r#"
-            SELECT id, username, password_hash, email, is_online, last_seen, created_at, updated_at
+            SELECT id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
             FROM users
             WHERE is_online = true
+              AND (users.id = $1 OR users.privacy = 'everyone' OR (users.privacy = 'contacts' AND EXISTS (
+                  SELECT 1 FROM contacts c
+                  WHERE c.status = 'accepted'
+                    AND ((c.requester_id = users.id AND c.addressee_id = $1) OR (c.requester_id = $1 AND c.addressee_id = users.id))
+              )))
             ORDER BY username
-            "#
+            "#,
+            _viewer_id
         )
         .fetch_all(_pool)
         .await?;
@@ -163,6 +505,18 @@ impl User {
                 username: "test_user1".to_string(),
                 password_hash: "".to_string(),
                 email: None,
+                email_verified_at: None,
+                role: Role::User,
+                banned_at: None,
+                ban_reason: None,
+                is_bot: false,
+                bot_owner_id: None,
+                display_name: None,
+                bio: None,
+                timezone: None,
+                pronouns: None,
+                avatar_updated_at: None,
+                privacy: Privacy::Everyone,
                 is_online: true,
                 last_seen: Utc::now(),
                 created_at: Utc::now(),
@@ -173,6 +527,18 @@ impl User {
                 username: "test_user2".to_string(),
                 password_hash: "".to_string(),
                 email: None,
+                email_verified_at: None,
+                role: Role::User,
+                banned_at: None,
+                ban_reason: None,
+                is_bot: false,
+                bot_owner_id: None,
+                display_name: None,
+                bio: None,
+                timezone: None,
+                pronouns: None,
+                avatar_updated_at: None,
+                privacy: Privacy::Everyone,
                 is_online: true,
                 last_seen: Utc::now(),
                 created_at: Utc::now(),
@@ -205,6 +571,352 @@ impl User {
         Ok(())
     }
 
+    /// Tandai banyak pengguna offline dalam satu query.
+    pub async fn mark_offline(_user_ids: &[Uuid], _pool: &PgPool) -> Result<()> {
+        let _now = Utc::now();
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET is_online = false, last_seen = $1, updated_at = $1
+            WHERE id = ANY($2)
+            "#,
+            _now,
+            _user_ids
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// ID semua pengguna yang tercatat online.
+    pub async fn online_ids(_pool: &PgPool) -> Result<Vec<Uuid>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id
+            FROM users
+            WHERE is_online = true
+            "#
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let ids = Vec::new();
+
+        Ok(ids)
+    }
+
+    /// Tandai offline pengguna di `_user_ids` yang masih tercatat online.
+    pub async fn reset_stale_presence(_user_ids: &[Uuid], _pool: &PgPool) -> Result<Vec<Self>> {
+        let _now = Utc::now();
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let users = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET is_online = false, last_seen = $1, updated_at = $1
+            WHERE is_online = true AND id = ANY($2)
+            RETURNING id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+            "#,
+            _now,
+            _user_ids
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let users = Vec::new();
+
+        Ok(users)
+    }
+
+    /// Versi multi-node: tandai offline pengguna tanpa heartbeat setelah `_cutoff`.
+    pub async fn reset_stale_presence_by_heartbeat(
+        _cutoff: DateTime<Utc>,
+        _pool: &PgPool,
+    ) -> Result<Vec<Self>> {
+        let _now = Utc::now();
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let users = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET is_online = false, last_seen = $1, updated_at = $1
+            WHERE is_online = true
+              AND NOT EXISTS (
+                  SELECT 1 FROM presence_heartbeats h
+                  WHERE h.user_id = users.id AND h.last_heartbeat > $2
+              )
+            RETURNING id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+            "#,
+            _now,
+            _cutoff
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let users = Vec::new();
+
+        Ok(users)
+    }
+
+    /// Tandai online pengguna di `_live_ids` yang masih tercatat offline.
+    pub async fn restore_live_presence(_live_ids: &[Uuid], _pool: &PgPool) -> Result<Vec<Self>> {
+        let _now = Utc::now();
+
+        #[cfg(not(any(debug_assertions, ci)))]
+        let users = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET is_online = true, last_seen = $1, updated_at = $1
+            WHERE is_online = false AND id = ANY($2)
+            RETURNING id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+            "#,
+            _now,
+            _live_ids
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let users = Vec::new();
+
+        Ok(users)
+    }
+
+    /// Daftar semua pengguna untuk admin, urut dari yang terbaru.
+    pub async fn list(_limit: i64, _offset: i64, _pool: &PgPool) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let users = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+            FROM users
+            ORDER BY created_at DESC
+            LIMIT $1 OFFSET $2
+            "#,
+            _limit,
+            _offset
+        )
+        .fetch_all(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let users = Vec::new();
+
+        Ok(users)
+    }
+
+    /// Cari pengguna untuk direktori. Pengguna yang diblokir dan pengguna `[deleted]` tidak
+    /// ditampilkan. Dengan kata kunci, hasil yang username-nya sama persis muncul pertama,
+    /// disusul kecocokan awalan lalu kemiripan trigram. Filter `online` hanya melihat status
+    /// online yang boleh dilihat `_viewer_id`.
+    pub async fn search(
+        _viewer_id: Uuid,
+        _query: &SearchUsersQuery,
+        _pool: &PgPool,
+    ) -> Result<Vec<Self>> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let users = {
+            let (limit, offset) = _query.page();
+            let online = _query.online;
+            let role = _query.role.map(|role| role.as_str());
+
+            match _query.search_term() {
+                Some(term) => {
+                    sqlx::query_as!(
+                        User,
+                        r#"
+                        SELECT id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+                        FROM users
+                        WHERE id <> $1
+                          AND banned_at IS NULL
+                          AND (
+                              LOWER(username) LIKE $3
+                              OR LOWER(display_name) LIKE $3
+                              OR LOWER(username) % $2
+                              OR LOWER(display_name) % $2
+                          )
+                          AND ($4::BOOLEAN IS NULL OR (is_online AND (users.id = $8 OR users.privacy = 'everyone' OR (users.privacy = 'contacts' AND EXISTS (
+                              SELECT 1 FROM contacts c
+                              WHERE c.status = 'accepted'
+                                AND ((c.requester_id = users.id AND c.addressee_id = $8) OR (c.requester_id = $8 AND c.addressee_id = users.id))
+                          )))) = $4)
+                          AND ($5::TEXT IS NULL OR role = $5)
+                        ORDER BY LOWER(username) = $2 DESC,
+                                 (LOWER(username) LIKE $3 OR LOWER(display_name) LIKE $3) DESC,
+                                 GREATEST(similarity(LOWER(username), $2), similarity(LOWER(display_name), $2)) DESC,
+                                 LOWER(username)
+                        LIMIT $6 OFFSET $7
+                        "#,
+                        DELETED_USER_ID,
+                        term,
+                        like_prefix_pattern(&term),
+                        online,
+                        role,
+                        limit,
+                        offset,
+                        _viewer_id
+                    )
+                    .fetch_all(_pool)
+                    .await?
+                }
+                None => {
+                    sqlx::query_as!(
+                        User,
+                        r#"
+                        SELECT id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+                        FROM users
+                        WHERE id <> $1
+                          AND banned_at IS NULL
+                          AND ($2::BOOLEAN IS NULL OR (is_online AND (users.id = $6 OR users.privacy = 'everyone' OR (users.privacy = 'contacts' AND EXISTS (
+                              SELECT 1 FROM contacts c
+                              WHERE c.status = 'accepted'
+                                AND ((c.requester_id = users.id AND c.addressee_id = $6) OR (c.requester_id = $6 AND c.addressee_id = users.id))
+                          )))) = $2)
+                          AND ($3::TEXT IS NULL OR role = $3)
+                        ORDER BY LOWER(username)
+                        LIMIT $4 OFFSET $5
+                        "#,
+                        DELETED_USER_ID,
+                        online,
+                        role,
+                        limit,
+                        offset,
+                        _viewer_id
+                    )
+                    .fetch_all(_pool)
+                    .await?
+                }
+            }
+        };
+
+        #[cfg(any(debug_assertions, ci))]
+        let users = Vec::new();
+
+        Ok(users)
+    }
+
+    /// Jadikan `username` admin jika belum ada admin sama sekali.
+    pub async fn promote_bootstrap_admin(_username: &str, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE users
+            SET role = 'admin', updated_at = $1
+            WHERE LOWER(username) = LOWER($2)
+              AND NOT EXISTS (SELECT 1 FROM users WHERE role = 'admin')
+            "#,
+            Utc::now(),
+            _username
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 0;
+
+        Ok(rows == 1)
+    }
+
+    pub async fn set_role(_user_id: Uuid, _role: Role, _pool: &PgPool) -> Result<bool> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let rows = sqlx::query!(
+            r#"
+            UPDATE users
+            SET role = $1, updated_at = $2
+            WHERE id = $3
+            "#,
+            _role.as_str(),
+            Utc::now(),
+            _user_id
+        )
+        .execute(_pool)
+        .await?
+        .rows_affected();
+
+        #[cfg(any(debug_assertions, ci))]
+        let rows = 1;
+
+        Ok(rows == 1)
+    }
+
+    /// Blokir pengguna dengan alasan opsional.
+    pub async fn ban(&self, _reason: Option<&str>, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET banned_at = $1, ban_reason = $2, updated_at = $1
+            WHERE id = $3
+            "#,
+            Utc::now(),
+            _reason,
+            self.id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn unban(&self, _pool: &PgPool) -> Result<()> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET banned_at = NULL, ban_reason = NULL, updated_at = $1
+            WHERE id = $2
+            "#,
+            Utc::now(),
+            self.id
+        )
+        .execute(_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Simpan field profil (`display_name`, `bio`, `timezone`, `pronouns`, `privacy`) milik `self`.
+    pub async fn update_profile(self, _pool: &PgPool) -> Result<Self> {
+        #[cfg(not(any(debug_assertions, ci)))]
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET display_name = $1, bio = $2, timezone = $3, pronouns = $4, privacy = $5, updated_at = $6
+            WHERE id = $7
+            RETURNING id, username, password_hash, email, email_verified_at, role as "role: Role", banned_at, ban_reason, is_bot, bot_owner_id, display_name, bio, timezone, pronouns, avatar_updated_at, privacy as "privacy: Privacy", is_online, last_seen, created_at, updated_at
+            "#,
+            self.display_name,
+            self.bio,
+            self.timezone,
+            self.pronouns,
+            self.privacy.as_str(),
+            Utc::now(),
+            self.id
+        )
+        .fetch_one(_pool)
+        .await?;
+
+        #[cfg(any(debug_assertions, ci))]
+        let user = Self {
+            updated_at: Utc::now(),
+            ..self
+        };
+
+        Ok(user)
+    }
+
     pub fn verify_password(&self, _password: &str) -> Result<bool> {
         #[cfg(any(debug_assertions, ci))]
         return Ok(true);
@@ -213,15 +925,72 @@ impl User {
         return Ok(verify(_password, &self.password_hash)?);
     }
 
+    pub fn is_email_verified(&self) -> bool {
+        self.email.is_some() && self.email_verified_at.is_some()
+    }
+
+    /// URL avatar dengan versi, supaya klien bisa menyimpannya di cache selamanya.
+    pub fn avatar_url(&self) -> Option<String> {
+        self.avatar_updated_at
+            .map(|updated_at| avatar_url(self.id, updated_at))
+    }
+
     pub fn into_response(self) -> UserResponse {
         UserResponse {
+            email_verified: self.is_email_verified(),
+            avatar_url: self.avatar_url(),
             id: self.id,
             username: self.username,
             email: self.email,
+            role: self.role,
+            is_bot: self.is_bot,
+            display_name: self.display_name,
+            bio: self.bio,
+            timezone: self.timezone,
+            pronouns: self.pronouns,
+            privacy: self.privacy,
             is_online: self.is_online,
             last_seen: self.last_seen,
         }
     }
+
+    /// Profil yang boleh dilihat pengguna lain, tanpa email.
+    pub fn into_profile(self) -> ProfileResponse {
+        ProfileResponse {
+            avatar_url: self.avatar_url(),
+            id: self.id,
+            username: self.username,
+            display_name: self.display_name,
+            bio: self.bio,
+            timezone: self.timezone,
+            pronouns: self.pronouns,
+            role: self.role,
+            is_bot: self.is_bot,
+            is_online: self.is_online,
+            last_seen: Some(self.last_seen),
+        }
+    }
+
+    /// Profil publik dengan status online yang disaring pengaturan privasi. `is_contact`
+    /// menyatakan apakah `viewer_id` kontak pengguna ini.
+    pub fn into_profile_for(self, viewer_id: Uuid, is_contact: bool) -> ProfileResponse {
+        let visible = self.shows_presence_to(viewer_id, is_contact);
+        let profile = self.into_profile();
+        if visible {
+            profile
+        } else {
+            profile.hide_presence()
+        }
+    }
+
+    pub fn shows_presence_to(&self, viewer_id: Uuid, is_contact: bool) -> bool {
+        viewer_id == self.id || self.privacy.allows(is_contact)
+    }
+}
+
+/// Jalankan verifikasi bcrypt yang pasti gagal, untuk login dengan username tidak dikenal.
+pub fn verify_dummy_password(password: &str) -> bool {
+    bcrypt::verify(password, &DUMMY_PASSWORD_HASH).unwrap_or(false)
 }
 
 fn hash_password(password: &str) -> Result<String> {
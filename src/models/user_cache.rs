@@ -0,0 +1,120 @@
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use lru::LruCache;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sqlx::postgres::PgPool;
+use uuid::Uuid;
+
+use crate::config::user_cache::{get_user_cache_capacity, get_user_cache_ttl};
+use crate::models::role::Role;
+use crate::models::user::User;
+
+static USER_CACHE: Lazy<UserCache> = Lazy::new(|| {
+    UserCache::new(
+        get_user_cache_capacity(),
+        Duration::from_secs(get_user_cache_ttl()),
+    )
+});
+
+/// Data tampilan pengguna yang jarang berubah, untuk menampilkan pengirim dan penerima pesan
+/// tanpa query. Status online dan pengaturan privasi tidak ikut disimpan: yang pertama terlalu
+/// sering berubah, yang kedua harus langsung berlaku di semua node.
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedUser {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub role: Role,
+    pub is_bot: bool,
+}
+
+impl From<&User> for CachedUser {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username.clone(),
+            display_name: user.display_name.clone(),
+            avatar_url: user.avatar_url(),
+            role: user.role,
+            is_bot: user.is_bot,
+        }
+    }
+}
+
+/// Cache LRU berukuran tetap dengan umur entri maksimal.
+pub struct UserCache {
+    entries: Mutex<LruCache<Uuid, (Instant, CachedUser)>>,
+    ttl: Duration,
+}
+
+impl UserCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+
+    /// Entri untuk `id`, atau `None` jika tidak ada atau sudah kedaluwarsa.
+    pub fn get(&self, id: Uuid) -> Option<CachedUser> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(&id) {
+            Some((cached_at, user)) if cached_at.elapsed() < self.ttl => Some(user.clone()),
+            Some(_) => {
+                entries.pop(&id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, user: CachedUser) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.put(user.id, (Instant::now(), user));
+    }
+
+    pub fn invalidate(&self, id: Uuid) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.pop(&id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Pengguna dari cache bersama, atau dari database jika belum ada. Pengguna yang tidak
+/// ditemukan tidak disimpan.
+pub async fn find_cached(id: Uuid, db: &PgPool) -> Result<Option<CachedUser>> {
+    if let Some(user) = USER_CACHE.get(id) {
+        return Ok(Some(user));
+    }
+
+    let user = User::find_by_id(id, db).await?.map(|user| {
+        let cached = CachedUser::from(&user);
+        USER_CACHE.insert(cached.clone());
+        cached
+    });
+
+    Ok(user)
+}
+
+/// Simpan data terbaru setelah pengguna diubah di node ini.
+pub fn store(user: &User) {
+    USER_CACHE.insert(CachedUser::from(user));
+}
+
+/// Buang pengguna dari cache, mis. setelah peran diubah atau akunnya dihapus.
+pub fn invalidate(id: Uuid) {
+    USER_CACHE.invalidate(id);
+}
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use once_cell::sync::Lazy;
+use reqwest::Url;
+use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+
+use crate::config::oidc::{OidcProviderConfig, get_oidc_providers};
+use crate::utils::token::generate_opaque_token;
+
+const METADATA_TTL: Duration = Duration::from_secs(3600);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Penyedia OIDC dari environment, dimuat sekali.
+static PROVIDERS: Lazy<HashMap<String, Arc<OidcProvider>>> = Lazy::new(|| {
+    get_oidc_providers()
+        .into_iter()
+        .map(|config| (config.name.clone(), Arc::new(OidcProvider::new(config))))
+        .collect()
+});
+
+pub fn provider(name: &str) -> Option<Arc<OidcProvider>> {
+    PROVIDERS.get(&name.to_ascii_lowercase()).cloned()
+}
+
+pub fn provider_names() -> Vec<String> {
+    let mut names: Vec<String> = PROVIDERS.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Bagian dokumen discovery (`/.well-known/openid-configuration`) yang dipakai.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+/// Klaim ID token yang dipakai untuk menautkan identitas.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    /// Beberapa penyedia mengirim `"true"` sebagai string.
+    #[serde(default, deserialize_with = "bool_or_string")]
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Parameter login yang harus disimpan server sampai callback diterima.
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+    pub url: String,
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenEndpointResponse {
+    id_token: String,
+}
+
+#[derive(Clone)]
+struct CachedMetadata {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+/// Klien authorization code + PKCE untuk satu penyedia.
+pub struct OidcProvider {
+    config: OidcProviderConfig,
+    http: reqwest::Client,
+    cache: RwLock<Option<CachedMetadata>>,
+}
+
+impl OidcProvider {
+    pub fn new(config: OidcProviderConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            config,
+            http,
+            cache: RwLock::new(None),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.config.issuer
+    }
+
+    /// Buat URL otorisasi dengan `state`, `nonce`, dan tantangan PKCE (S256) baru.
+    pub async fn authorization_request(&self) -> Result<AuthorizationRequest> {
+        let metadata = self.metadata(false).await?.metadata;
+        let state = generate_opaque_token();
+        let nonce = generate_opaque_token();
+        // 64 karakter hex, dalam batas 43-128 karakter RFC 7636.
+        let code_verifier = generate_opaque_token();
+
+        let mut url = Url::parse(&metadata.authorization_endpoint)
+            .context("authorization_endpoint tidak valid")?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_uri)
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &pkce_challenge(&code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(AuthorizationRequest {
+            url: url.into(),
+            state,
+            nonce,
+            code_verifier,
+        })
+    }
+
+    /// Tukar authorization code dengan ID token dan validasi klaimnya.
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims> {
+        let metadata = self.metadata(false).await?.metadata;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("Token endpoint membalas {}: {}", status, body);
+        }
+
+        let tokens: TokenEndpointResponse = response.json().await?;
+        self.validate_id_token(&tokens.id_token, nonce).await
+    }
+
+    async fn validate_id_token(&self, id_token: &str, nonce: &str) -> Result<IdTokenClaims> {
+        let header = decode_header(id_token)?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            bail!("Algoritma ID token {:?} tidak diterima", header.alg);
+        }
+
+        let issuer = self.metadata(false).await?.metadata.issuer;
+        let key = match self.decoding_key(&header.kid, false).await? {
+            Some(key) => key,
+            // Kunci tidak dikenal: penyedia mungkin baru merotasi kuncinya.
+            None => self
+                .decoding_key(&header.kid, true)
+                .await?
+                .ok_or_else(|| anyhow!("Kunci ID token tidak ditemukan di JWKS"))?,
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)?.claims;
+        if claims.nonce.as_deref() != Some(nonce) {
+            bail!("Nonce ID token tidak cocok");
+        }
+
+        Ok(claims)
+    }
+
+    async fn decoding_key(
+        &self,
+        kid: &Option<String>,
+        refresh: bool,
+    ) -> Result<Option<DecodingKey>> {
+        let jwks = self.metadata(refresh).await?.jwks;
+
+        let jwk = match kid {
+            Some(kid) => jwks.find(kid),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        };
+
+        jwk.map(DecodingKey::from_jwk)
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Dokumen discovery dan JWKS, di-cache selama satu jam.
+    async fn metadata(&self, refresh: bool) -> Result<CachedMetadata> {
+        if !refresh
+            && let Some(cached) = self.cache.read().await.as_ref()
+            && cached.fetched_at.elapsed() < METADATA_TTL
+        {
+            return Ok(cached.clone());
+        }
+
+        let discovery_url = format!("{}/.well-known/openid-configuration", self.config.issuer);
+        let metadata: ProviderMetadata = self
+            .http
+            .get(&discovery_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("Dokumen discovery tidak valid: {}", discovery_url))?;
+        if metadata.issuer.trim_end_matches('/') != self.config.issuer {
+            bail!(
+                "Issuer discovery {} tidak sama dengan {}",
+                metadata.issuer,
+                self.config.issuer
+            );
+        }
+
+        let jwks: JwkSet = self
+            .http
+            .get(&metadata.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("JWKS penyedia tidak valid")?;
+
+        let cached = CachedMetadata {
+            metadata,
+            jwks,
+            fetched_at: Instant::now(),
+        };
+        *self.cache.write().await = Some(cached.clone());
+
+        Ok(cached)
+    }
+}
+
+/// Tantangan PKCE metode S256: `BASE64URL(SHA256(code_verifier))`.
+pub fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+fn bool_or_string<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrString {
+        Bool(bool),
+        String(String),
+    }
+
+    Ok(match BoolOrString::deserialize(deserializer)? {
+        BoolOrString::Bool(value) => value,
+        BoolOrString::String(value) => value.eq_ignore_ascii_case("true"),
+    })
+}
@@ -2,8 +2,9 @@ use std::sync::Arc;
 
 use axum::{
     Extension, Router,
+    extract::DefaultBodyLimit,
     middleware::from_fn_with_state,
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
 use http::HeaderName;
 use http::HeaderValue;
@@ -11,11 +12,35 @@ use http::Method;
 use tower_http::cors::CorsLayer;
 
 use crate::{
+    config::profile::get_avatar_max_upload_bytes,
     handlers::{
-        auth::{login, register},
-        message::{get_conversation, get_public_messages, send_message},
-        user::{get_current_user, get_online_users, update_online_status},
-        websocket::ws_handler,
+        account::{cancel_account_deletion, delete_account, export_data},
+        admin::{ban_user, delete_message, list_users, unban_user, update_role},
+        api_token::{create_token, list_tokens, revoke_token},
+        auth::{
+            forgot_password, jwks, login, logout, logout_all, refresh, register, reset_password,
+            verify_email,
+        },
+        block::{block_user, list_blocks, list_mutes, mute_user, unblock_user, unmute_user},
+        bot::{create_bot, list_bots},
+        contact::{
+            accept_contact_request, decline_contact_request, list_contact_requests, list_contacts,
+            remove_contact, send_contact_request,
+        },
+        conversation::{list_conversations, mark_direct_read, mark_public_read},
+        message::{get_conversation, get_public_messages, search_messages, send_message},
+        mfa::{confirm_totp, disable_totp, enroll_totp, verify_mfa},
+        oidc::{authorize, callback, list_providers},
+        retention::{
+            create_legal_hold, delete_retention_policy, get_retention_report,
+            get_retention_settings, list_legal_holds, release_legal_hold, set_retention_policy,
+        },
+        user::{
+            change_email, delete_avatar, get_avatar, get_current_user, get_online_users,
+            get_user_profile, list_sessions, resend_email_verification, revoke_session,
+            search_users, update_online_status, update_profile, upload_avatar,
+        },
+        websocket::{get_ws_metrics, ws_handler},
     },
     middleware::auth::{AppState, auth_middleware},
 };
@@ -27,6 +52,7 @@ pub fn create_routes(state: Arc<AppState>) -> Router {
             Method::GET,
             Method::POST,
             Method::PUT,
+            Method::PATCH,
             Method::DELETE,
             Method::OPTIONS,
         ])
@@ -42,16 +68,100 @@ pub fn create_routes(state: Arc<AppState>) -> Router {
     let public_routes = Router::new()
         .route("/auth/register", post(register))
         .route("/auth/login", post(login))
+        .route("/auth/refresh", post(refresh))
+        .route("/auth/password/forgot", post(forgot_password))
+        .route("/auth/password/reset", post(reset_password))
+        .route("/auth/mfa/verify", post(verify_mfa))
+        .route("/auth/oidc", get(list_providers))
+        .route("/auth/oidc/{provider}/authorize", get(authorize))
+        .route("/auth/oidc/{provider}/callback", post(callback))
+        .route("/auth/email/verify", post(verify_email))
+        .route("/.well-known/jwks.json", get(jwks))
+        .route("/users/{user_id}/avatar", get(get_avatar))
         .route("/ws", get(ws_handler))
         .with_state(state.clone());
 
     let protected_routes = Router::new()
-        .route("/users/me", get(get_current_user))
+        .route("/auth/logout", post(logout))
+        .route("/auth/logout-all", post(logout_all))
+        .route(
+            "/users/me",
+            get(get_current_user)
+                .patch(update_profile)
+                .delete(delete_account),
+        )
+        .route(
+            "/users/me/avatar",
+            put(upload_avatar)
+                .layer(DefaultBodyLimit::max(get_avatar_max_upload_bytes()))
+                .delete(delete_avatar),
+        )
+        .route("/users/me/deletion", delete(cancel_account_deletion))
+        .route("/users/me/export", get(export_data))
+        .route("/users/me/email", put(change_email))
+        .route(
+            "/users/me/email/verification",
+            post(resend_email_verification),
+        )
+        .route("/users/me/mfa/totp", post(enroll_totp))
+        .route("/users/me/mfa/totp/confirm", post(confirm_totp))
+        .route("/users/me/mfa/totp/disable", post(disable_totp))
+        .route("/users/me/sessions", get(list_sessions))
+        .route("/users/me/sessions/{session_id}", delete(revoke_session))
+        .route("/users/me/tokens", get(list_tokens).post(create_token))
+        .route("/users/me/tokens/{token_id}", delete(revoke_token))
+        .route("/users/me/bots", get(list_bots).post(create_bot))
+        .route("/users/me/blocks", get(list_blocks))
+        .route("/users/me/mutes", get(list_mutes))
+        .route("/users/me/contacts", get(list_contacts))
+        .route("/users/me/contacts/requests", get(list_contact_requests))
+        .route(
+            "/users/me/contacts/requests/{user_id}/accept",
+            post(accept_contact_request),
+        )
+        .route(
+            "/users/me/contacts/requests/{user_id}/decline",
+            post(decline_contact_request),
+        )
+        .route("/users", get(search_users))
         .route("/users/online", get(get_online_users))
         .route("/users/status", post(update_online_status))
+        .route("/users/{user_id}", get(get_user_profile))
+        .route(
+            "/users/{user_id}/block",
+            put(block_user).delete(unblock_user),
+        )
+        .route("/users/{user_id}/mute", put(mute_user).delete(unmute_user))
+        .route(
+            "/users/{user_id}/contact",
+            put(send_contact_request).delete(remove_contact),
+        )
+        .route("/conversations", get(list_conversations))
+        .route("/conversations/public/read", post(mark_public_read))
+        .route("/conversations/{partner_id}/read", post(mark_direct_read))
         .route("/messages", post(send_message))
         .route("/messages/public", get(get_public_messages))
+        .route("/messages/search", get(search_messages))
         .route("/messages/{receiver_id}", get(get_conversation))
+        .route("/ws/metrics", get(get_ws_metrics))
+        .route("/admin/users", get(list_users))
+        .route("/admin/users/{user_id}/role", put(update_role))
+        .route(
+            "/admin/users/{user_id}/ban",
+            post(ban_user).delete(unban_user),
+        )
+        .route("/admin/messages/{message_id}", delete(delete_message))
+        .route("/admin/retention", get(get_retention_settings))
+        .route("/admin/retention/report", get(get_retention_report))
+        .route(
+            "/admin/retention/policies/{conversation_key}",
+            put(set_retention_policy).delete(delete_retention_policy),
+        )
+        .route(
+            "/admin/legal-holds",
+            get(list_legal_holds).post(create_legal_hold),
+        )
+        .route("/admin/legal-holds/{hold_id}", delete(release_legal_hold))
         .route_layer(from_fn_with_state(state.clone(), auth_middleware))
         .with_state(state.clone());
 
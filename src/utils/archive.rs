@@ -0,0 +1,34 @@
+use std::io::{Cursor, Write};
+
+use anyhow::Result;
+use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+/// Satu file di dalam arsip ekspor.
+pub struct ArchiveEntry {
+    /// Path relatif di dalam arsip, mis. `messages.json` atau `attachments/<id>.png`.
+    pub path: String,
+    pub contents: Vec<u8>,
+}
+
+impl ArchiveEntry {
+    /// File JSON dengan format yang mudah dibaca manusia.
+    pub fn json(path: &str, value: &impl serde::Serialize) -> Result<Self> {
+        Ok(Self {
+            path: path.to_string(),
+            contents: serde_json::to_vec_pretty(value)?,
+        })
+    }
+}
+
+/// Kemas file-file menjadi arsip ZIP di memori.
+pub fn build_zip(entries: &[ArchiveEntry]) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for entry in entries {
+        zip.start_file(entry.path.as_str(), options)?;
+        zip.write_all(&entry.contents)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
@@ -0,0 +1,28 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use image::{ImageFormat, ImageReader, Limits, imageops::FilterType};
+
+/// Dimensi maksimum gambar yang mau didekode, supaya file kecil berisi gambar raksasa
+/// tidak menghabiskan memori.
+const MAX_SOURCE_DIMENSION: u32 = 8192;
+
+/// Dekode gambar (PNG, JPEG, WebP, atau frame pertama GIF), potong ke tengah menjadi
+/// persegi `size` x `size`, lalu kodekan ulang sebagai PNG. Metadata seperti EXIF ikut
+/// terbuang.
+pub fn resize_avatar(bytes: &[u8], size: u32) -> Result<Vec<u8>> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    reader.limits(limits);
+    let image = reader.decode()?;
+
+    let avatar = image.resize_to_fill(size, size, FilterType::Lanczos3);
+
+    let mut png = Vec::new();
+    avatar.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+
+    Ok(png)
+}
@@ -0,0 +1,29 @@
+/// Panjang maksimum alamat email (RFC 5321).
+const MAX_EMAIL_LENGTH: usize = 254;
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+
+/// Rapikan dan periksa format email. Mengembalikan `None` jika formatnya tidak valid.
+///
+/// Pemeriksaan sengaja sederhana: kepemilikan alamat dibuktikan lewat email verifikasi.
+pub fn normalize_email(email: &str) -> Option<String> {
+    let email = email.trim();
+    if email.len() > MAX_EMAIL_LENGTH || email.chars().any(|c| c.is_whitespace()) {
+        return None;
+    }
+
+    let (local, domain) = email.rsplit_once('@')?;
+    if local.is_empty() || local.len() > MAX_LOCAL_PART_LENGTH || local.contains('@') {
+        return None;
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid_domain = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        });
+
+    valid_domain.then(|| email.to_string())
+}
@@ -1,3 +1,10 @@
+pub mod archive;
+pub mod avatar;
+pub mod email;
+pub mod token;
+pub mod totp;
+pub mod validation;
+
 pub fn setup_tracing() {
     use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -6,3 +13,28 @@ pub fn setup_tracing() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 }
+
+/// Selesai saat proses menerima SIGINT (Ctrl+C) atau SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Gagal memasang handler Ctrl+C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Gagal memasang handler SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -0,0 +1,14 @@
+use rand::{RngCore, rngs::OsRng};
+use sha2::{Digest, Sha256};
+
+/// Buat token acak (256 bit, hex) untuk refresh token dan token sekali pakai lainnya.
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Hash SHA-256 (hex) dari token. Hanya hash ini yang disimpan di database.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
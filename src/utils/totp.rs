@@ -0,0 +1,99 @@
+//! TOTP (RFC 6238) dengan parameter standar aplikasi authenticator:
+//! HMAC-SHA1, 6 digit, periode 30 detik.
+
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::{RngCore, rngs::OsRng};
+use sha1::Sha1;
+
+pub const TOTP_DIGITS: u32 = 6;
+pub const TOTP_PERIOD: i64 = 30;
+/// Jumlah langkah sebelum/sesudah langkah saat ini yang masih diterima (toleransi jam).
+pub const TOTP_SKEW: i64 = 1;
+
+const SECRET_LENGTH: usize = 20;
+
+/// Buat secret TOTP acak (160 bit) dalam format base32 tanpa padding.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_LENGTH];
+    OsRng.fill_bytes(&mut bytes);
+    BASE32_NOPAD.encode(&bytes)
+}
+
+/// URI `otpauth://` untuk dipindai aplikasi authenticator (biasanya sebagai QR code).
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        encode_component(issuer),
+        encode_component(account),
+        secret,
+        encode_component(issuer),
+        TOTP_DIGITS,
+        TOTP_PERIOD
+    )
+}
+
+/// Langkah waktu TOTP untuk timestamp Unix `unix_time`.
+pub fn time_step(unix_time: i64) -> i64 {
+    unix_time.div_euclid(TOTP_PERIOD)
+}
+
+/// Kode TOTP untuk satu langkah waktu. `None` jika secret bukan base32 yang valid.
+pub fn code_at(secret: &str, step: i64) -> Option<String> {
+    let key = BASE32_NOPAD
+        .decode(secret.trim_end_matches('=').as_bytes())
+        .ok()?;
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).ok()?;
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226 bagian 5.3).
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+
+    Some(format!(
+        "{:0width$}",
+        binary % 10u32.pow(TOTP_DIGITS),
+        width = TOTP_DIGITS as usize
+    ))
+}
+
+/// Cari langkah waktu yang cocok dengan `code` di sekitar `unix_time`.
+///
+/// Langkah yang dikembalikan disimpan pemanggil agar kode yang sama tidak bisa dipakai ulang.
+pub fn matching_step(secret: &str, code: &str, unix_time: i64) -> Option<i64> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != TOTP_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let current = time_step(unix_time);
+    (current - TOTP_SKEW..=current + TOTP_SKEW).find(|step| {
+        code_at(secret, *step).is_some_and(|expected| constant_time_eq(&expected, &code))
+    })
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
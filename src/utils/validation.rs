@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+use std::fs;
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use tracing::{info, warn};
+
+use crate::config::password::{MAX_PASSWORD_BYTES, PasswordPolicy};
+use crate::models::errors::FieldError;
+
+pub const MIN_USERNAME_LENGTH: usize = 3;
+pub const MAX_USERNAME_LENGTH: usize = 32;
+
+/// Nama yang bisa disangka akun resmi atau bentrok dengan path API.
+const RESERVED_USERNAMES: &[&str] = &[
+    "admin",
+    "administrator",
+    "anonymous",
+    "api",
+    "everyone",
+    "here",
+    "me",
+    "moderator",
+    "null",
+    "root",
+    "support",
+    "system",
+    "undefined",
+];
+
+/// Daftar password bocor dari `PASSWORD_BREACHED_LIST_FILE`, dimuat sekali.
+static BREACHED_PASSWORDS: Lazy<BreachedPasswords> = Lazy::new(|| {
+    let Some(path) = PasswordPolicy::from_env().breached_list_file else {
+        return BreachedPasswords::default();
+    };
+
+    match BreachedPasswords::load(&path) {
+        Ok(list) => {
+            info!("Loaded {} breached password(s) from {}", list.len(), path);
+            list
+        }
+        Err(e) => {
+            warn!("Failed to load breached password list {}: {}", path, e);
+            BreachedPasswords::default()
+        }
+    }
+});
+
+/// Himpunan password yang pernah bocor, dibandingkan tanpa membedakan huruf besar/kecil.
+#[derive(Debug, Default)]
+pub struct BreachedPasswords(HashSet<String>);
+
+impl BreachedPasswords {
+    /// Muat file teks berisi satu password per baris. Baris kosong dan baris
+    /// berawalan `#` diabaikan.
+    pub fn load(path: &str) -> Result<Self> {
+        Ok(Self::from_lines(&fs::read_to_string(path)?))
+    }
+
+    pub fn from_lines(content: &str) -> Self {
+        Self(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_lowercase)
+                .collect(),
+        )
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        self.0.contains(&password.to_lowercase())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+pub fn breached_passwords() -> &'static BreachedPasswords {
+    &BREACHED_PASSWORDS
+}
+
+/// Periksa username: 3-32 karakter huruf, angka, `_`, `-`, atau `.`, diawali huruf
+/// atau angka, dan bukan nama yang dicadangkan.
+pub fn validate_username(username: &str) -> Result<(), FieldError> {
+    let error = |code, message: &str| {
+        Err(FieldError {
+            field: "username",
+            code,
+            message: message.to_string(),
+        })
+    };
+
+    if username.is_empty() {
+        return error("required", "Username wajib diisi");
+    }
+
+    let length = username.chars().count();
+    if length < MIN_USERNAME_LENGTH {
+        return error("too_short", "Username minimal 3 karakter");
+    }
+    if length > MAX_USERNAME_LENGTH {
+        return error("too_long", "Username maksimal 32 karakter");
+    }
+
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        || !username.starts_with(|c: char| c.is_ascii_alphanumeric())
+    {
+        return error(
+            "invalid_characters",
+            "Username hanya boleh berisi huruf, angka, _, - dan ., diawali huruf atau angka",
+        );
+    }
+
+    if RESERVED_USERNAMES.contains(&username.to_ascii_lowercase().as_str()) {
+        return error("reserved", "Username tidak tersedia");
+    }
+
+    Ok(())
+}
+
+/// Periksa password terhadap kebijakan: panjang, tidak sama dengan username,
+/// dan tidak ada di daftar password bocor.
+pub fn validate_password(
+    password: &str,
+    username: &str,
+    policy: &PasswordPolicy,
+    breached: &BreachedPasswords,
+) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let mut add = |code, message: String| {
+        errors.push(FieldError {
+            field: "password",
+            code,
+            message,
+        })
+    };
+
+    if password.is_empty() {
+        add("required", "Password wajib diisi".to_string());
+        return errors;
+    }
+
+    if password.chars().count() < policy.min_length {
+        add(
+            "too_short",
+            format!("Password minimal {} karakter", policy.min_length),
+        );
+    }
+    if password.len() > MAX_PASSWORD_BYTES {
+        add(
+            "too_long",
+            format!("Password maksimal {} byte", MAX_PASSWORD_BYTES),
+        );
+    }
+
+    if !username.is_empty() && password.eq_ignore_ascii_case(username) {
+        add(
+            "matches_username",
+            "Password tidak boleh sama dengan username".to_string(),
+        );
+    }
+
+    if breached.contains(password) {
+        add(
+            "breached",
+            "Password ini pernah bocor dan mudah ditebak, gunakan password lain".to_string(),
+        );
+    }
+
+    errors
+}
+
+/// Wilayah teratas nama zona waktu IANA, mis. `Asia` pada `Asia/Jakarta`.
+const TIMEZONE_AREAS: &[&str] = &[
+    "Africa",
+    "America",
+    "Antarctica",
+    "Arctic",
+    "Asia",
+    "Atlantic",
+    "Australia",
+    "Europe",
+    "Indian",
+    "Pacific",
+    "Etc",
+];
+
+/// Periksa bentuk nama zona waktu IANA: `UTC`, atau `Wilayah/Kota` (boleh lebih dari dua
+/// bagian, mis. `America/Argentina/Buenos_Aires`). Keberadaan zonanya tidak diperiksa
+/// karena server tidak membawa database zona waktu; klien yang menerjemahkannya.
+pub fn is_valid_timezone(timezone: &str) -> bool {
+    if timezone == "UTC" {
+        return true;
+    }
+
+    let mut parts = timezone.split('/');
+    let area_ok = parts
+        .next()
+        .is_some_and(|area| TIMEZONE_AREAS.contains(&area));
+    let rest: Vec<&str> = parts.collect();
+
+    area_ok
+        && timezone.len() <= 64
+        && !rest.is_empty()
+        && rest.iter().all(|part| {
+            part.starts_with(|c: char| c.is_ascii_alphanumeric())
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))
+        })
+}
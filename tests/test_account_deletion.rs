@@ -0,0 +1,217 @@
+mod common;
+
+use std::io::Read;
+
+use anyhow::Result;
+use axum::body::to_bytes;
+use axum::{Extension, Json, http::StatusCode};
+use backend::config::account::{get_account_deletion_grace_period, get_reauth_max_age};
+use backend::config::jwt::{generate_session_token, validate_token};
+use backend::handlers::account::{cancel_account_deletion, delete_account, export_data};
+use backend::jobs::accounts::{self, export_entries, write_export};
+use backend::middleware::auth::{AuthUser, Credential};
+use backend::middleware::client::ClientInfo;
+use backend::models::account_deletion::{
+    AccountDeletion, DELETED_USER_ID, DeleteAccountRequest, MessageDeletionMode,
+};
+use backend::models::contact::Privacy;
+use backend::models::data_export::{DataExport, ExportStatus};
+use backend::models::errors::AppError;
+use backend::models::role::Role;
+use backend::models::session::Session;
+use backend::models::user::User;
+use backend::utils::archive::build_zip;
+use backend::utils::validation::validate_username;
+use chrono::{Duration, Utc};
+use common::{test_state, test_user};
+use uuid::Uuid;
+use zip::ZipArchive;
+
+#[tokio::test]
+async fn test_account_deletion_is_scheduled_after_grace_period() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("leaving_user").await?;
+
+    // Tanpa pilihan, pesan dianonimkan
+    let request: DeleteAccountRequest = serde_json::from_str(r#"{"password":"password123"}"#)?;
+    assert_eq!(request.messages, MessageDeletionMode::Anonymize);
+
+    let session = || -> Result<Credential> {
+        let token = generate_session_token(user.id, Uuid::new_v4(), Role::User)?;
+        Ok(Credential::Session(validate_token(&token)?))
+    };
+
+    let (status, Json(scheduled)) = delete_account(
+        Extension(state.clone()),
+        Extension(session()?),
+        AuthUser(user.clone()),
+        Json(DeleteAccountRequest {
+            password: Some("password123".to_string()),
+            messages: MessageDeletionMode::Delete,
+            ..Default::default()
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(scheduled.messages, MessageDeletionMode::Delete);
+    assert_eq!(
+        scheduled.scheduled_for - scheduled.requested_at,
+        Duration::seconds(get_account_deletion_grace_period())
+    );
+
+    // Bot dihapus bersama pemiliknya, bukan sendiri-sendiri
+    let bot = User::new_bot(user.id, "leaving_bot".to_string())?;
+    let result = delete_account(
+        Extension(state.clone()),
+        Extension(session()?),
+        AuthUser(bot),
+        Json(DeleteAccountRequest::default()),
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    // Tanpa password, kode 2FA hanya diterima jika 2FA aktif
+    let result = delete_account(
+        Extension(state.clone()),
+        Extension(session()?),
+        AuthUser(user.clone()),
+        Json(DeleteAccountRequest {
+            code: Some("123456".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::Validation(_))));
+
+    // Tanpa password dan kode, sesi harus baru saja login
+    let result = delete_account(
+        Extension(state.clone()),
+        Extension(session()?),
+        AuthUser(user.clone()),
+        Json(DeleteAccountRequest::default()),
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::Auth(_))));
+
+    let cancelled = cancel_account_deletion(Extension(state.clone()), AuthUser(user.clone())).await;
+    assert!(matches!(cancelled, Err(AppError::NotFound(_))));
+
+    let deletion = AccountDeletion::new(user.id, MessageDeletionMode::Anonymize, 0);
+    assert_eq!(
+        accounts::delete_account(&deletion, &state.db).await?,
+        Some(1)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_each_deleted_account_gets_its_own_tombstone() -> Result<()> {
+    let first = User::new_tombstone()?;
+    let second = User::new_tombstone()?;
+
+    // Pengganti berbeda untuk setiap akun, jadi percakapannya tidak tergabung
+    assert_ne!(first.id, second.id);
+    assert_ne!(first.username, second.username);
+    assert_ne!(first.id, DELETED_USER_ID);
+
+    // Tidak bisa didaftarkan, tidak bisa login, dan tidak muncul di direktori
+    assert!(validate_username(&first.username).is_err());
+    assert!(first.banned_at.is_some());
+    assert_eq!(first.privacy, Privacy::Nobody);
+    assert_eq!(first.display_name.as_deref(), Some("[deleted]"));
+
+    Ok(())
+}
+
+#[test]
+fn test_recent_login_replaces_password() {
+    let client = ClientInfo {
+        ip_address: None,
+        user_agent: None,
+    };
+    let session = Session::new(Uuid::new_v4(), None, client);
+    let now = Utc::now();
+    let max_age = get_reauth_max_age();
+
+    assert!(session.is_recent_login(max_age, now));
+    assert!(!session.is_recent_login(max_age, now + Duration::seconds(max_age + 1)));
+
+    // Sesi yang sudah dicabut tidak pernah dihitung
+    let revoked = Session {
+        revoked_at: Some(now),
+        ..session
+    };
+    assert!(!revoked.is_recent_login(max_age, now));
+}
+
+#[tokio::test]
+async fn test_export_archive_contents() -> Result<()> {
+    let state = test_state()?;
+    let user_id = Uuid::new_v4();
+
+    let entries = export_entries(user_id, &state.db).await?;
+    let mut archive = ZipArchive::new(std::io::Cursor::new(build_zip(&entries)?))?;
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort_unstable();
+    assert_eq!(names, ["messages.json", "profile.json", "sessions.json"]);
+
+    let mut profile = String::new();
+    archive
+        .by_name("profile.json")?
+        .read_to_string(&mut profile)?;
+    let profile: serde_json::Value = serde_json::from_str(&profile)?;
+    assert_eq!(profile["id"], user_id.to_string());
+    assert!(profile.get("password_hash").is_none());
+
+    let mut messages = String::new();
+    archive
+        .by_name("messages.json")?
+        .read_to_string(&mut messages)?;
+    let messages: Vec<serde_json::Value> = serde_json::from_str(&messages)?;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["sender_id"], user_id.to_string());
+
+    // Arsip ditulis ke direktori ekspor dengan nama ID ekspor
+    let dir = std::env::temp_dir().join(format!("chat-export-test-{}", Uuid::new_v4()));
+    let export = DataExport::new(user_id);
+    let path = write_export(&export, &dir, &state.db).await?;
+    assert_eq!(path, dir.join(format!("{}.zip", export.id)));
+    assert_eq!(ZipArchive::new(std::fs::File::open(&path)?)?.len(), 3);
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_endpoint_queues_background_job() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("leaving_user").await?;
+
+    let response = export_data(Extension(state), AuthUser(user.clone())).await?;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    let body: serde_json::Value = serde_json::from_slice(&body)?;
+    assert_eq!(body["status"], "pending");
+
+    let pending = DataExport::new(user.id);
+    assert!(pending.is_in_progress());
+    assert!(!pending.is_downloadable());
+
+    let ready = DataExport {
+        status: ExportStatus::Ready,
+        file_path: Some("exports/archive.zip".to_string()),
+        expires_at: Some(Utc::now() + Duration::hours(1)),
+        ..pending.clone()
+    };
+    assert!(ready.is_downloadable());
+    assert!(!ready.is_in_progress());
+
+    let expired = DataExport {
+        expires_at: Some(Utc::now() - Duration::seconds(1)),
+        ..ready
+    };
+    assert!(!expired.is_downloadable());
+
+    Ok(())
+}
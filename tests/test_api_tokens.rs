@@ -0,0 +1,208 @@
+mod common;
+
+use anyhow::Result;
+use axum::extract::Path;
+use axum::{Extension, Json, http::StatusCode};
+use backend::config::jwt::{generate_session_token, validate_token};
+use backend::handlers::api_token::{create_token, revoke_token};
+use backend::handlers::bot::create_bot;
+use backend::middleware::auth::{
+    AuthUser, Credential, TokenAccess, authenticate, authorize_api_token, token_access,
+};
+use backend::models::api_token::{
+    API_TOKEN_PREFIX, CreateApiTokenRequest, PersonalAccessToken, TokenScope,
+};
+use backend::models::errors::AppError;
+use backend::models::role::Role;
+use backend::models::user::CreateBotRequest;
+use chrono::{Duration, Utc};
+use common::{test_state, test_user};
+use http::Method;
+use uuid::Uuid;
+
+#[test]
+fn test_token_generation_and_validity() {
+    let user_id = Uuid::new_v4();
+    let (token, api_token) = PersonalAccessToken::new(
+        user_id,
+        user_id,
+        "  deploy bot  ",
+        &[
+            TokenScope::MessagesWrite,
+            TokenScope::MessagesRead,
+            TokenScope::MessagesWrite,
+        ],
+        Some(30),
+    );
+
+    // Hanya hash yang disimpan, dan scope duplikat digabung
+    assert!(token.starts_with(API_TOKEN_PREFIX));
+    assert_ne!(api_token.token_hash, token);
+    assert_eq!(api_token.name, "deploy bot");
+    assert_eq!(api_token.scopes, "messages:read messages:write");
+    assert!(api_token.has_scope(TokenScope::MessagesRead));
+    assert!(!api_token.has_scope(TokenScope::Presence));
+    assert!(api_token.is_active());
+
+    let expired = PersonalAccessToken {
+        expires_at: Some(Utc::now() - Duration::seconds(1)),
+        ..api_token.clone()
+    };
+    assert!(!expired.is_active());
+    let revoked = PersonalAccessToken {
+        revoked_at: Some(Utc::now()),
+        ..api_token.clone()
+    };
+    assert!(!revoked.is_active());
+
+    // `last_used_at` tidak ditulis ulang di setiap request
+    let now = Utc::now();
+    assert!(api_token.needs_touch(now));
+    let recently_used = PersonalAccessToken {
+        last_used_at: Some(now - Duration::seconds(10)),
+        ..api_token
+    };
+    assert!(!recently_used.needs_touch(now));
+    assert!(recently_used.needs_touch(now + Duration::minutes(5)));
+
+    let invalid = CreateApiTokenRequest {
+        name: " ".to_string(),
+        scopes: Vec::new(),
+        expires_in_days: Some(0),
+        bot_id: None,
+    }
+    .validate();
+    assert!(invalid.has("name", "required"));
+    assert!(invalid.has("scopes", "required"));
+    assert!(invalid.has("expires_in_days", "out_of_range"));
+}
+
+#[tokio::test]
+async fn test_scope_enforcement() -> Result<()> {
+    assert_eq!(
+        token_access(&Method::POST, "/messages"),
+        TokenAccess::Scope(TokenScope::MessagesWrite)
+    );
+    assert_eq!(
+        token_access(&Method::GET, "/messages/{receiver_id}"),
+        TokenAccess::Scope(TokenScope::MessagesRead)
+    );
+    assert_eq!(
+        token_access(&Method::GET, "/users/online"),
+        TokenAccess::Scope(TokenScope::Presence)
+    );
+    assert_eq!(token_access(&Method::GET, "/users/me"), TokenAccess::Any);
+    assert_eq!(
+        token_access(&Method::POST, "/users/me/tokens"),
+        TokenAccess::SessionOnly
+    );
+
+    let user_id = Uuid::new_v4();
+    let (_, reader) = PersonalAccessToken::new(
+        user_id,
+        user_id,
+        "reader",
+        &[TokenScope::MessagesRead],
+        None,
+    );
+    assert!(authorize_api_token(&reader, &Method::GET, "/messages/public").is_ok());
+    assert!(matches!(
+        authorize_api_token(&reader, &Method::POST, "/messages"),
+        Err(AppError::Forbidden(_))
+    ));
+    assert!(matches!(
+        authorize_api_token(&reader, &Method::GET, "/admin/users"),
+        Err(AppError::Forbidden(_))
+    ));
+
+    // JWT sesi memiliki semua scope; token mendapat ID kredensial sendiri untuk WebSocket
+    let claims = validate_token(&generate_session_token(
+        user_id,
+        Uuid::new_v4(),
+        Role::User,
+    )?)?;
+    let session = Credential::Session(claims.clone());
+    assert!(session.allows(TokenScope::Presence));
+    assert_eq!(session.id(), claims.jti);
+    let api = Credential::ApiToken(reader.clone());
+    assert!(!api.allows(TokenScope::MessagesWrite));
+    assert_eq!(api.id(), PersonalAccessToken::credential_id(reader.id));
+    assert_eq!(api.session_id(), None);
+
+    let state = test_state()?;
+    let unknown = authenticate(&format!("{API_TOKEN_PREFIX}unknown"), &state.db).await;
+    assert!(matches!(unknown, Err(AppError::Auth(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_token_and_bot_endpoints() -> Result<()> {
+    let state = test_state()?;
+    let owner = test_user("token_owner").await?;
+
+    let (status, Json(created)) = create_token(
+        Extension(state.clone()),
+        AuthUser(owner.clone()),
+        Json(CreateApiTokenRequest {
+            name: "script".to_string(),
+            scopes: vec![TokenScope::MessagesRead, TokenScope::Presence],
+            expires_in_days: Some(90),
+            bot_id: None,
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(created.token.starts_with(API_TOKEN_PREFIX));
+    assert_eq!(created.details.user_id, owner.id);
+    assert_eq!(
+        created.details.scopes,
+        vec![TokenScope::MessagesRead, TokenScope::Presence]
+    );
+
+    // Token hanya bisa diterbitkan untuk bot milik sendiri
+    let not_a_bot = create_token(
+        Extension(state.clone()),
+        AuthUser(owner.clone()),
+        Json(CreateApiTokenRequest {
+            name: "bot".to_string(),
+            scopes: vec![TokenScope::MessagesWrite],
+            expires_in_days: None,
+            bot_id: Some(Uuid::new_v4()),
+        }),
+    )
+    .await;
+    assert!(matches!(not_a_bot, Err(AppError::NotFound(_))));
+
+    let missing = revoke_token(
+        Extension(state.clone()),
+        AuthUser(owner.clone()),
+        Path(Uuid::new_v4()),
+    )
+    .await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+
+    let (status, Json(bot)) = create_bot(
+        Extension(state.clone()),
+        AuthUser(owner.clone()),
+        Json(CreateBotRequest {
+            username: " deploy_bot ".to_string(),
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(bot.username, "deploy_bot");
+    assert!(bot.is_bot);
+
+    let invalid = create_bot(
+        Extension(state),
+        AuthUser(owner),
+        Json(CreateBotRequest {
+            username: "x".to_string(),
+        }),
+    )
+    .await;
+    assert!(matches!(invalid, Err(AppError::InvalidFields(e)) if e.has("username", "too_short")));
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use backend::config::jwt::{Claims, generate_token, validate_token};
+use backend::models::role::Role;
 use backend::models::user::{RegisterRequest, User};
 use chrono::{Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
@@ -41,6 +42,9 @@ async fn test_jwt_token_expiration() -> Result<()> {
         exp: expired_time.timestamp(),
         iat: (expired_time - Duration::hours(1)).timestamp(),
         iss: "test_chat_app".to_string(),
+        jti: Uuid::new_v4().to_string(),
+        sid: None,
+        role: Role::User,
     };
     // Menandatangani token
     let token = jsonwebtoken::encode(&header, &claims, &EncodingKey::from_secret(secret))?;
@@ -0,0 +1,112 @@
+mod common;
+
+use anyhow::Result;
+use axum::extract::Path;
+use axum::{Extension, http::StatusCode};
+use backend::handlers::block::{block_user, list_mutes, mute_user, unblock_user};
+use backend::handlers::message::check_direct_message;
+use backend::handlers::websocket::WebSocketMessage;
+use backend::middleware::auth::AuthUser;
+use backend::models::block::{BlockKind, BlockList, UserBlock};
+use backend::models::errors::AppError;
+use common::{test_state, test_user};
+use uuid::Uuid;
+
+#[test]
+fn test_block_list_filters_broadcast_recipients() {
+    let sender = Uuid::new_v4();
+    let blocker = Uuid::new_v4();
+    let muter = Uuid::new_v4();
+    let bystander = Uuid::new_v4();
+
+    let list = BlockList::from_blocks(&[
+        UserBlock::new(blocker, sender, BlockKind::Block),
+        UserBlock::new(muter, sender, BlockKind::Mute),
+    ]);
+
+    assert!(list.hides_from(blocker));
+    assert!(!list.silences_for(blocker));
+    assert!(!list.hides_from(muter));
+    assert!(list.silences_for(muter));
+    assert!(!list.hides_from(bystander));
+    assert!(!list.silences_for(bystander));
+}
+
+#[tokio::test]
+async fn test_block_and_mute_endpoints() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("blocking_user").await?;
+    let target = Uuid::new_v4();
+
+    let own = block_user(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(user.id),
+    )
+    .await;
+    assert!(matches!(own, Err(AppError::Validation(_))));
+
+    let status = block_user(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(target),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let status = mute_user(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(target),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let missing = unblock_user(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(Uuid::new_v4()),
+    )
+    .await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+
+    let mutes = list_mutes(Extension(state), AuthUser(user)).await?;
+    assert!(mutes.0.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_direct_message_check_and_silent_frames() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("blocking_user").await?;
+    let receiver_id = Uuid::new_v4();
+
+    let target = check_direct_message(&user, receiver_id, &state.db).await?;
+    assert_eq!(target.receiver.id, receiver_id);
+    assert!(!target.muted);
+
+    // Frame dari klien tidak perlu membawa `silent`
+    let incoming: WebSocketMessage =
+        serde_json::from_str(r#"{"type":"Text","data":{"content":"hai","receiver_id":null}}"#)?;
+    assert!(matches!(
+        incoming,
+        WebSocketMessage::Text { silent: false, .. }
+    ));
+
+    let loud = serde_json::to_value(WebSocketMessage::Text {
+        content: "hai".to_string(),
+        receiver_id: None,
+        silent: false,
+    })?;
+    assert!(loud["data"].get("silent").is_none());
+
+    let silent = serde_json::to_value(WebSocketMessage::Text {
+        content: "hai".to_string(),
+        receiver_id: None,
+        silent: true,
+    })?;
+    assert_eq!(silent["data"]["silent"], true);
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use anyhow::Result;
+use axum::extract::Path;
+use axum::{Extension, http::StatusCode};
+use backend::handlers::contact::{
+    accept_contact_request, decline_contact_request, list_contact_requests, remove_contact,
+    send_contact_request,
+};
+use backend::middleware::auth::AuthUser;
+use backend::models::block::DirectMessageAccess;
+use backend::models::contact::{ContactStatus, Privacy};
+use backend::models::errors::AppError;
+use backend::models::profile::UpdateProfileRequest;
+use common::{test_state, test_user};
+use uuid::Uuid;
+
+#[test]
+fn test_privacy_setting_from_profile_update() -> Result<()> {
+    assert!(Privacy::Everyone.allows(false));
+    assert!(!Privacy::Contacts.allows(false));
+    assert!(Privacy::Contacts.allows(true));
+    assert!(!Privacy::Nobody.allows(true));
+
+    let request: UpdateProfileRequest = serde_json::from_str(r#"{"privacy":"contacts"}"#)?;
+    assert_eq!(request.privacy, Some(Privacy::Contacts));
+    assert!(request.validate().is_empty());
+
+    let invalid = serde_json::from_str::<UpdateProfileRequest>(r#"{"privacy":"friends"}"#);
+    assert!(invalid.is_err());
+
+    // Izin pesan pribadi memakai privasi terbaru penerima; blokir selalu menang
+    let access = DirectMessageAccess {
+        privacy: Privacy::Contacts,
+        is_contact: true,
+        blocked_by_sender: false,
+        blocked_by_receiver: false,
+        muted_by_receiver: false,
+    };
+    assert!(access.allowed());
+    assert!(
+        !DirectMessageAccess {
+            is_contact: false,
+            ..access
+        }
+        .allowed()
+    );
+    assert!(
+        !DirectMessageAccess {
+            privacy: Privacy::Everyone,
+            blocked_by_receiver: true,
+            ..access
+        }
+        .allowed()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_presence_hidden_by_privacy() -> Result<()> {
+    let mut user = test_user("private_user").await?;
+    user.is_online = true;
+    user.privacy = Privacy::Contacts;
+    let stranger = Uuid::new_v4();
+
+    let hidden = user.clone().into_profile_for(stranger, false);
+    assert!(!hidden.is_online);
+    assert!(hidden.last_seen.is_none());
+
+    let contact = user.clone().into_profile_for(stranger, true);
+    assert!(contact.is_online);
+    assert!(contact.last_seen.is_some());
+
+    user.privacy = Privacy::Nobody;
+    assert!(!user.shows_presence_to(stranger, true));
+    assert!(user.shows_presence_to(user.id, false));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_contact_request_endpoints() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("contact_user").await?;
+    let target = Uuid::new_v4();
+
+    let own = send_contact_request(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(user.id),
+    )
+    .await;
+    assert!(matches!(own, Err(AppError::Validation(_))));
+
+    let (status, contact) = send_contact_request(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(target),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(contact.user_id, target);
+    assert_eq!(contact.status, ContactStatus::Pending);
+    assert!(!contact.incoming);
+
+    let accept = accept_contact_request(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(target),
+    )
+    .await;
+    assert!(matches!(accept, Err(AppError::NotFound(_))));
+
+    let decline = decline_contact_request(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(target),
+    )
+    .await;
+    assert!(matches!(decline, Err(AppError::NotFound(_))));
+
+    let removed = remove_contact(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(target),
+    )
+    .await;
+    assert!(matches!(removed, Err(AppError::NotFound(_))));
+
+    let requests = list_contact_requests(Extension(state), AuthUser(user)).await?;
+    assert!(requests.0.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use anyhow::Result;
+use axum::extract::{Path, Query};
+use axum::http::{Method, Uri};
+use axum::{Extension, http::StatusCode};
+use backend::handlers::conversation::{list_conversations, mark_direct_read, mark_public_read};
+use backend::middleware::auth::{AuthUser, TokenAccess, token_access};
+use backend::models::api_token::TokenScope;
+use backend::models::conversation::{
+    ConversationKind, ConversationsQuery, MAX_CONVERSATION_PAGE_SIZE, MESSAGE_PREVIEW_LENGTH,
+    message_preview,
+};
+use common::{test_state, test_user};
+use uuid::Uuid;
+
+#[test]
+fn test_conversation_paging_and_preview() -> Result<()> {
+    let uri: Uri = "/conversations?limit=1000&offset=-1".parse()?;
+    let Query(query) = Query::<ConversationsQuery>::try_from_uri(&uri)?;
+    assert_eq!(query.page(), (MAX_CONVERSATION_PAGE_SIZE, 0));
+    assert_eq!(ConversationsQuery::default().page(), (20, 0));
+
+    assert_eq!(message_preview("halo"), "halo");
+
+    let long = "é".repeat(MESSAGE_PREVIEW_LENGTH + 5);
+    let preview = message_preview(&long);
+    assert_eq!(preview.chars().count(), MESSAGE_PREVIEW_LENGTH + 1);
+    assert!(preview.ends_with('…'));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_conversations() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("conversation_user").await?;
+
+    let conversations = list_conversations(
+        Extension(state),
+        AuthUser(user.clone()),
+        Query(ConversationsQuery::default()),
+    )
+    .await?
+    .0;
+    assert_eq!(conversations.len(), 2);
+
+    let direct = &conversations[0];
+    assert_eq!(direct.kind, ConversationKind::Direct);
+    assert!(direct.partner.is_some());
+    assert_eq!(direct.last_message.sender_id, user.id);
+    assert_eq!(direct.unread_count, 2);
+    assert!(!direct.muted);
+
+    let public = &conversations[1];
+    assert_eq!(public.kind, ConversationKind::Public);
+    assert!(public.partner.is_none());
+
+    let json = serde_json::to_value(&conversations[1])?;
+    assert_eq!(json["kind"], "public");
+    assert!(json["partner"].is_null());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mark_read_endpoints() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("conversation_user").await?;
+
+    let status = mark_direct_read(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Path(Uuid::new_v4()),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let status = mark_public_read(Extension(state), AuthUser(user)).await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    assert_eq!(
+        token_access(&Method::GET, "/conversations"),
+        TokenAccess::Scope(TokenScope::MessagesRead)
+    );
+    assert_eq!(
+        token_access(&Method::POST, "/conversations/{partner_id}/read"),
+        TokenAccess::Scope(TokenScope::MessagesRead)
+    );
+
+    Ok(())
+}
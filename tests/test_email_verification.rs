@@ -0,0 +1,156 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{Extension, Json};
+use backend::handlers::auth::register;
+use backend::handlers::message::ensure_can_post_public;
+use backend::mail::{Email, Mailer};
+use backend::middleware::client::ClientInfo;
+use backend::models::email_verification::EmailVerificationToken;
+use backend::models::errors::AppError;
+use backend::models::user::{RegisterRequest, User};
+use backend::utils::email::normalize_email;
+use chrono::Utc;
+use common::test_state_with_mailer;
+use serial_test::serial;
+use uuid::Uuid;
+
+#[derive(Default)]
+struct RecordingMailer {
+    sent: Mutex<Vec<Email>>,
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_email_format_validation() {
+    assert_eq!(
+        normalize_email("  Alice@Example.com "),
+        Some("Alice@Example.com".to_string())
+    );
+    assert!(normalize_email("a.b+tag@mail.example.co.id").is_some());
+
+    for invalid in [
+        "",
+        "alice",
+        "alice@",
+        "@example.com",
+        "alice@example",
+        "alice@@example.com",
+        "alice@exa mple.com",
+        "alice@-example.com",
+        "alice@example..com",
+    ] {
+        assert_eq!(
+            normalize_email(invalid),
+            None,
+            "{invalid} seharusnya ditolak"
+        );
+    }
+
+    let long_local = format!("{}@example.com", "a".repeat(65));
+    assert_eq!(normalize_email(&long_local), None);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_verified_email_required_for_public_posts() -> Result<()> {
+    let user = User::new(RegisterRequest {
+        username: "unverified".to_string(),
+        password: "password123".to_string(),
+        email: Some("unverified@example.com".to_string()),
+    })
+    .await?;
+    assert!(!user.clone().into_response().email_verified);
+
+    // Tanpa konfigurasi, pengguna yang belum terverifikasi tetap boleh posting
+    assert!(ensure_can_post_public(&user).is_ok());
+
+    unsafe { std::env::set_var("REQUIRE_VERIFIED_EMAIL", "true") };
+    let forbidden = ensure_can_post_public(&user);
+
+    let verified = User {
+        email_verified_at: Some(Utc::now()),
+        ..user
+    };
+    let allowed = ensure_can_post_public(&verified);
+    unsafe { std::env::remove_var("REQUIRE_VERIFIED_EMAIL") };
+
+    assert!(matches!(forbidden, Err(AppError::Forbidden(_))));
+    assert!(allowed.is_ok());
+    assert!(verified.into_response().email_verified);
+
+    // Token verifikasi terikat ke alamat email saat dibuat
+    let (token, record) = EmailVerificationToken::new(Uuid::new_v4(), "alice@example.com");
+    assert_eq!(record.email, "alice@example.com");
+    assert_ne!(record.token_hash, token);
+    assert!(record.is_usable());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_register_validates_and_sends_verification() -> Result<()> {
+    let mailer = Arc::new(RecordingMailer::default());
+    let state = test_state_with_mailer(mailer.clone())?;
+    let client = || ClientInfo {
+        ip_address: None,
+        user_agent: None,
+    };
+
+    let invalid = register(
+        Extension(state.clone()),
+        client(),
+        Json(RegisterRequest {
+            username: "bob".to_string(),
+            password: "password123".to_string(),
+            email: Some("bob@invalid".to_string()),
+        }),
+    )
+    .await;
+    match invalid {
+        Err(AppError::InvalidFields(errors)) => assert!(errors.has("email", "invalid")),
+        other => panic!(
+            "email tidak valid seharusnya ditolak: {:?}",
+            other.map(|_| ())
+        ),
+    }
+
+    let Json(response) = register(
+        Extension(state),
+        client(),
+        Json(RegisterRequest {
+            username: "bob".to_string(),
+            password: "password123".to_string(),
+            email: Some(" bob@example.com ".to_string()),
+        }),
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+
+    assert_eq!(response.user.email.as_deref(), Some("bob@example.com"));
+    assert!(!response.user.email_verified);
+
+    // Email verifikasi dikirim di background
+    for _ in 0..50 {
+        if !mailer.sent.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let sent = mailer.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to, "bob@example.com");
+    assert!(sent[0].body.contains("token="));
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use anyhow::Result;
+use backend::config::DEFAULT_JWT_SECRET;
+use backend::config::jwt::{Claims, KeyConfig, KeyRing};
+use backend::models::role::Role;
+use chrono::{Duration, Utc};
+use jsonwebtoken::{Algorithm, decode_header};
+use uuid::Uuid;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/jwt");
+
+fn fixture(name: &str) -> String {
+    format!("{}/{}", FIXTURES, name)
+}
+
+fn claims() -> Claims {
+    let now = Utc::now();
+    Claims {
+        sub: Uuid::new_v4().to_string(),
+        iat: now.timestamp(),
+        exp: (now + Duration::minutes(15)).timestamp(),
+        iss: "chat_app".to_string(),
+        jti: Uuid::new_v4().to_string(),
+        sid: None,
+        role: Role::User,
+    }
+}
+
+fn rsa_config(key_id: &str) -> KeyConfig {
+    KeyConfig {
+        algorithm: "RS256".to_string(),
+        private_key_file: fixture(&format!("rsa-{}.pem", key_id)),
+        key_id: key_id.to_string(),
+        public_keys: format!(
+            "2026-01:{}, 2026-10:{}",
+            fixture("rsa-2026-01.pub.pem"),
+            fixture("rsa-2026-10.pub.pem")
+        ),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_rs256_key_rotation() -> Result<()> {
+    // Token ditandatangani dengan kunci lama sebelum rotasi
+    let old_ring = KeyRing::load(&rsa_config("2026-01"))?;
+    let old_token = old_ring.sign(&claims())?;
+    let header = decode_header(&old_token)?;
+    assert_eq!(header.alg, Algorithm::RS256);
+    assert_eq!(header.kid.as_deref(), Some("2026-01"));
+
+    // Setelah rotasi, token baru memakai kid baru dan token lama tetap valid
+    let new_ring = KeyRing::load(&rsa_config("2026-10"))?;
+    let new_token = new_ring.sign(&claims())?;
+    assert_eq!(decode_header(&new_token)?.kid.as_deref(), Some("2026-10"));
+    assert!(new_ring.verify(&old_token).is_ok());
+    assert!(new_ring.verify(&new_token).is_ok());
+
+    // JWKS memuat kedua kunci publik
+    let jwks = serde_json::to_value(new_ring.jwks())?;
+    let keys = jwks["keys"].as_array().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0]["kid"], "2026-01");
+    assert_eq!(keys[0]["kty"], "RSA");
+    assert_eq!(keys[0]["alg"], "RS256");
+    assert_eq!(keys[0]["use"], "sig");
+    assert_eq!(keys[0]["e"], "AQAB");
+    assert_eq!(keys[1]["kid"], "2026-10");
+
+    Ok(())
+}
+
+#[test]
+fn test_eddsa_signing_rejects_other_keys() -> Result<()> {
+    let ring = KeyRing::load(&KeyConfig {
+        algorithm: "EdDSA".to_string(),
+        private_key_file: fixture("ed25519.pem"),
+        key_id: "ed-1".to_string(),
+        public_keys: format!("ed-1:{}", fixture("ed25519.pub.pem")),
+        ..Default::default()
+    })?;
+
+    let token = ring.sign(&claims())?;
+    assert_eq!(decode_header(&token)?.alg, Algorithm::EdDSA);
+    assert!(ring.verify(&token).is_ok());
+
+    let jwks = serde_json::to_value(ring.jwks())?;
+    assert_eq!(jwks["keys"][0]["kty"], "OKP");
+    assert_eq!(jwks["keys"][0]["crv"], "Ed25519");
+    assert_eq!(jwks["keys"][0]["alg"], "EdDSA");
+
+    // Token HS256 dan token dari kid yang tidak dikenal ditolak
+    let hs_ring = KeyRing::load(&KeyConfig {
+        algorithm: "HS256".to_string(),
+        secret: "test-secret".to_string(),
+        key_id: "ed-1".to_string(),
+        ..Default::default()
+    })?;
+    assert!(ring.verify(&hs_ring.sign(&claims())?).is_err());
+    let rsa_token = KeyRing::load(&rsa_config("2026-01"))?.sign(&claims())?;
+    assert!(ring.verify(&rsa_token).is_err());
+
+    // Kunci rahasia HS256 tidak pernah dipublikasikan
+    assert!(hs_ring.jwks().keys.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_key_configuration_refused() {
+    // Secret default ditolak di production
+    let production = KeyConfig {
+        algorithm: "HS256".to_string(),
+        secret: DEFAULT_JWT_SECRET.to_string(),
+        production: true,
+        ..Default::default()
+    };
+    assert!(KeyRing::load(&production).is_err());
+    assert!(
+        KeyRing::load(&KeyConfig {
+            production: false,
+            ..production
+        })
+        .is_ok()
+    );
+
+    // Kunci privat yang tidak cocok dengan kunci publik untuk kid-nya
+    let mismatched = KeyConfig {
+        private_key_file: fixture("rsa-2026-01.pem"),
+        ..rsa_config("2026-10")
+    };
+    assert!(KeyRing::load(&mismatched).is_err());
+
+    // Kunci publik untuk JWT_KEY_ID wajib ada
+    let missing = KeyConfig {
+        public_keys: format!("2026-01:{}", fixture("rsa-2026-01.pub.pem")),
+        ..rsa_config("2026-10")
+    };
+    assert!(KeyRing::load(&missing).is_err());
+
+    // Algoritma yang tidak didukung
+    let unsupported = KeyConfig {
+        algorithm: "none".to_string(),
+        ..rsa_config("2026-01")
+    };
+    assert!(KeyRing::load(&unsupported).is_err());
+}
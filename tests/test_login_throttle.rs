@@ -0,0 +1,131 @@
+mod common;
+
+use anyhow::Result;
+use axum::{Extension, Json};
+use backend::config::login::{DEFAULT_LOGIN_LOCKOUT_DURATION, LoginPolicy};
+use backend::handlers::auth::login;
+use backend::middleware::client::ClientInfo;
+use backend::models::errors::AppError;
+use backend::models::login_throttle::{LockoutScope, LoginLockout, LoginThrottle};
+use backend::models::user::{LoginRequest, verify_dummy_password};
+use chrono::{Duration, Utc};
+use common::test_state;
+use serial_test::serial;
+
+fn throttle(failures: i32, seconds_ago: i64) -> LoginThrottle {
+    LoginThrottle {
+        key: LockoutScope::Username.key("alice"),
+        failures,
+        last_failure_at: Utc::now() - Duration::seconds(seconds_ago),
+        locked_until: None,
+    }
+}
+
+#[test]
+fn test_exponential_backoff_and_lockout() {
+    let policy = LoginPolicy::default();
+    let now = Utc::now();
+
+    // Beberapa kegagalan pertama tidak menimbulkan jeda
+    assert_eq!(throttle(2, 0).backoff(&policy), 0);
+    assert_eq!(throttle(2, 0).retry_after(&policy, now), None);
+
+    // Setelah itu jeda berlipat dua, dibatasi lama penguncian
+    assert_eq!(throttle(3, 0).backoff(&policy), 1);
+    assert_eq!(throttle(4, 0).backoff(&policy), 2);
+    assert_eq!(throttle(7, 0).backoff(&policy), 16);
+    assert_eq!(throttle(100, 0).backoff(&policy), policy.lockout_duration);
+
+    let recent = throttle(6, 1);
+    assert_eq!(recent.retry_after(&policy, now), Some(7));
+    assert_eq!(throttle(6, 10).retry_after(&policy, now), None);
+
+    // Batas kegagalan tercapai: username dikunci sampai locked_until
+    let mut at_limit = throttle(policy.max_failures, 0);
+    assert!(at_limit.should_lock(policy.max_failures, now));
+    assert!(!throttle(policy.max_failures - 1, 0).should_lock(policy.max_failures, now));
+
+    at_limit.locked_until = Some(now + Duration::seconds(policy.lockout_duration));
+    assert!(!at_limit.should_lock(policy.max_failures, now));
+    assert_eq!(
+        at_limit.retry_after(&policy, now),
+        Some(policy.lockout_duration)
+    );
+}
+
+#[test]
+#[serial]
+fn test_login_policy_and_lockout_audit() {
+    unsafe {
+        std::env::set_var("LOGIN_MAX_FAILURES", "5");
+        std::env::set_var("LOGIN_IP_MAX_FAILURES", "0");
+        std::env::set_var("LOGIN_BACKOFF_BASE", "2");
+    }
+    let policy = LoginPolicy::from_env();
+    unsafe {
+        std::env::remove_var("LOGIN_MAX_FAILURES");
+        std::env::remove_var("LOGIN_IP_MAX_FAILURES");
+        std::env::remove_var("LOGIN_BACKOFF_BASE");
+    }
+
+    assert_eq!(policy.max_failures, 5);
+    // Nilai tidak valid kembali ke default
+    assert_eq!(
+        policy.ip_max_failures,
+        LoginPolicy::default().ip_max_failures
+    );
+    assert_eq!(policy.lockout_duration, DEFAULT_LOGIN_LOCKOUT_DURATION);
+    assert_eq!(throttle(4, 0).backoff(&policy), 4);
+    assert_eq!(LockoutScope::Username.max_failures(&policy), 5);
+
+    // Username dihitung tanpa membedakan huruf besar/kecil
+    assert_eq!(
+        LockoutScope::Username.key(" Alice "),
+        LockoutScope::Username.key("alice")
+    );
+    assert_eq!(LockoutScope::Ip.key("10.0.0.1"), "ip:10.0.0.1");
+
+    let client = ClientInfo {
+        ip_address: Some("10.0.0.1".to_string()),
+        user_agent: Some("curl/8.0".to_string()),
+    };
+    let until = Utc::now() + Duration::minutes(15);
+    let lockout = LoginLockout::new(LockoutScope::Ip, "10.0.0.1", 50, until, &client);
+    assert_eq!(lockout.scope, "ip");
+    assert_eq!(lockout.subject, "10.0.0.1");
+    assert_eq!(lockout.ip_address.as_deref(), Some("10.0.0.1"));
+    assert_eq!(lockout.user_agent.as_deref(), Some("curl/8.0"));
+    assert_eq!(lockout.locked_until, until);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_unknown_username_runs_password_check() -> Result<()> {
+    let state = test_state()?;
+
+    assert!(!verify_dummy_password("password123"));
+
+    let started = std::time::Instant::now();
+    let result = login(
+        Extension(state),
+        ClientInfo {
+            ip_address: Some("10.0.0.1".to_string()),
+            user_agent: None,
+        },
+        Json(LoginRequest {
+            username: "nobody".to_string(),
+            password: "password123".to_string(),
+            device_name: None,
+        }),
+    )
+    .await;
+
+    // Pesan yang sama dengan password salah, dan bcrypt tetap dijalankan
+    match result {
+        Err(AppError::Auth(message)) => assert_eq!(message, "Username atau password salah"),
+        other => panic!("login seharusnya gagal: {:?}", other.map(|_| ())),
+    }
+    assert!(started.elapsed() >= std::time::Duration::from_millis(1));
+
+    Ok(())
+}
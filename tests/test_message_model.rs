@@ -32,7 +32,7 @@ async fn test_message_creation() -> Result<()> {
     assert_eq!(message.sender_id, sender.id);
     assert_eq!(message.receiver_id, Some(receiver.id));
     assert_eq!(message.content, "Ini adalah pesan test");
-    assert_eq!(message.is_read, false);
+    assert!(!message.is_read);
 
     Ok(())
 }
@@ -58,7 +58,7 @@ async fn test_public_message() -> Result<()> {
     assert_eq!(message.sender_id, sender.id);
     assert_eq!(message.receiver_id, None);
     assert_eq!(message.content, "Ini adalah pesan publik");
-    assert_eq!(message.is_read, false);
+    assert!(!message.is_read);
 
     Ok(())
 }
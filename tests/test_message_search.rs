@@ -0,0 +1,114 @@
+mod common;
+
+use anyhow::Result;
+use axum::extract::Query;
+use axum::http::Uri;
+use axum::{Extension, Json};
+use backend::handlers::message::search_messages;
+use backend::middleware::auth::AuthUser;
+use backend::models::errors::AppError;
+use backend::models::message_search::{
+    ConversationFilter, HIGHLIGHT_END, HIGHLIGHT_START, MAX_MESSAGE_SEARCH_PAGE_SIZE,
+    MessageSearchQuery, SearchCursor, highlight_snippet,
+};
+use chrono::{TimeZone, Utc};
+use common::{test_state, test_user};
+use uuid::Uuid;
+
+#[test]
+fn test_search_query_from_url() -> Result<()> {
+    let partner = Uuid::new_v4();
+    let uri: Uri = format!(
+        "/messages/search?q=%20rapat%20besok%20&conversation={partner}&from=2026-01-01T00:00:00Z&limit=500"
+    )
+    .parse()?;
+    let Query(query) = Query::<MessageSearchQuery>::try_from_uri(&uri)?;
+
+    assert!(query.validate().is_empty());
+    assert_eq!(query.search_term(), Some("rapat besok"));
+    assert_eq!(
+        query.conversation_filter(),
+        Some(ConversationFilter::Direct(partner))
+    );
+    assert_eq!(query.limit(), MAX_MESSAGE_SEARCH_PAGE_SIZE);
+
+    let public = Query::<MessageSearchQuery>::try_from_uri(
+        &"/messages/search?q=halo&conversation=public".parse()?,
+    )?
+    .0;
+    assert_eq!(
+        public.conversation_filter(),
+        Some(ConversationFilter::Public)
+    );
+
+    let invalid = MessageSearchQuery {
+        q: Some("   ".to_string()),
+        conversation: Some("room-1".to_string()),
+        from: Some(Utc.with_ymd_and_hms(2026, 2, 1, 0, 0, 0).unwrap()),
+        to: Some(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()),
+        cursor: Some("bukan-cursor".to_string()),
+        ..Default::default()
+    }
+    .validate();
+    assert!(invalid.has("q", "required"));
+    assert!(invalid.has("conversation", "invalid"));
+    assert!(invalid.has("to", "invalid"));
+    assert!(invalid.has("cursor", "invalid"));
+
+    // Lampiran belum ada, jadi filternya ditolak, bukan diabaikan diam-diam
+    let attachment = Query::<MessageSearchQuery>::try_from_uri(
+        &"/messages/search?q=halo&attachment=true".parse()?,
+    )?
+    .0;
+    assert!(attachment.validate().has("has_attachment", "unsupported"));
+
+    Ok(())
+}
+
+#[test]
+fn test_cursor_and_snippet_highlighting() {
+    let cursor = SearchCursor {
+        created_at: Utc.with_ymd_and_hms(2026, 10, 19, 8, 30, 0).unwrap(),
+        id: Uuid::new_v4(),
+    };
+    assert_eq!(SearchCursor::decode(&cursor.encode()), Some(cursor));
+    assert_eq!(SearchCursor::decode("!!!"), None);
+
+    let raw = format!("{HIGHLIGHT_START}rapat{HIGHLIGHT_END} <script> & \"kopi\"");
+    assert_eq!(
+        highlight_snippet(&raw),
+        "<mark>rapat</mark> &lt;script&gt; &amp; &quot;kopi&quot;"
+    );
+}
+
+#[tokio::test]
+async fn test_search_messages_endpoint() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("search_user").await?;
+
+    let Json(response) = search_messages(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Query(MessageSearchQuery {
+            q: Some("semua".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await?;
+    assert_eq!(response.results.len(), 1);
+    assert_eq!(
+        response.results[0].snippet,
+        "Halo <mark>semua</mark> &lt;b&gt;!&lt;/b&gt;"
+    );
+    assert!(response.next_cursor.is_none());
+
+    let result = search_messages(
+        Extension(state),
+        AuthUser(user),
+        Query(MessageSearchQuery::default()),
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::InvalidFields(errors)) if errors.has("q", "required")));
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use anyhow::Result;
+use axum::{Extension, Json};
+use backend::handlers::mfa::{enroll_totp, verify_mfa};
+use backend::middleware::auth::AuthUser;
+use backend::middleware::client::ClientInfo;
+use backend::models::errors::AppError;
+use backend::models::mfa::{
+    MfaChallengeResponse, MfaVerifyRequest, RecoveryCode, UserTotp, normalize_recovery_code,
+};
+use backend::models::user::{LoginResponse, RegisterRequest, User};
+use backend::utils::token::hash_token;
+use backend::utils::totp::{code_at, generate_secret, matching_step, otpauth_uri, time_step};
+use chrono::Utc;
+use common::test_state;
+use uuid::Uuid;
+
+/// Secret ASCII "12345678901234567890" dari vektor uji RFC 6238, dalam base32.
+const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+#[test]
+fn test_totp_rfc6238_vectors() {
+    // Enam digit terakhir dari kode 8 digit di RFC 6238 (SHA1)
+    assert_eq!(
+        code_at(RFC_SECRET, time_step(59)).as_deref(),
+        Some("287082")
+    );
+    assert_eq!(
+        code_at(RFC_SECRET, time_step(1111111109)).as_deref(),
+        Some("081804")
+    );
+    assert_eq!(
+        code_at(RFC_SECRET, time_step(1234567890)).as_deref(),
+        Some("005924")
+    );
+
+    // Toleransi satu langkah ke depan dan ke belakang
+    let now = 1234567890;
+    assert_eq!(
+        matching_step(RFC_SECRET, "005924", now + 30),
+        Some(time_step(now))
+    );
+    assert_eq!(matching_step(RFC_SECRET, "005924", now + 90), None);
+    assert_eq!(matching_step(RFC_SECRET, "00592", now), None);
+    assert_eq!(matching_step(RFC_SECRET, "abcdef", now), None);
+
+    let secret = generate_secret();
+    assert_eq!(secret.len(), 32);
+    assert_ne!(secret, generate_secret());
+
+    let uri = otpauth_uri("Chat App", "alice", &secret);
+    assert!(uri.starts_with("otpauth://totp/Chat%20App:alice?"));
+    assert!(uri.contains(&format!("secret={}", secret)));
+    assert!(uri.contains("issuer=Chat%20App"));
+}
+
+#[test]
+fn test_totp_replay_and_recovery_codes() {
+    let now = Utc::now().timestamp();
+    let step = time_step(now);
+    let code = code_at(RFC_SECRET, step).unwrap();
+
+    let mut totp = UserTotp {
+        user_id: Uuid::new_v4(),
+        secret: RFC_SECRET.to_string(),
+        enabled_at: Some(Utc::now()),
+        last_used_step: None,
+        created_at: Utc::now(),
+    };
+    assert_eq!(totp.verify_code(&code, now), Some(step));
+
+    // Kode yang sudah dipakai tidak diterima lagi
+    totp.last_used_step = Some(step);
+    assert_eq!(totp.verify_code(&code, now), None);
+
+    let user_id = Uuid::new_v4();
+    let (codes, records) = RecoveryCode::generate_set(user_id);
+    assert_eq!(codes.len(), 10);
+    assert_eq!(records.len(), 10);
+
+    let mut unique = codes.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), codes.len());
+
+    for (code, record) in codes.iter().zip(&records) {
+        assert_eq!(code.len(), 11);
+        assert_eq!(&code[5..6], "-");
+        assert_eq!(record.user_id, user_id);
+        // Hanya hash yang disimpan, dan format input dinormalisasi
+        assert_eq!(record.code_hash, hash_token(&normalize_recovery_code(code)));
+        assert_eq!(
+            normalize_recovery_code(&code.to_uppercase().replace('-', " ")),
+            normalize_recovery_code(code)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_mfa_enrollment_and_challenge() -> Result<()> {
+    let state = test_state()?;
+    let user = User::new(RegisterRequest {
+        username: "admin".to_string(),
+        password: "password123".to_string(),
+        email: None,
+    })
+    .await?;
+
+    let Json(enrollment) = enroll_totp(Extension(state.clone()), AuthUser(user))
+        .await
+        .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    assert!(enrollment.otpauth_uri.contains(":admin?"));
+    assert!(
+        enrollment
+            .otpauth_uri
+            .contains(&format!("secret={}", enrollment.secret))
+    );
+
+    // Balasan login dengan 2FA aktif tidak memuat token apa pun
+    let challenge = serde_json::to_value(LoginResponse::MfaRequired(MfaChallengeResponse {
+        mfa_required: true,
+        mfa_token: "challenge".to_string(),
+        expires_in: 300,
+    }))?;
+    assert_eq!(challenge["mfa_required"], true);
+    assert_eq!(challenge["mfa_token"], "challenge");
+    assert!(challenge.get("access_token").is_none());
+
+    let result = verify_mfa(
+        Extension(state),
+        ClientInfo::default(),
+        Json(MfaVerifyRequest {
+            mfa_token: "unknown".to_string(),
+            code: "123456".to_string(),
+        }),
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::Auth(_))));
+
+    Ok(())
+}
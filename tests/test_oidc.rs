@@ -0,0 +1,261 @@
+mod common;
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use axum::{Extension, Json, extract::Path};
+use backend::config::jwt::{KeyConfig, KeyRing};
+use backend::config::oidc::{DEFAULT_OIDC_SCOPES, OidcProviderConfig, get_oidc_providers};
+use backend::handlers::oidc::{callback, list_providers};
+use backend::middleware::client::ClientInfo;
+use backend::models::errors::AppError;
+use backend::models::oidc::OidcCallbackRequest;
+use backend::oidc::{IdTokenClaims, OidcProvider, pkce_challenge};
+use chrono::{Duration, Utc};
+use common::test_state;
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use reqwest::Url;
+use serde_json::json;
+use serial_test::serial;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/jwt");
+
+/// Server OIDC tiruan dengan discovery, JWKS, dan token endpoint.
+async fn mock_issuer() -> Result<MockServer> {
+    let server = MockServer::start().await;
+    let ring = KeyRing::load(&KeyConfig {
+        algorithm: "RS256".to_string(),
+        private_key_file: format!("{}/rsa-2026-01.pem", FIXTURES),
+        key_id: "2026-01".to_string(),
+        public_keys: format!("2026-01:{}/rsa-2026-01.pub.pem", FIXTURES),
+        ..Default::default()
+    })?;
+
+    Mock::given(method("GET"))
+        .and(path("/.well-known/openid-configuration"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "issuer": server.uri(),
+            "authorization_endpoint": format!("{}/authorize", server.uri()),
+            "token_endpoint": format!("{}/token", server.uri()),
+            "jwks_uri": format!("{}/jwks", server.uri()),
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/jwks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(ring.jwks()))
+        .mount(&server)
+        .await;
+
+    Ok(server)
+}
+
+fn provider_for(server: &MockServer) -> OidcProvider {
+    OidcProvider::new(OidcProviderConfig {
+        name: "mock".to_string(),
+        issuer: server.uri(),
+        client_id: "chat-app".to_string(),
+        client_secret: Some("s3cret".to_string()),
+        redirect_uri: "http://localhost:3000/sso/callback".to_string(),
+        scopes: DEFAULT_OIDC_SCOPES.to_string(),
+    })
+}
+
+fn id_token(issuer: &str, audience: &str, nonce: &str) -> Result<String> {
+    let key = EncodingKey::from_rsa_pem(&std::fs::read(format!("{}/rsa-2026-01.pem", FIXTURES))?)?;
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some("2026-01".to_string());
+
+    let now = Utc::now();
+    let claims = json!({
+        "iss": issuer,
+        "sub": "248289761001",
+        "aud": audience,
+        "iat": now.timestamp(),
+        "exp": (now + Duration::minutes(5)).timestamp(),
+        "nonce": nonce,
+        "email": "Jane.Doe@Example.com",
+        "email_verified": true,
+        "preferred_username": "jane",
+    });
+
+    Ok(encode(&header, &claims, &key)?)
+}
+
+async fn mount_token(server: &MockServer, code: &str, id_token: String) {
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains(format!("code={}", code)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "provider-access-token",
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "id_token": id_token,
+        })))
+        .mount(server)
+        .await;
+}
+
+#[test]
+#[serial]
+fn test_pkce_and_provider_config() -> Result<()> {
+    // Vektor uji RFC 7636 lampiran B
+    assert_eq!(
+        pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    );
+
+    unsafe {
+        std::env::set_var("OIDC_PROVIDERS", "Corp, broken");
+        std::env::set_var("OIDC_CORP_ISSUER", "https://login.example.com/");
+        std::env::set_var("OIDC_CORP_CLIENT_ID", "chat-app");
+        std::env::set_var("OIDC_CORP_REDIRECT_URI", "http://localhost:3000/sso");
+        std::env::set_var("OIDC_BROKEN_ISSUER", "https://broken.example.com");
+    }
+    let providers = get_oidc_providers();
+    unsafe {
+        for key in [
+            "OIDC_PROVIDERS",
+            "OIDC_CORP_ISSUER",
+            "OIDC_CORP_CLIENT_ID",
+            "OIDC_CORP_REDIRECT_URI",
+            "OIDC_BROKEN_ISSUER",
+        ] {
+            std::env::remove_var(key);
+        }
+    }
+
+    // Penyedia tanpa client id diabaikan
+    assert_eq!(providers.len(), 1);
+    assert_eq!(providers[0].name, "corp");
+    assert_eq!(providers[0].issuer, "https://login.example.com");
+    assert_eq!(providers[0].client_secret, None);
+    assert_eq!(providers[0].scopes, DEFAULT_OIDC_SCOPES);
+
+    // Sebagian penyedia mengirim email_verified sebagai string
+    let claims: IdTokenClaims = serde_json::from_value(json!({
+        "iss": "https://login.example.com",
+        "sub": "42",
+        "email": "a@example.com",
+        "email_verified": "true",
+    }))?;
+    assert!(claims.email_verified);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_authorization_code_flow_with_mock_issuer() -> Result<()> {
+    let server = mock_issuer().await?;
+    let provider = provider_for(&server);
+
+    let request = provider.authorization_request().await?;
+    let url = Url::parse(&request.url)?;
+    assert_eq!(url.path(), "/authorize");
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    assert_eq!(params["response_type"], "code");
+    assert_eq!(params["client_id"], "chat-app");
+    assert_eq!(params["scope"], "openid email profile");
+    assert_eq!(params["state"], request.state);
+    assert_eq!(params["nonce"], request.nonce);
+    assert_eq!(params["code_challenge_method"], "S256");
+    assert_eq!(
+        params["code_challenge"],
+        pkce_challenge(&request.code_verifier)
+    );
+
+    mount_token(
+        &server,
+        "good-code",
+        id_token(&server.uri(), "chat-app", &request.nonce)?,
+    )
+    .await;
+    let claims = provider
+        .exchange_code("good-code", &request.code_verifier, &request.nonce)
+        .await?;
+    assert_eq!(claims.iss, server.uri());
+    assert_eq!(claims.sub, "248289761001");
+    assert_eq!(claims.preferred_username.as_deref(), Some("jane"));
+    assert!(claims.email_verified);
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    let token_request = requests
+        .iter()
+        .find(|r| r.url.path() == "/token")
+        .expect("token endpoint dipanggil");
+    let body = String::from_utf8_lossy(&token_request.body);
+    assert!(body.contains(&format!("code_verifier={}", request.code_verifier)));
+    assert!(body.contains("client_secret=s3cret"));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_id_token_validation_and_callback_errors() -> Result<()> {
+    let server = mock_issuer().await?;
+    let provider = provider_for(&server);
+
+    // Nonce dari login lain ditolak
+    mount_token(
+        &server,
+        "replayed",
+        id_token(&server.uri(), "chat-app", "other-nonce")?,
+    )
+    .await;
+    assert!(
+        provider
+            .exchange_code("replayed", "verifier", "expected-nonce")
+            .await
+            .is_err()
+    );
+
+    // Token untuk client lain ditolak
+    mount_token(
+        &server,
+        "wrong-aud",
+        id_token(&server.uri(), "other-client", "nonce")?,
+    )
+    .await;
+    assert!(
+        provider
+            .exchange_code("wrong-aud", "verifier", "nonce")
+            .await
+            .is_err()
+    );
+
+    // Token dari issuer lain ditolak
+    mount_token(
+        &server,
+        "wrong-iss",
+        id_token("https://evil.example.com", "chat-app", "nonce")?,
+    )
+    .await;
+    assert!(
+        provider
+            .exchange_code("wrong-iss", "verifier", "nonce")
+            .await
+            .is_err()
+    );
+
+    let state = test_state()?;
+    assert!(list_providers().await.0.providers.is_empty());
+
+    let result = callback(
+        Extension(state),
+        Path("unknown".to_string()),
+        ClientInfo::default(),
+        Json(OidcCallbackRequest {
+            code: "code".to_string(),
+            state: "state".to_string(),
+            device_name: None,
+        }),
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::{Extension, Json, http::StatusCode};
+use backend::config::mail::{
+    DEFAULT_PASSWORD_RESET_TTL, MailerKind, SmtpConfig, SmtpSecurity, get_password_reset_ttl,
+};
+use backend::handlers::auth::{forgot_password, reset_password};
+use backend::mail::{Email, Mailer, SmtpMailer};
+use backend::models::errors::AppError;
+use backend::models::password_reset::{
+    ForgotPasswordRequest, PasswordResetToken, ResetPasswordRequest,
+};
+use backend::utils::token::hash_token;
+use chrono::{Duration, Utc};
+use common::test_state_with_mailer;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+/// Mailer yang hanya menyimpan email untuk diperiksa test.
+#[derive(Default)]
+struct RecordingMailer {
+    sent: Mutex<Vec<Email>>,
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
+
+/// SMTP sink minimal: menerima satu pesan dan mengirim isi DATA lewat channel.
+async fn smtp_sink() -> Result<(u16, oneshot::Receiver<String>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let port = listener.local_addr()?.port();
+    let (tx, rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut tx = Some(tx);
+
+        writer.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let command = line.to_ascii_uppercase();
+            if command.starts_with("DATA") {
+                writer.write_all(b"354 End data with .\r\n").await.unwrap();
+                let mut data = String::new();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if line == "." {
+                        break;
+                    }
+                    data.push_str(&line);
+                    data.push('\n');
+                }
+                if let Some(tx) = tx.take() {
+                    let _ = tx.send(data);
+                }
+                writer.write_all(b"250 Queued\r\n").await.unwrap();
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 Bye\r\n").await.unwrap();
+                break;
+            } else {
+                writer.write_all(b"250 OK\r\n").await.unwrap();
+            }
+        }
+    });
+
+    Ok((port, rx))
+}
+
+#[test]
+fn test_reset_token_single_use_and_expiry() {
+    assert_eq!(get_password_reset_ttl(), DEFAULT_PASSWORD_RESET_TTL);
+
+    let user_id = Uuid::new_v4();
+    let (token, record) = PasswordResetToken::new(user_id);
+
+    // Hanya hash token yang disimpan
+    assert_eq!(record.user_id, user_id);
+    assert_ne!(record.token_hash, token);
+    assert_eq!(record.token_hash, hash_token(&token));
+    assert!(record.expires_at > Utc::now() + Duration::minutes(59));
+    assert!(record.is_usable());
+
+    let used = PasswordResetToken {
+        used_at: Some(Utc::now()),
+        ..record.clone()
+    };
+    assert!(!used.is_usable());
+
+    let expired = PasswordResetToken {
+        expires_at: Utc::now() - Duration::seconds(1),
+        ..record
+    };
+    assert!(!expired.is_usable());
+
+    // Token baru untuk pengguna yang sama selalu berbeda
+    let (other_token, _) = PasswordResetToken::new(user_id);
+    assert_ne!(token, other_token);
+}
+
+#[tokio::test]
+async fn test_smtp_mailer_delivers_to_sink() -> Result<()> {
+    assert_eq!("SMTP".parse::<MailerKind>(), Ok(MailerKind::Smtp));
+    assert!("sendmail".parse::<MailerKind>().is_err());
+
+    let (port, received) = smtp_sink().await?;
+    let config = SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        username: String::new(),
+        password: String::new(),
+        security: "none".parse::<SmtpSecurity>().unwrap(),
+    };
+    let mailer = SmtpMailer::new(&config, "Chat App <no-reply@example.com>")?;
+
+    mailer
+        .send(Email {
+            to: "alice@example.com".to_string(),
+            subject: "Reset password".to_string(),
+            body: "Token: abc123".to_string(),
+        })
+        .await?;
+
+    let data = tokio::time::timeout(std::time::Duration::from_secs(5), received).await??;
+    assert!(data.contains("To: alice@example.com"));
+    assert!(data.contains("Subject: Reset password"));
+    assert!(data.contains("Token: abc123"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_password_reset_handlers() -> Result<()> {
+    let mailer = Arc::new(RecordingMailer::default());
+    let state = test_state_with_mailer(mailer.clone())?;
+
+    // Email tidak terdaftar tetap dibalas 202 dan tidak ada email yang dikirim
+    let status = forgot_password(
+        Extension(state.clone()),
+        Json(ForgotPasswordRequest {
+            email: "unknown@example.com".to_string(),
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert!(mailer.sent.lock().unwrap().is_empty());
+
+    let empty = forgot_password(
+        Extension(state.clone()),
+        Json(ForgotPasswordRequest {
+            email: "  ".to_string(),
+        }),
+    )
+    .await;
+    assert!(matches!(empty, Err(AppError::Validation(_))));
+
+    // Token yang tidak dikenal ditolak
+    let result = reset_password(
+        Extension(state),
+        Json(ResetPasswordRequest {
+            token: "not-a-real-token".to_string(),
+            new_password: "new_password123".to_string(),
+        }),
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::Validation(_))));
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use anyhow::Result;
+use backend::config::presence::{
+    DEFAULT_PRESENCE_HEARTBEAT_TTL, DEFAULT_PRESENCE_RECONCILE_INTERVAL,
+    get_presence_heartbeat_ttl, get_presence_node_id, get_presence_reconcile_interval,
+};
+use backend::jobs::presence::{PresenceChanges, presence_changes, reconcile_once};
+use common::test_state;
+use serial_test::serial;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[test]
+#[serial]
+fn test_presence_config() {
+    // Tanpa konfigurasi, server berjalan sebagai node tunggal
+    assert_eq!(
+        get_presence_reconcile_interval(),
+        DEFAULT_PRESENCE_RECONCILE_INTERVAL
+    );
+    assert_eq!(get_presence_heartbeat_ttl(), DEFAULT_PRESENCE_HEARTBEAT_TTL);
+    assert_eq!(get_presence_node_id(), None);
+
+    // PRESENCE_NODE_ID mengaktifkan mode multi-node
+    unsafe { std::env::set_var("PRESENCE_NODE_ID", " node-a ") };
+    assert_eq!(get_presence_node_id(), Some("node-a".to_string()));
+    unsafe { std::env::remove_var("PRESENCE_NODE_ID") };
+}
+
+#[test]
+fn test_presence_changes() {
+    let stale = Uuid::new_v4();
+    let connected = Uuid::new_v4();
+    let reconnected = Uuid::new_v4();
+
+    // Online di database tanpa koneksi direset, yang terhubung tetap online, dan yang
+    // terhubung tetapi tercatat offline dipulihkan
+    assert_eq!(
+        presence_changes(&[stale, connected], &[connected, reconnected]),
+        PresenceChanges {
+            went_offline: vec![stale],
+            came_online: vec![reconnected],
+        }
+    );
+
+    // Sudah sinkron: tidak ada koreksi
+    assert_eq!(
+        presence_changes(&[connected], &[connected]),
+        PresenceChanges::default()
+    );
+    // Restart tanpa koneksi: semua yang tercatat online direset
+    assert_eq!(
+        presence_changes(&[stale, connected], &[]).went_offline,
+        vec![stale, connected]
+    );
+}
+
+#[tokio::test]
+async fn test_reconcile_without_stale_users() -> Result<()> {
+    let state = test_state()?;
+
+    // Mode node tunggal dan multi-node sama-sama tidak mengoreksi apa pun
+    // ketika database dan registry koneksi sudah sinkron
+    assert_eq!(
+        reconcile_once(&state.db, None, Duration::from_secs(180)).await?,
+        0
+    );
+    assert_eq!(
+        reconcile_once(&state.db, Some("node-a"), Duration::from_secs(180)).await?,
+        0
+    );
+
+    Ok(())
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use std::io::Cursor;
+
+use anyhow::Result;
+use axum::body::Bytes;
+use axum::extract::Path;
+use axum::{Extension, Json};
+use backend::handlers::user::{
+    delete_avatar, get_avatar, get_user_profile, update_profile, upload_avatar,
+};
+use backend::handlers::websocket::WebSocketMessage;
+use backend::middleware::auth::AuthUser;
+use backend::models::errors::AppError;
+use backend::models::profile::UpdateProfileRequest;
+use backend::models::user::User;
+use backend::utils::avatar::resize_avatar;
+use backend::utils::validation::is_valid_timezone;
+use common::{test_state, test_user};
+use image::{ImageFormat, RgbImage};
+use uuid::Uuid;
+
+fn png(width: u32, height: u32) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    RgbImage::from_pixel(width, height, image::Rgb([200, 40, 40]))
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+    Ok(bytes)
+}
+
+#[tokio::test]
+async fn test_profile_request_validation_and_merge() -> Result<()> {
+    let invalid = UpdateProfileRequest {
+        display_name: Some("x".repeat(51)),
+        bio: Some("baris\u{0007}".to_string()),
+        timezone: Some("Mars/Olympus".to_string()),
+        pronouns: None,
+        privacy: None,
+    }
+    .validate();
+    assert!(invalid.has("display_name", "too_long"));
+    assert!(invalid.has("bio", "invalid_characters"));
+    assert!(invalid.has("timezone", "invalid"));
+    assert_eq!(invalid.errors().len(), 3);
+
+    assert!(is_valid_timezone("Asia/Jakarta"));
+    assert!(is_valid_timezone("America/Argentina/Buenos_Aires"));
+    assert!(is_valid_timezone("Etc/GMT+7"));
+    assert!(is_valid_timezone("UTC"));
+    assert!(!is_valid_timezone("Asia"));
+    assert!(!is_valid_timezone("Asia/../etc"));
+
+    let user = User {
+        display_name: Some("Lama".to_string()),
+        bio: Some("Bio lama".to_string()),
+        ..test_user("profile_user").await?
+    };
+
+    // Field yang tidak dikirim tetap, string kosong menghapus isinya
+    let request: UpdateProfileRequest =
+        serde_json::from_str(r#"{"display_name":"  Budi  ","bio":"","timezone":"Asia/Jakarta"}"#)?;
+    assert!(request.validate().is_empty());
+    let updated = request.apply(user);
+    assert_eq!(updated.display_name.as_deref(), Some("Budi"));
+    assert_eq!(updated.bio, None);
+    assert_eq!(updated.timezone.as_deref(), Some("Asia/Jakarta"));
+    assert_eq!(updated.pronouns, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_profile_and_public_view() -> Result<()> {
+    let state = test_state()?;
+    let user = User {
+        email: Some("profile@example.com".to_string()),
+        ..test_user("profile_user").await?
+    };
+
+    let Json(response) = update_profile(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Json(UpdateProfileRequest {
+            display_name: Some("Profil Saya".to_string()),
+            pronouns: Some("dia".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await?;
+    assert_eq!(response.display_name.as_deref(), Some("Profil Saya"));
+    assert_eq!(response.pronouns.as_deref(), Some("dia"));
+    assert_eq!(response.email.as_deref(), Some("profile@example.com"));
+
+    let invalid = update_profile(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Json(UpdateProfileRequest {
+            timezone: Some("Jakarta".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await;
+    assert!(
+        matches!(invalid, Err(AppError::InvalidFields(errors)) if errors.has("timezone", "invalid"))
+    );
+
+    // Profil publik tidak membawa email
+    let other_id = Uuid::new_v4();
+    let Json(profile) = get_user_profile(Extension(state), AuthUser(user), Path(other_id)).await?;
+    assert_eq!(profile.id, other_id);
+    let json = serde_json::to_value(&profile)?;
+    assert!(json.get("email").is_none());
+
+    let event = serde_json::to_value(WebSocketMessage::UserUpdated { user: profile })?;
+    assert_eq!(event["type"], "UserUpdated");
+    assert_eq!(event["data"]["user"]["id"], other_id.to_string());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_avatar_is_resized_to_square_png() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("profile_user").await?;
+
+    let resized = resize_avatar(&png(400, 200)?, 128)?;
+    let decoded = image::load_from_memory_with_format(&resized, ImageFormat::Png)?;
+    assert_eq!((decoded.width(), decoded.height()), (128, 128));
+
+    assert!(resize_avatar(b"bukan gambar", 128).is_err());
+
+    let Json(response) = upload_avatar(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Bytes::from(png(64, 64)?),
+    )
+    .await?;
+    let avatar_url = response.avatar_url.expect("avatar_url harus terisi");
+    assert!(avatar_url.starts_with(&format!("/users/{}/avatar?v=", user.id)));
+
+    let invalid = upload_avatar(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Bytes::from_static(b"GIF89a rusak"),
+    )
+    .await;
+    assert!(matches!(invalid, Err(AppError::Validation(_))));
+
+    let missing = get_avatar(Extension(state.clone()), Path(user.id)).await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+    let deleted = delete_avatar(Extension(state), AuthUser(user)).await;
+    assert!(matches!(deleted, Err(AppError::NotFound(_))));
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use anyhow::Result;
+use axum::extract::{FromRequestParts, Path, Query};
+use axum::{Extension, Json, http::StatusCode};
+use backend::config::jwt::{generate_session_token, validate_token};
+use backend::handlers::admin::{ban_user, delete_message, list_users, update_role};
+use backend::middleware::auth::{Admin, AuthUser, Moderator, RequireRole, ensure_not_banned};
+use backend::models::admin::{
+    BanUserRequest, ListUsersQuery, MAX_ADMIN_PAGE_SIZE, UpdateRoleRequest,
+};
+use backend::models::errors::AppError;
+use backend::models::role::Role;
+use backend::models::user::{RegisterRequest, User};
+use chrono::Utc;
+use common::test_state;
+use http::Request;
+use uuid::Uuid;
+
+async fn user_with_role(role: Role) -> Result<User> {
+    let user = User::new(RegisterRequest {
+        username: format!("{}_user", role),
+        password: "password123".to_string(),
+        email: None,
+    })
+    .await?;
+
+    Ok(User { role, ..user })
+}
+
+/// Jalankan extractor seperti setelah `auth_middleware` menyisipkan pengguna.
+async fn extract<T>(user: Option<User>) -> Result<T, AppError>
+where
+    T: FromRequestParts<(), Rejection = AppError>,
+{
+    let mut builder = Request::builder();
+    if let Some(user) = user {
+        builder = builder.extension(user);
+    }
+    let (mut parts, _) = builder.body(()).unwrap().into_parts();
+
+    T::from_request_parts(&mut parts, &()).await
+}
+
+#[test]
+fn test_roles_and_token_claims() -> Result<()> {
+    assert!(Role::Admin.satisfies(Role::Moderator));
+    assert!(Role::Moderator.satisfies(Role::Moderator));
+    assert!(!Role::User.satisfies(Role::Moderator));
+    assert_eq!(" Admin ".parse::<Role>(), Ok(Role::Admin));
+    assert!("superuser".parse::<Role>().is_err());
+    assert_eq!(serde_json::to_value(Role::Moderator)?, "moderator");
+
+    // Peran ikut masuk ke access token
+    let claims = validate_token(&generate_session_token(
+        Uuid::new_v4(),
+        Uuid::new_v4(),
+        Role::Admin,
+    )?)?;
+    assert_eq!(claims.role, Role::Admin);
+
+    // Token lama tanpa klaim peran dianggap pengguna biasa
+    let legacy: backend::config::jwt::Claims = serde_json::from_value(serde_json::json!({
+        "sub": Uuid::new_v4().to_string(),
+        "iat": 0,
+        "exp": 0,
+        "iss": "chat_app",
+    }))?;
+    assert_eq!(legacy.role, Role::User);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_require_role_extractor() -> Result<()> {
+    let moderator = user_with_role(Role::Moderator).await?;
+
+    let allowed = extract::<RequireRole<Moderator>>(Some(moderator.clone())).await;
+    assert_eq!(allowed.map(|r| r.0.id).ok(), Some(moderator.id));
+
+    let denied = extract::<RequireRole<Admin>>(Some(moderator.clone())).await;
+    assert!(matches!(denied, Err(AppError::Forbidden(_))));
+
+    let admin = user_with_role(Role::Admin).await?;
+    assert!(extract::<RequireRole<Moderator>>(Some(admin)).await.is_ok());
+
+    let anonymous = extract::<RequireRole<Moderator>>(None).await;
+    assert!(matches!(anonymous, Err(AppError::Auth(_))));
+    assert!(extract::<AuthUser>(Some(moderator.clone())).await.is_ok());
+
+    // Pengguna yang diblokir ditolak meskipun tokennya masih berlaku
+    assert!(ensure_not_banned(&moderator).is_ok());
+    let banned = User {
+        banned_at: Some(Utc::now()),
+        ..moderator
+    };
+    assert!(matches!(
+        ensure_not_banned(&banned),
+        Err(AppError::Forbidden(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_admin_endpoints() -> Result<()> {
+    let state = test_state()?;
+    let admin = user_with_role(Role::Admin).await?;
+    let require_admin = || async { extract::<RequireRole<Admin>>(Some(admin.clone())).await };
+
+    let query = ListUsersQuery {
+        limit: Some(10_000),
+        offset: Some(-5),
+    };
+    assert_eq!(query.page(), (MAX_ADMIN_PAGE_SIZE, 0));
+    let Json(users) = list_users(
+        Extension(state.clone()),
+        require_admin().await?,
+        Query(query),
+    )
+    .await?;
+    assert!(users.is_empty());
+
+    // Admin tidak bisa menurunkan perannya sendiri
+    let demote_self = update_role(
+        Extension(state.clone()),
+        require_admin().await?,
+        Path(admin.id),
+        Json(UpdateRoleRequest { role: Role::User }),
+    )
+    .await;
+    assert!(matches!(demote_self, Err(AppError::Forbidden(_))));
+
+    let target = Uuid::new_v4();
+    let Json(promoted) = update_role(
+        Extension(state.clone()),
+        require_admin().await?,
+        Path(target),
+        Json(UpdateRoleRequest {
+            role: Role::Moderator,
+        }),
+    )
+    .await?;
+    assert_eq!(promoted.id, target);
+    assert_eq!(promoted.role, Role::Moderator);
+
+    let status = ban_user(
+        Extension(state.clone()),
+        require_admin().await?,
+        Path(target),
+        Json(BanUserRequest {
+            reason: Some("spam".to_string()),
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let ban_self = ban_user(
+        Extension(state.clone()),
+        require_admin().await?,
+        Path(admin.id),
+        Json(BanUserRequest::default()),
+    )
+    .await;
+    assert!(matches!(ban_self, Err(AppError::Forbidden(_))));
+
+    let moderator = user_with_role(Role::Moderator).await?;
+    let missing = delete_message(
+        Extension(state),
+        extract::<RequireRole<Moderator>>(Some(moderator)).await?,
+        Path(Uuid::new_v4()),
+    )
+    .await;
+    assert!(matches!(missing, Err(AppError::NotFound(_))));
+
+    Ok(())
+}
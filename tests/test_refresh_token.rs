@@ -0,0 +1,51 @@
+use anyhow::Result;
+use backend::models::refresh_token::RefreshToken;
+use backend::utils::token::{generate_opaque_token, hash_token};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+#[test]
+fn test_opaque_token_hashing() {
+    let token = generate_opaque_token();
+    // 32 byte acak dalam bentuk hex
+    assert_eq!(token.len(), 64);
+    assert_ne!(token, generate_opaque_token());
+    // Hash deterministik dan tidak sama dengan token mentah
+    assert_eq!(hash_token(&token), hash_token(&token));
+    assert_ne!(hash_token(&token), token);
+}
+
+#[tokio::test]
+async fn test_refresh_token_rotation_family() -> Result<()> {
+    let user_id = Uuid::new_v4();
+
+    // Login memulai keluarga rotasi baru
+    let (raw, first) = RefreshToken::new(user_id, None);
+    assert_eq!(first.user_id, user_id);
+    assert_eq!(first.token_hash, hash_token(&raw));
+    assert!(first.expires_at > Utc::now());
+    assert!(!first.is_spent());
+    assert!(!first.is_expired());
+
+    // Rotasi mempertahankan keluarga yang sama dengan token berbeda
+    let (rotated_raw, rotated) = RefreshToken::new(user_id, Some(first.family_id));
+    assert_eq!(rotated.family_id, first.family_id);
+    assert_ne!(rotated_raw, raw);
+
+    Ok(())
+}
+
+#[test]
+fn test_refresh_token_spent_and_expired() {
+    let (_, mut token) = RefreshToken::new(Uuid::new_v4(), None);
+
+    token.rotated_at = Some(Utc::now());
+    assert!(token.is_spent());
+
+    token.rotated_at = None;
+    token.revoked_at = Some(Utc::now());
+    assert!(token.is_spent());
+
+    token.expires_at = Utc::now() - Duration::seconds(1);
+    assert!(token.is_expired());
+}
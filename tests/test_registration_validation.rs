@@ -0,0 +1,148 @@
+mod common;
+
+use anyhow::Result;
+use axum::{Extension, Json, response::IntoResponse};
+use backend::config::password::{DEFAULT_PASSWORD_MIN_LENGTH, PasswordPolicy};
+use backend::handlers::auth::register;
+use backend::middleware::client::ClientInfo;
+use backend::models::errors::AppError;
+use backend::models::user::RegisterRequest;
+use backend::utils::validation::{BreachedPasswords, validate_password, validate_username};
+use common::test_state;
+use serial_test::serial;
+
+const BREACHED_FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/breached-passwords.txt"
+);
+
+fn code_of(result: Result<(), backend::models::errors::FieldError>) -> Option<&'static str> {
+    result.err().map(|e| e.code)
+}
+
+#[test]
+fn test_username_rules() {
+    for valid in [
+        "bob",
+        "alice_01",
+        "jane.doe",
+        "x-men",
+        "A1234567890123456789012345678901",
+    ] {
+        assert!(validate_username(valid).is_ok(), "{valid} seharusnya valid");
+    }
+
+    assert_eq!(code_of(validate_username("")), Some("required"));
+    assert_eq!(code_of(validate_username("ab")), Some("too_short"));
+    assert_eq!(
+        code_of(validate_username(&"a".repeat(33))),
+        Some("too_long")
+    );
+    assert_eq!(
+        code_of(validate_username("bob smith")),
+        Some("invalid_characters")
+    );
+    assert_eq!(
+        code_of(validate_username("_bob")),
+        Some("invalid_characters")
+    );
+    assert_eq!(
+        code_of(validate_username("bób")),
+        Some("invalid_characters")
+    );
+
+    // Nama yang dicadangkan ditolak tanpa membedakan huruf besar/kecil
+    assert_eq!(code_of(validate_username("admin")), Some("reserved"));
+    assert_eq!(code_of(validate_username("Admin")), Some("reserved"));
+    assert_eq!(code_of(validate_username("me")), Some("too_short"));
+    assert_eq!(code_of(validate_username("system")), Some("reserved"));
+}
+
+#[test]
+#[serial]
+fn test_password_policy_and_breached_list() -> Result<()> {
+    unsafe {
+        std::env::set_var("PASSWORD_MIN_LENGTH", "10");
+        std::env::set_var("PASSWORD_BREACHED_LIST_FILE", BREACHED_FIXTURE);
+    }
+    let policy = PasswordPolicy::from_env();
+    unsafe {
+        std::env::remove_var("PASSWORD_MIN_LENGTH");
+        std::env::remove_var("PASSWORD_BREACHED_LIST_FILE");
+    }
+    assert_eq!(policy.min_length, 10);
+    assert_eq!(policy.breached_list_file.as_deref(), Some(BREACHED_FIXTURE));
+    assert_eq!(
+        PasswordPolicy::default().min_length,
+        DEFAULT_PASSWORD_MIN_LENGTH
+    );
+
+    // Baris kosong dan komentar diabaikan, perbandingan tanpa huruf besar/kecil
+    let breached = BreachedPasswords::load(BREACHED_FIXTURE)?;
+    assert_eq!(breached.len(), 4);
+    assert!(breached.contains("qwerty2024"));
+    assert!(!breached.contains("# Contoh daftar password bocor untuk test"));
+    assert!(BreachedPasswords::load("/tidak/ada.txt").is_err());
+
+    let codes = |password: &str| -> Vec<&'static str> {
+        validate_password(password, "alice_01", &policy, &breached)
+            .into_iter()
+            .map(|e| e.code)
+            .collect()
+    };
+    assert_eq!(codes("correct horse battery"), Vec::<&str>::new());
+    assert_eq!(codes(""), vec!["required"]);
+    assert_eq!(codes("short"), vec!["too_short"]);
+    assert_eq!(codes(&"x".repeat(73)), vec!["too_long"]);
+    assert_eq!(codes("Alice_01"), vec!["too_short", "matches_username"]);
+    assert_eq!(codes("PASSWORD123"), vec!["breached"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_register_returns_errors_per_field() -> Result<()> {
+    let state = test_state()?;
+
+    let result = register(
+        Extension(state),
+        ClientInfo::default(),
+        Json(RegisterRequest {
+            username: "root".to_string(),
+            password: "root".to_string(),
+            email: Some("root@localhost".to_string()),
+        }),
+    )
+    .await;
+
+    let Err(error) = result else {
+        panic!("registrasi seharusnya ditolak");
+    };
+    assert!(
+        matches!(&error, AppError::InvalidFields(errors) if errors.has("username", "reserved"))
+    );
+
+    let response = error.into_response();
+    assert_eq!(response.status(), 400);
+    let body: serde_json::Value =
+        serde_json::from_slice(&axum::body::to_bytes(response.into_body(), usize::MAX).await?)?;
+    let errors = body["errors"].as_array().expect("errors per field");
+
+    let fields: Vec<(&str, &str)> = errors
+        .iter()
+        .map(|e| (e["field"].as_str().unwrap(), e["code"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("username", "reserved"),
+            ("password", "too_short"),
+            ("password", "matches_username"),
+            ("email", "invalid"),
+        ]
+    );
+    assert!(errors.iter().all(|e| e["message"].is_string()));
+    assert_eq!(body["status"], "error");
+
+    Ok(())
+}
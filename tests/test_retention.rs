@@ -0,0 +1,122 @@
+mod common;
+
+use anyhow::Result;
+use backend::config::retention::RetentionAction;
+use backend::models::retention::{
+    ConversationKey, LegalHold, LegalHoldRequest, RetentionPolicyRequest, RetentionReport,
+    expired_messages_report, purge_expired_messages,
+};
+use chrono::Utc;
+use common::test_state;
+use uuid::Uuid;
+
+#[test]
+fn test_conversation_key_is_canonical() {
+    let (user1_id, user2_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let key = ConversationKey::direct(user1_id, user2_id);
+    assert_eq!(key, ConversationKey::direct(user2_id, user1_id));
+
+    // Urutan ID di URL tidak berpengaruh; teksnya selalu sama dengan kolom di database
+    let reversed = format!(
+        "direct:{}:{}",
+        user1_id.max(user2_id),
+        user1_id.min(user2_id)
+    );
+    assert_eq!(ConversationKey::parse(&reversed), Some(key));
+    assert_eq!(
+        key.to_string(),
+        format!(
+            "direct:{}:{}",
+            user1_id.min(user2_id),
+            user1_id.max(user2_id)
+        )
+    );
+    assert_eq!(
+        ConversationKey::parse("public"),
+        Some(ConversationKey::Public)
+    );
+
+    assert_eq!(ConversationKey::parse("room-1"), None);
+    assert_eq!(
+        ConversationKey::parse(&format!("direct:{user1_id}:{user1_id}")),
+        None
+    );
+    assert_eq!(ConversationKey::parse(&format!("direct:{user1_id}")), None);
+
+    assert_eq!(" Archive ".parse(), Ok(RetentionAction::Archive));
+    assert_eq!("delete".parse(), Ok(RetentionAction::Delete));
+    assert!("shred".parse::<RetentionAction>().is_err());
+}
+
+#[test]
+fn test_policy_and_legal_hold_validation() {
+    assert!(
+        RetentionPolicyRequest {
+            retention_days: None
+        }
+        .validate()
+        .is_empty()
+    );
+    assert!(
+        RetentionPolicyRequest {
+            retention_days: Some(0)
+        }
+        .validate()
+        .has("retention_days", "out_of_range")
+    );
+
+    let both = LegalHoldRequest {
+        user_id: Some(Uuid::new_v4()),
+        conversation: Some("public".to_string()),
+        reason: "  ".to_string(),
+    }
+    .validate();
+    assert!(both.has("user_id", "invalid"));
+    assert!(both.has("reason", "required"));
+
+    let invalid = LegalHoldRequest {
+        conversation: Some("direct:abc".to_string()),
+        reason: "Perkara 12/2026".to_string(),
+        ..Default::default()
+    }
+    .validate();
+    assert!(invalid.has("conversation", "invalid"));
+
+    let (user1_id, user2_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let request = LegalHoldRequest {
+        conversation: Some(format!("direct:{}:{}", user1_id, user2_id)),
+        reason: " Perkara 12/2026 ".to_string(),
+        ..Default::default()
+    };
+    assert!(request.validate().is_empty());
+
+    let hold = LegalHold::new(request, Uuid::new_v4());
+    assert_eq!(
+        hold.conversation_key,
+        Some(ConversationKey::direct(user1_id, user2_id).to_string())
+    );
+    assert_eq!(hold.reason, "Perkara 12/2026");
+    assert!(hold.released_at.is_none());
+}
+
+#[tokio::test]
+async fn test_dry_run_report_and_purge() -> Result<()> {
+    let state = test_state()?;
+    let db = &state.db;
+    let now = Utc::now();
+
+    // Tanpa default, percakapan tanpa kebijakan sendiri disimpan selamanya
+    assert!(expired_messages_report(None, now, db).await?.is_empty());
+
+    let entries = expired_messages_report(Some(30), now, db).await?;
+    let report = RetentionReport::new(now, Some(30), RetentionAction::Archive, entries);
+    assert_eq!(report.total_expired, 3);
+    assert_eq!(report.total_held, 1);
+    assert_eq!(report.conversations[0].conversation_key, "public");
+    assert!(report.conversations[0].oldest_expired_at < now - chrono::Duration::days(30));
+
+    let purged = purge_expired_messages(Some(30), RetentionAction::Delete, 100, now, db).await?;
+    assert_eq!(purged, 0);
+
+    Ok(())
+}
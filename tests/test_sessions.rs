@@ -0,0 +1,63 @@
+use anyhow::Result;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use backend::config::jwt::{generate_session_token, validate_token};
+use backend::middleware::client::ClientInfo;
+use backend::models::role::Role;
+use backend::models::session::Session;
+use http::{Request, header};
+use std::net::SocketAddr;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_client_info_from_request() -> Result<()> {
+    let addr: SocketAddr = "203.0.113.7:51234".parse()?;
+    let request = Request::builder()
+        .header(header::USER_AGENT, "ChatApp/1.0 (Android)")
+        .extension(ConnectInfo(addr))
+        .body(())?;
+    let (mut parts, _) = request.into_parts();
+
+    let client = ClientInfo::from_request_parts(&mut parts, &())
+        .await
+        .unwrap();
+    assert_eq!(client.ip_address.as_deref(), Some("203.0.113.7"));
+    assert_eq!(client.user_agent.as_deref(), Some("ChatApp/1.0 (Android)"));
+
+    Ok(())
+}
+
+#[test]
+fn test_session_response_marks_current() {
+    let user_id = Uuid::new_v4();
+    let client = ClientInfo {
+        ip_address: Some("127.0.0.1".to_string()),
+        user_agent: None,
+    };
+
+    // Nama perangkat kosong tidak disimpan
+    let session = Session::new(user_id, Some("  ".to_string()), client.clone());
+    assert_eq!(session.device_name, None);
+    assert_eq!(session.user_id, user_id);
+
+    let current_id = session.id;
+    let response = session.into_response(Some(current_id));
+    assert!(response.current);
+    assert_eq!(response.ip_address.as_deref(), Some("127.0.0.1"));
+
+    let other = Session::new(user_id, Some("Laptop".to_string()), client);
+    let response = other.into_response(Some(current_id));
+    assert!(!response.current);
+    assert_eq!(response.device_name.as_deref(), Some("Laptop"));
+}
+
+#[test]
+fn test_session_token_carries_sid() -> Result<()> {
+    let user_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+
+    let claims = validate_token(&generate_session_token(user_id, session_id, Role::User)?)?;
+    assert_eq!(claims.sid, Some(session_id));
+    assert_eq!(claims.user_id()?, user_id);
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use anyhow::Result;
+use axum::extract::ws::close_code;
+use backend::config::jwt::generate_token;
+use backend::config::{DEFAULT_SHUTDOWN_TIMEOUT, get_shutdown_timeout};
+use backend::handlers::websocket::{
+    WS_PROTOCOL, WS_TOKEN_PROTOCOL_PREFIX, connected_user_ids, drain_connections, is_shutting_down,
+};
+use backend::routes::create_routes;
+use common::test_state;
+use futures::StreamExt;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::SEC_WEBSOCKET_PROTOCOL;
+use uuid::Uuid;
+
+#[test]
+fn test_shutdown_timeout_default() {
+    // Tanpa SHUTDOWN_TIMEOUT, nilai default yang dipakai
+    assert_eq!(get_shutdown_timeout(), DEFAULT_SHUTDOWN_TIMEOUT);
+}
+
+#[tokio::test]
+async fn test_drain_closes_connections() -> Result<()> {
+    // Pool lazy: tidak membuka koneksi sampai dipakai
+    let state = test_state()?;
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let app = create_routes(state.clone()).into_make_service_with_connect_info::<SocketAddr>();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let user_id = Uuid::new_v4();
+    let mut request = format!("ws://{addr}/ws").into_client_request()?;
+    request.headers_mut().insert(
+        SEC_WEBSOCKET_PROTOCOL,
+        format!(
+            "{WS_PROTOCOL}, {WS_TOKEN_PROTOCOL_PREFIX}{}",
+            generate_token(user_id)?
+        )
+        .parse()?,
+    );
+    let (mut socket, _) = connect_async(request).await?;
+
+    let registered = Instant::now();
+    while !connected_user_ids().contains(&user_id) {
+        assert!(registered.elapsed() < Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!is_shutting_down());
+
+    // Baca sampai socket selesai supaya close frame server dibalas
+    let reader = tokio::spawn(async move {
+        let mut close = None;
+        while let Some(Ok(message)) = socket.next().await {
+            if let Message::Close(frame) = message {
+                close = frame;
+            }
+        }
+        close
+    });
+
+    let started = Instant::now();
+    let drained = drain_connections(&state.db, Duration::from_secs(5)).await;
+
+    // Koneksi ditutup dengan kode restart dan dihapus dari registry sebelum batas waktu
+    assert_eq!(drained, 1);
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(connected_user_ids().is_empty());
+    let frame = reader.await?.expect("server harus mengirim close frame");
+    assert_eq!(u16::from(frame.code), close_code::RESTART);
+    assert_eq!(frame.reason, "Server sedang restart");
+    // Koneksi baru ditolak setelah shutdown dimulai
+    assert!(is_shutting_down());
+
+    Ok(())
+}
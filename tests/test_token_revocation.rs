@@ -0,0 +1,141 @@
+mod common;
+
+use anyhow::Result;
+use axum::body::Body;
+use axum::http::{Request, StatusCode, header};
+use axum::routing::post;
+use axum::{Extension, Router};
+use backend::config::jwt::{Claims, generate_token, validate_token};
+use backend::handlers::auth::logout;
+use backend::handlers::websocket::{disconnect_token, disconnect_user};
+use backend::middleware::auth::authenticate_token;
+use backend::models::revoked_token::{revocation_cutoff, revoked_by_cutoff};
+use chrono::{DateTime, Duration, Utc};
+use common::{test_state, test_user};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_token_has_unique_jti() -> Result<()> {
+    let user_id = Uuid::new_v4();
+    let first = validate_token(&generate_token(user_id)?)?;
+    let second = validate_token(&generate_token(user_id)?)?;
+
+    // Setiap token punya jti sendiri sehingga bisa dicabut satu per satu
+    assert!(!first.jti.is_empty());
+    assert_ne!(first.jti, second.jti);
+    assert_eq!(first.user_id()?, user_id);
+    assert!(first.expires_at() > first.issued_at());
+
+    // Token yang diterbitkan setelah logout-all, di detik yang sama, tetap berlaku
+    let cutoff = revocation_cutoff(Utc::now());
+    let reissued = validate_token(&generate_token(user_id)?)?;
+    assert!(cutoff <= reissued.issued_at());
+    assert_eq!(cutoff.timestamp_subsec_nanos(), 0);
+    let session_id = Some(Uuid::new_v4());
+    assert!(!revoked_by_cutoff(cutoff, reissued.issued_at(), session_id));
+
+    Ok(())
+}
+
+#[test]
+fn test_cutoff_catches_token_from_same_second() -> Result<()> {
+    // Token diterbitkan lalu logout-all terjadi di detik yang sama
+    let issued_at = DateTime::from_timestamp(1_700_000_000, 0).expect("timestamp valid");
+    let cutoff = revocation_cutoff(issued_at + Duration::milliseconds(400));
+    assert_eq!(cutoff, issued_at);
+
+    // Token tanpa sesi tidak bisa dibedakan dari token baru, jadi ditolak
+    assert!(revoked_by_cutoff(cutoff, issued_at, None));
+    assert!(revoked_by_cutoff(
+        cutoff,
+        issued_at + Duration::seconds(5),
+        None
+    ));
+    // Token dengan sesi dari detik sebelumnya ditolak lewat batas waktu; dari detik yang
+    // sama ditolak lewat pencabutan sesinya
+    let session_id = Some(Uuid::new_v4());
+    assert!(revoked_by_cutoff(
+        cutoff,
+        issued_at - Duration::seconds(1),
+        session_id
+    ));
+    assert!(!revoked_by_cutoff(cutoff, issued_at, session_id));
+
+    Ok(())
+}
+
+#[test]
+fn test_legacy_claims_without_jti() -> Result<()> {
+    // Token yang diterbitkan sebelum ada klaim jti tetap bisa dibaca
+    let claims: Claims = serde_json::from_str(
+        r#"{"sub":"5f0c1e2a-7b7e-4c38-9d8e-2f0f3c0f6a11","iat":0,"exp":1,"iss":"chat_app"}"#,
+    )?;
+    assert!(claims.jti.is_empty());
+
+    // jti kosong tidak boleh menutup koneksi apa pun
+    assert_eq!(disconnect_token(&claims.jti), 0);
+    assert_eq!(disconnect_user(claims.user_id()?), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_authenticate_token() -> Result<()> {
+    let state = test_state()?;
+    let user_id = Uuid::new_v4();
+
+    let (user, claims) = authenticate_token(&generate_token(user_id)?, &state.db).await?;
+    assert_eq!(user.id, user_id);
+    assert_eq!(claims.sub, user_id.to_string());
+
+    // Token rusak ditolak sebelum menyentuh database
+    assert!(
+        authenticate_token("bukan.token.valid", &state.db)
+            .await
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_logout_without_body() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("logout_user").await?;
+    let claims = validate_token(&generate_token(user.id)?)?;
+    let app = Router::new()
+        .route("/auth/logout", post(logout))
+        .layer(Extension(claims))
+        .layer(Extension(user))
+        .layer(Extension(state));
+
+    // Cukup header Authorization, tanpa body maupun Content-Type
+    let response = app
+        .clone()
+        .oneshot(Request::post("/auth/logout").body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::post("/auth/logout")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"refresh_token":"abc"}"#))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // JSON rusak tetap ditolak
+    let response = app
+        .oneshot(
+            Request::post("/auth/logout")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from("{"))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::Extension;
+use axum::extract::Path;
+use backend::handlers::message::{check_direct_message, get_conversation};
+use backend::middleware::auth::AuthUser;
+use backend::models::user_cache::{self, CachedUser, UserCache};
+use common::{test_state, test_user};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_cache_is_bounded_and_expires() -> Result<()> {
+    let first = CachedUser::from(&test_user("first_user").await?);
+    let second = CachedUser::from(&test_user("second_user").await?);
+    let third = CachedUser::from(&test_user("third_user").await?);
+
+    let cache = UserCache::new(2, Duration::from_secs(60));
+    cache.insert(first.clone());
+    cache.insert(second.clone());
+    assert!(cache.get(first.id).is_some());
+
+    // `second` paling lama tidak dipakai, jadi dibuang saat kapasitas penuh
+    cache.insert(third.clone());
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(second.id).is_none());
+    assert_eq!(
+        cache.get(third.id).map(|user| user.username),
+        Some(third.username)
+    );
+
+    cache.invalidate(first.id);
+    assert!(cache.get(first.id).is_none());
+
+    let expired = UserCache::new(2, Duration::ZERO);
+    expired.insert(first.clone());
+    assert!(expired.get(first.id).is_none());
+    assert!(expired.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_direct_message_check_uses_cache() -> Result<()> {
+    let state = test_state()?;
+    let sender = test_user("cache_sender").await?;
+    let receiver = test_user("cached_receiver").await?;
+
+    // Tanpa entri cache, penerima dimuat dari database
+    let target = check_direct_message(&sender, receiver.id, &state.db).await?;
+    assert_eq!(target.receiver.username, "test_user");
+
+    user_cache::store(&receiver);
+    let target = check_direct_message(&sender, receiver.id, &state.db).await?;
+    assert_eq!(target.receiver.username, "cached_receiver");
+
+    user_cache::invalidate(receiver.id);
+    let cached = user_cache::find_cached(receiver.id, &state.db).await?;
+    assert_eq!(
+        cached.map(|user| user.username).as_deref(),
+        Some("test_user")
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_history_includes_usernames() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("history_user").await?;
+    let partner_id = Uuid::new_v4();
+
+    let messages = get_conversation(Extension(state), AuthUser(user.clone()), Path(partner_id))
+        .await?
+        .0;
+
+    assert_eq!(messages.len(), 2);
+    for message in &messages {
+        assert!(!message.sender_username.is_empty());
+        assert!(message.receiver_username.is_some());
+    }
+    assert_eq!(messages[0].sender_id, user.id);
+    assert_eq!(messages[1].receiver_id, Some(user.id));
+
+    Ok(())
+}
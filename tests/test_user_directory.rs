@@ -0,0 +1,70 @@
+mod common;
+
+use anyhow::Result;
+use axum::extract::Query;
+use axum::http::Uri;
+use axum::{Extension, Json};
+use backend::handlers::user::search_users;
+use backend::middleware::auth::AuthUser;
+use backend::models::directory::{MAX_DIRECTORY_PAGE_SIZE, SearchUsersQuery, like_prefix_pattern};
+use backend::models::errors::AppError;
+use backend::models::role::Role;
+use common::{test_state, test_user};
+
+#[test]
+fn test_search_query_from_url() -> Result<()> {
+    let uri: Uri =
+        "/users?query=%20Budi%20&online=true&role=moderator&limit=500&offset=-3".parse()?;
+    let Query(query) = Query::<SearchUsersQuery>::try_from_uri(&uri)?;
+
+    assert_eq!(query.search_term().as_deref(), Some("budi"));
+    assert_eq!(query.online, Some(true));
+    assert_eq!(query.role, Some(Role::Moderator));
+    assert_eq!(query.page(), (MAX_DIRECTORY_PAGE_SIZE, 0));
+
+    let browse = Query::<SearchUsersQuery>::try_from_uri(&"/users?query=++".parse()?)?.0;
+    assert_eq!(browse.search_term(), None);
+    assert_eq!(browse.page(), (20, 0));
+
+    Ok(())
+}
+
+#[test]
+fn test_like_prefix_pattern_escapes_wildcards() {
+    assert_eq!(like_prefix_pattern("budi"), "budi%");
+    assert_eq!(like_prefix_pattern("50%_off"), "50\\%\\_off%");
+    assert_eq!(like_prefix_pattern("a\\b"), "a\\\\b%");
+}
+
+#[tokio::test]
+async fn test_search_users_validates_query() -> Result<()> {
+    let state = test_state()?;
+    let user = test_user("directory_user").await?;
+
+    let Json(users) = search_users(
+        Extension(state.clone()),
+        AuthUser(user.clone()),
+        Query(SearchUsersQuery {
+            query: Some("bud".to_string()),
+            online: Some(true),
+            ..Default::default()
+        }),
+    )
+    .await?;
+    assert!(users.is_empty());
+
+    let result = search_users(
+        Extension(state),
+        AuthUser(user),
+        Query(SearchUsersQuery {
+            query: Some("x".repeat(65)),
+            ..Default::default()
+        }),
+    )
+    .await;
+    assert!(
+        matches!(result, Err(AppError::InvalidFields(errors)) if errors.has("query", "too_long"))
+    );
+
+    Ok(())
+}
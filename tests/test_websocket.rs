@@ -0,0 +1,202 @@
+use backend::config::websocket::{
+    DEFAULT_WS_BUFFER_SIZE, SlowConsumerPolicy, get_slow_consumer_policy, get_ws_buffer_size,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use backend::handlers::websocket::{Delivery, WebSocketMessage, enqueue, protocol_token};
+use http::{HeaderMap, HeaderValue, header};
+use serial_test::serial;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+#[test]
+fn test_slow_consumer_policy_parsing() {
+    // Nilai yang dikenal, tidak peka huruf besar/kecil
+    assert_eq!(
+        "drop".parse::<SlowConsumerPolicy>(),
+        Ok(SlowConsumerPolicy::Drop)
+    );
+    assert_eq!(
+        "Disconnect".parse::<SlowConsumerPolicy>(),
+        Ok(SlowConsumerPolicy::Disconnect)
+    );
+    assert_eq!(
+        " RESUME ".parse::<SlowConsumerPolicy>(),
+        Ok(SlowConsumerPolicy::Resume)
+    );
+    // Nilai yang tidak dikenal ditolak
+    assert!("block".parse::<SlowConsumerPolicy>().is_err());
+}
+
+#[test]
+#[serial]
+fn test_websocket_config_from_env() {
+    unsafe {
+        std::env::set_var("WS_BUFFER_SIZE", "16");
+        std::env::set_var("WS_SLOW_CONSUMER_POLICY", "resume");
+    }
+    assert_eq!(get_ws_buffer_size(), 16);
+    assert_eq!(get_slow_consumer_policy(), SlowConsumerPolicy::Resume);
+
+    // Nilai tidak valid kembali ke default
+    unsafe {
+        std::env::set_var("WS_BUFFER_SIZE", "0");
+        std::env::set_var("WS_SLOW_CONSUMER_POLICY", "unknown");
+    }
+    assert_eq!(get_ws_buffer_size(), DEFAULT_WS_BUFFER_SIZE);
+    assert_eq!(get_slow_consumer_policy(), SlowConsumerPolicy::Drop);
+
+    unsafe {
+        std::env::remove_var("WS_BUFFER_SIZE");
+        std::env::remove_var("WS_SLOW_CONSUMER_POLICY");
+    }
+}
+
+#[test]
+fn test_resume_required_serialization() {
+    let json = serde_json::to_string(&WebSocketMessage::ResumeRequired).unwrap();
+    assert_eq!(json, r#"{"type":"ResumeRequired"}"#);
+}
+
+#[test]
+fn test_full_buffer_drop_and_disconnect_policies() {
+    let user_id = Uuid::new_v4();
+    let needs_resume = AtomicBool::new(false);
+    let frame = || WebSocketMessage::ResumeRequired;
+
+    // drop: frame yang tidak muat dibuang, koneksi tetap jalan
+    let (tx, mut rx) = mpsc::channel(1);
+    assert_eq!(
+        enqueue(
+            user_id,
+            &tx,
+            &needs_resume,
+            SlowConsumerPolicy::Drop,
+            frame()
+        ),
+        Delivery::Sent
+    );
+    assert_eq!(
+        enqueue(
+            user_id,
+            &tx,
+            &needs_resume,
+            SlowConsumerPolicy::Drop,
+            frame()
+        ),
+        Delivery::Dropped
+    );
+    assert!(rx.try_recv().is_ok());
+    assert!(rx.try_recv().is_err());
+    assert_eq!(
+        enqueue(
+            user_id,
+            &tx,
+            &needs_resume,
+            SlowConsumerPolicy::Drop,
+            frame()
+        ),
+        Delivery::Sent
+    );
+
+    // disconnect: koneksi harus ditutup begitu buffer penuh
+    let policy = SlowConsumerPolicy::Disconnect;
+    let (tx, rx) = mpsc::channel(1);
+    assert_eq!(
+        enqueue(user_id, &tx, &needs_resume, policy, frame()),
+        Delivery::Sent
+    );
+    assert_eq!(
+        enqueue(user_id, &tx, &needs_resume, policy, frame()),
+        Delivery::Disconnect
+    );
+    assert!(!needs_resume.load(Ordering::Acquire));
+
+    // Penerima yang sudah ditutup dilaporkan, bukan dianggap penuh
+    drop(rx);
+    assert_eq!(
+        enqueue(user_id, &tx, &needs_resume, policy, frame()),
+        Delivery::Closed
+    );
+}
+
+#[test]
+fn test_full_buffer_resume_policy() {
+    let user_id = Uuid::new_v4();
+    let needs_resume = AtomicBool::new(false);
+    let policy = SlowConsumerPolicy::Resume;
+    let (tx, mut rx) = mpsc::channel(1);
+
+    assert_eq!(
+        enqueue(
+            user_id,
+            &tx,
+            &needs_resume,
+            policy,
+            WebSocketMessage::ResumeRequired
+        ),
+        Delivery::Sent
+    );
+    assert_eq!(
+        enqueue(
+            user_id,
+            &tx,
+            &needs_resume,
+            policy,
+            WebSocketMessage::ResumeRequired
+        ),
+        Delivery::Dropped
+    );
+    assert!(needs_resume.load(Ordering::Acquire));
+
+    // Meski antrean sudah kosong, frame tetap dibuang sampai klien diminta resume
+    assert!(rx.try_recv().is_ok());
+    assert_eq!(
+        enqueue(
+            user_id,
+            &tx,
+            &needs_resume,
+            policy,
+            WebSocketMessage::ResumeRequired
+        ),
+        Delivery::Dropped
+    );
+    assert!(rx.try_recv().is_err());
+
+    needs_resume.store(false, Ordering::Release);
+    assert_eq!(
+        enqueue(
+            user_id,
+            &tx,
+            &needs_resume,
+            policy,
+            WebSocketMessage::ResumeRequired
+        ),
+        Delivery::Sent
+    );
+}
+
+#[test]
+fn test_protocol_token_extraction() {
+    // Token dibawa sebagai entri `bearer.<jwt>` di samping subprotocol chat
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static("chat, bearer.abc.def.ghi"),
+    );
+    assert_eq!(protocol_token(&headers), Some("abc.def.ghi"));
+
+    // Tanpa entri bearer, klien harus mengirim frame Authenticate
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static("chat"),
+    );
+    assert_eq!(protocol_token(&headers), None);
+    assert_eq!(protocol_token(&HeaderMap::new()), None);
+
+    // Frame Authenticate dari klien
+    let frame: WebSocketMessage =
+        serde_json::from_str(r#"{"type":"Authenticate","data":{"token":"abc"}}"#).unwrap();
+    assert!(matches!(frame, WebSocketMessage::Authenticate { token } if token == "abc"));
+}